@@ -0,0 +1,691 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+
+use serde::Serialize;
+use tauri_plugin_http::reqwest;
+
+use crate::audit;
+
+// ===========================================================================
+// Archive Inspection
+// ===========================================================================
+//
+// `inspect_archive(pathOrUrl)` lists what is inside a zip or tar attachment
+// without extracting anything, and flags entries worth a second look:
+// executables and scripts, double extensions (`invoice.pdf.exe`), nested
+// archives, symlinks, paths escaping the extraction directory, encrypted
+// entries and suspicious compression ratios.
+//
+// Zip listings only need the central directory at the end of the file, so
+// for URLs we fetch just the tail with an HTTP range request (falling back
+// to a capped full download if the server ignores ranges). Tar has no index
+// and is read header by header; compressed tarballs (.tar.gz etc.) would
+// need inflating and are reported as unsupported.
+// ===========================================================================
+
+/// Entries returned to the UI; the rest are only counted.
+const MAX_LISTED: usize = 5000;
+/// Cap on bytes fetched for remote archives that can't be range-read.
+const MAX_REMOTE_BYTES: usize = 64 * 1024 * 1024;
+/// End-of-central-directory record plus the largest possible comment.
+const EOCD_SEARCH: u64 = 22 + 65_535;
+/// Cap on a GNU long name or pax record; real ones are a few hundred bytes.
+const MAX_TAR_EXTENDED: u64 = 64 * 1024;
+/// Cap on a single tar entry's size (1 TB); base-256 sizes can claim far
+/// more than any real file.
+const MAX_TAR_ENTRY: u64 = 1 << 40;
+const SUSPICIOUS_RATIO: u64 = 100;
+
+const EXECUTABLE_EXTENSIONS: &[&str] = &[
+    "exe", "scr", "com", "pif", "bat", "cmd", "msi", "msp", "dll", "cpl", "vbs", "vbe", "js",
+    "jse", "wsf", "wsh", "ps1", "psm1", "hta", "lnk", "jar", "reg", "app", "dmg", "pkg", "sh",
+    "command", "appimage", "deb", "rpm", "apk",
+];
+const ARCHIVE_EXTENSIONS: &[&str] = &[
+    "zip", "rar", "7z", "tar", "gz", "tgz", "bz2", "xz", "zst", "iso", "cab",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum EntryFlag {
+    Executable,
+    DoubleExtension,
+    NestedArchive,
+    Symlink,
+    PathTraversal,
+    Encrypted,
+    HighCompressionRatio,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ArchiveEntry {
+    pub path: String,
+    pub size: u64,
+    /// Stored size, for formats that compress per entry.
+    pub compressed_size: Option<u64>,
+    pub is_dir: bool,
+    pub flags: Vec<EntryFlag>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ArchiveReport {
+    pub format: &'static str,
+    pub entry_count: usize,
+    pub total_size: u64,
+    /// At most `MAX_LISTED` entries.
+    pub entries: Vec<ArchiveEntry>,
+    pub flagged_count: usize,
+}
+
+impl ArchiveReport {
+    fn new(format: &'static str) -> Self {
+        Self {
+            format,
+            entry_count: 0,
+            total_size: 0,
+            entries: Vec::new(),
+            flagged_count: 0,
+        }
+    }
+
+    fn push(&mut self, mut entry: ArchiveEntry, symlink: bool, encrypted: bool) {
+        if !entry.is_dir {
+            classify(&mut entry);
+        }
+        if symlink {
+            entry.flags.push(EntryFlag::Symlink);
+        }
+        if encrypted {
+            entry.flags.push(EntryFlag::Encrypted);
+        }
+        if escapes_root(&entry.path) {
+            entry.flags.push(EntryFlag::PathTraversal);
+        }
+        if let Some(packed) = entry.compressed_size.filter(|&c| c > 0) {
+            if entry.size / packed > SUSPICIOUS_RATIO {
+                entry.flags.push(EntryFlag::HighCompressionRatio);
+            }
+        }
+
+        self.entry_count += 1;
+        self.total_size = self.total_size.saturating_add(entry.size);
+        if !entry.flags.is_empty() {
+            self.flagged_count += 1;
+        }
+        if self.entries.len() < MAX_LISTED {
+            self.entries.push(entry);
+        }
+    }
+}
+
+fn extension(name: &str) -> Option<String> {
+    let (stem, ext) = name.rsplit_once('.')?;
+    (!stem.is_empty()).then(|| ext.to_ascii_lowercase())
+}
+
+fn classify(entry: &mut ArchiveEntry) {
+    let name = entry.path.rsplit('/').next().unwrap_or(&entry.path);
+    let Some(ext) = extension(name) else { return };
+    if EXECUTABLE_EXTENSIONS.contains(&ext.as_str()) {
+        entry.flags.push(EntryFlag::Executable);
+        // "report.pdf.exe": a harmless-looking extension hiding the real one.
+        let stem = &name[..name.len() - ext.len() - 1];
+        if extension(stem).is_some_and(|inner| {
+            inner.len() <= 4 && !EXECUTABLE_EXTENSIONS.contains(&inner.as_str())
+        }) {
+            entry.flags.push(EntryFlag::DoubleExtension);
+        }
+    }
+    if ARCHIVE_EXTENSIONS.contains(&ext.as_str()) {
+        entry.flags.push(EntryFlag::NestedArchive);
+    }
+}
+
+fn escapes_root(path: &str) -> bool {
+    let path = path.replace('\\', "/");
+    path.starts_with('/')
+        || path.as_bytes().get(1) == Some(&b':')
+        || path.split('/').any(|part| part == "..")
+}
+
+fn le16(b: &[u8], at: usize) -> u64 {
+    u64::from(u16::from_le_bytes([b[at], b[at + 1]]))
+}
+
+fn le32(b: &[u8], at: usize) -> u64 {
+    u64::from(u32::from_le_bytes([b[at], b[at + 1], b[at + 2], b[at + 3]]))
+}
+
+fn le64(b: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(b[at..at + 8].try_into().expect("8 bytes"))
+}
+
+// ---------------------------------------------------------------------------
+// Zip
+// ---------------------------------------------------------------------------
+
+/// Where the central directory lives.
+struct CentralDirectory {
+    offset: u64,
+    size: u64,
+}
+
+/// Locate the central directory from the last bytes of the file.
+/// `tail_offset` is the absolute offset of `tail[0]`.
+fn find_central_directory(tail: &[u8], tail_offset: u64) -> Result<CentralDirectory, String> {
+    let eocd = (0..tail.len().saturating_sub(21))
+        .rev()
+        .find(|&i| tail[i..].starts_with(b"PK\x05\x06"))
+        .ok_or("not a zip archive (no end-of-central-directory record)")?;
+    let entries = le16(tail, eocd + 10);
+    let mut cd = CentralDirectory {
+        size: le32(tail, eocd + 12),
+        offset: le32(tail, eocd + 16),
+    };
+
+    let zip64 = entries == 0xFFFF || cd.size == 0xFFFF_FFFF || cd.offset == 0xFFFF_FFFF;
+    if zip64 {
+        let locator = eocd
+            .checked_sub(20)
+            .filter(|&l| tail[l..].starts_with(b"PK\x06\x07"))
+            .ok_or("zip64 archive without a zip64 locator")?;
+        let record = le64(tail, locator + 8)
+            .checked_sub(tail_offset)
+            .and_then(|r| usize::try_from(r).ok())
+            .filter(|&r| {
+                r.checked_add(56).is_some_and(|end| end <= tail.len())
+                    && tail[r..].starts_with(b"PK\x06\x06")
+            })
+            .ok_or("zip64 end-of-central-directory record is unreadable")?;
+        cd.size = le64(tail, record + 40);
+        cd.offset = le64(tail, record + 48);
+    }
+    Ok(cd)
+}
+
+impl CentralDirectory {
+    /// End offset, if the directory fits within a file of `len` bytes.
+    /// Checked before anything is allocated for it: the offset and size
+    /// come straight from the archive.
+    fn end_within(&self, len: u64) -> Result<u64, String> {
+        self.offset
+            .checked_add(self.size)
+            .filter(|&end| end <= len)
+            .ok_or_else(|| "central directory lies outside the file".to_string())
+    }
+}
+
+fn parse_central_directory(cd: &[u8], report: &mut ArchiveReport) -> Result<(), String> {
+    let mut pos = 0;
+    while pos + 46 <= cd.len() && cd[pos..].starts_with(b"PK\x01\x02") {
+        let made_by_unix = cd[pos + 5] == 3;
+        let flags = le16(cd, pos + 8);
+        let mut compressed = le32(cd, pos + 20);
+        let mut size = le32(cd, pos + 24);
+        let name_len = le16(cd, pos + 28) as usize;
+        let extra_len = le16(cd, pos + 30) as usize;
+        let comment_len = le16(cd, pos + 32) as usize;
+        let external_attrs = le32(cd, pos + 38);
+
+        let name_start = pos + 46;
+        let extra_start = name_start + name_len;
+        let next = extra_start + extra_len + comment_len;
+        if next > cd.len() {
+            return Err("central directory is truncated".into());
+        }
+        let name = String::from_utf8_lossy(&cd[name_start..extra_start]).into_owned();
+
+        // Zip64 extended information: 64-bit sizes replace 0xFFFFFFFF fields.
+        let mut extra = &cd[extra_start..extra_start + extra_len];
+        while extra.len() >= 4 {
+            let id = le16(extra, 0);
+            let len = (le16(extra, 2) as usize).min(extra.len() - 4);
+            if id == 0x0001 {
+                let mut field = &extra[4..4 + len];
+                if size == 0xFFFF_FFFF && field.len() >= 8 {
+                    size = le64(field, 0);
+                    field = &field[8..];
+                }
+                if compressed == 0xFFFF_FFFF && field.len() >= 8 {
+                    compressed = le64(field, 0);
+                }
+            }
+            extra = &extra[4 + len..];
+        }
+
+        let symlink = made_by_unix && (external_attrs >> 16) & 0o170_000 == 0o120_000;
+        report.push(
+            ArchiveEntry {
+                is_dir: name.ends_with('/'),
+                path: name,
+                size,
+                compressed_size: Some(compressed),
+                flags: Vec::new(),
+            },
+            symlink,
+            flags & 1 != 0,
+        );
+        pos = next;
+    }
+    Ok(())
+}
+
+fn inspect_zip_file(file: &mut File, len: u64) -> Result<ArchiveReport, String> {
+    let tail_offset = len.saturating_sub(EOCD_SEARCH);
+    let mut tail = Vec::new();
+    file.seek(SeekFrom::Start(tail_offset))
+        .map_err(|e| e.to_string())?;
+    file.read_to_end(&mut tail).map_err(|e| e.to_string())?;
+
+    let location = find_central_directory(&tail, tail_offset)?;
+    location.end_within(len)?;
+    let mut cd = vec![0u8; location.size as usize];
+    file.seek(SeekFrom::Start(location.offset))
+        .map_err(|e| e.to_string())?;
+    file.read_exact(&mut cd)
+        .map_err(|e| format!("central directory is unreadable: {e}"))?;
+
+    let mut report = ArchiveReport::new("zip");
+    parse_central_directory(&cd, &mut report)?;
+    Ok(report)
+}
+
+fn inspect_zip_bytes(data: &[u8]) -> Result<ArchiveReport, String> {
+    let location = find_central_directory(data, 0)?;
+    let end = location.end_within(data.len() as u64)?;
+    let cd = &data[location.offset as usize..end as usize];
+    let mut report = ArchiveReport::new("zip");
+    parse_central_directory(cd, &mut report)?;
+    Ok(report)
+}
+
+// ---------------------------------------------------------------------------
+// Tar
+// ---------------------------------------------------------------------------
+
+fn tar_field(header: &[u8], start: usize, len: usize) -> String {
+    let field = &header[start..start + len];
+    let end = field.iter().position(|&b| b == 0).unwrap_or(len);
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+/// Octal size field, or GNU base-256 when the high bit is set.
+fn tar_size(header: &[u8]) -> Result<u64, String> {
+    let field = &header[124..136];
+    let size = if field[0] & 0x80 != 0 {
+        // 11 bytes don't fit a u64; anything in the top three is too big.
+        if field[1..4].iter().any(|&b| b != 0) {
+            u64::MAX
+        } else {
+            field[4..]
+                .iter()
+                .fold(0u64, |acc, &b| (acc << 8) | u64::from(b))
+        }
+    } else {
+        let text = tar_field(header, 124, 12);
+        u64::from_str_radix(text.trim(), 8).unwrap_or(0)
+    };
+    if size > MAX_TAR_ENTRY {
+        return Err("tar entry size is implausibly large".into());
+    }
+    Ok(size)
+}
+
+fn inspect_tar(reader: &mut impl Read) -> Result<ArchiveReport, String> {
+    let mut report = ArchiveReport::new("tar");
+    let mut header = [0u8; 512];
+    let mut long_name: Option<String> = None;
+
+    loop {
+        if reader.read_exact(&mut header).is_err() || header.iter().all(|&b| b == 0) {
+            break;
+        }
+        if &header[257..262] != b"ustar" && report.entry_count == 0 && long_name.is_none() {
+            return Err("not a tar archive".into());
+        }
+        let size = tar_size(&header)?;
+        let padded = size
+            .div_ceil(512)
+            .checked_mul(512)
+            .ok_or("tar entry size is implausibly large")?;
+        let kind = header[156];
+
+        if matches!(kind, b'L' | b'x') {
+            // GNU long name / pax extended header: the next entry's path.
+            if size > MAX_TAR_EXTENDED {
+                return Err("tar extended header is implausibly large".into());
+            }
+            let mut data = vec![0u8; padded as usize];
+            reader.read_exact(&mut data).map_err(|e| e.to_string())?;
+            let data = &data[..size as usize];
+            long_name = if kind == b'L' {
+                Some(
+                    String::from_utf8_lossy(data)
+                        .trim_end_matches('\0')
+                        .to_string(),
+                )
+            } else {
+                String::from_utf8_lossy(data)
+                    .lines()
+                    .find_map(|line| line.split_once(" path=").map(|(_, p)| p.to_string()))
+                    .or(long_name)
+            };
+            continue;
+        }
+
+        let path = long_name.take().unwrap_or_else(|| {
+            let prefix = tar_field(&header, 345, 155);
+            let name = tar_field(&header, 0, 100);
+            if prefix.is_empty() {
+                name
+            } else {
+                format!("{prefix}/{name}")
+            }
+        });
+        report.push(
+            ArchiveEntry {
+                is_dir: kind == b'5' || path.ends_with('/'),
+                path,
+                size,
+                compressed_size: None,
+                flags: Vec::new(),
+            },
+            kind == b'2',
+            false,
+        );
+
+        std::io::copy(&mut reader.by_ref().take(padded), &mut std::io::sink())
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(report)
+}
+
+// ---------------------------------------------------------------------------
+// Sources
+// ---------------------------------------------------------------------------
+
+fn sniff(head: &[u8]) -> Result<&'static str, String> {
+    if head.starts_with(b"PK\x03\x04") || head.starts_with(b"PK\x05\x06") {
+        Ok("zip")
+    } else if head.len() >= 262 && &head[257..262] == b"ustar" {
+        Ok("tar")
+    } else if head.starts_with(b"\x1f\x8b")
+        || head.starts_with(b"BZh")
+        || head.starts_with(b"\xfd7zXZ")
+    {
+        Err("compressed tarballs can't be listed without decompressing them".into())
+    } else {
+        Err("unsupported archive format; only zip and tar can be inspected".into())
+    }
+}
+
+fn inspect_path(path: &str) -> Result<ArchiveReport, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let len = file.metadata().map_err(|e| e.to_string())?.len();
+    let mut head = Vec::with_capacity(512);
+    file.by_ref()
+        .take(512)
+        .read_to_end(&mut head)
+        .map_err(|e| e.to_string())?;
+    file.seek(SeekFrom::Start(0)).map_err(|e| e.to_string())?;
+
+    match sniff(&head)? {
+        "zip" => inspect_zip_file(&mut file, len),
+        _ => inspect_tar(&mut std::io::BufReader::new(file)),
+    }
+}
+
+struct Fetched {
+    partial: bool,
+    /// Full length from `Content-Range`, for partial responses.
+    total_len: Option<u64>,
+    bytes: Vec<u8>,
+}
+
+async fn fetch(
+    client: &reqwest::Client,
+    url: &str,
+    range: Option<String>,
+) -> Result<Fetched, String> {
+    let mut request = client.get(url);
+    if let Some(range) = range {
+        request = request.header("Range", range);
+    }
+    let mut response = request
+        .send()
+        .await
+        .map_err(|e| format!("download failed: {e}"))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("server returned {status}"));
+    }
+    let total_len = response
+        .headers()
+        .get("Content-Range")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.rsplit_once('/'))
+        .and_then(|(_, total)| total.parse().ok());
+    let too_large = || "archive is too large to inspect before downloading".to_string();
+    if response
+        .content_length()
+        .is_some_and(|len| len > MAX_REMOTE_BYTES as u64)
+    {
+        return Err(too_large());
+    }
+    // Content-Length may be missing or wrong; enforce the cap as we read.
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        if bytes.len() + chunk.len() > MAX_REMOTE_BYTES {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(Fetched {
+        partial: status.as_u16() == 206,
+        total_len,
+        bytes,
+    })
+}
+
+fn inspect_bytes(data: &[u8]) -> Result<ArchiveReport, String> {
+    match sniff(data)? {
+        "zip" => inspect_zip_bytes(data),
+        _ => inspect_tar(&mut &data[..]),
+    }
+}
+
+async fn inspect_url(url: &str) -> Result<ArchiveReport, String> {
+    let client = reqwest::Client::new();
+
+    // Most attachment URLs are zips: try reading just the tail first.
+    let tail = fetch(&client, url, Some(format!("bytes=-{EOCD_SEARCH}"))).await?;
+    let Some(total_len) = tail.total_len.filter(|_| tail.partial) else {
+        // Range ignored — we already have the whole file.
+        return inspect_bytes(&tail.bytes);
+    };
+    let tail_offset = total_len.saturating_sub(tail.bytes.len() as u64);
+
+    match find_central_directory(&tail.bytes, tail_offset) {
+        Ok(location) => {
+            location.end_within(total_len)?;
+            if location.size > MAX_REMOTE_BYTES as u64 {
+                return Err("archive is too large to inspect before downloading".into());
+            }
+            let cd = match location.offset.checked_sub(tail_offset) {
+                Some(start) if start + location.size <= tail.bytes.len() as u64 => {
+                    tail.bytes[start as usize..(start + location.size) as usize].to_vec()
+                }
+                _ => {
+                    let end = location.offset + location.size.saturating_sub(1);
+                    let fetched = fetch(
+                        &client,
+                        url,
+                        Some(format!("bytes={}-{end}", location.offset)),
+                    )
+                    .await?;
+                    // A 200 is the whole file, not the directory.
+                    if !fetched.partial || fetched.bytes.len() as u64 != location.size {
+                        return Err("server ignored the range request for the zip directory".into());
+                    }
+                    fetched.bytes
+                }
+            };
+            let mut report = ArchiveReport::new("zip");
+            parse_central_directory(&cd, &mut report)?;
+            Ok(report)
+        }
+        // Not a zip: tar needs a full read.
+        Err(_) => inspect_bytes(&fetch(&client, url, None).await?.bytes),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// List a zip or tar archive (local path or http(s) URL) without extracting.
+#[tauri::command]
+pub(crate) async fn inspect_archive(path_or_url: String) -> Result<ArchiveReport, String> {
+    if path_or_url.starts_with("http://") || path_or_url.starts_with("https://") {
+        audit::record("networkRequest", "inspect_archive", &path_or_url);
+        inspect_url(&path_or_url).await
+    } else {
+        audit::record_file("fileRead", "inspect_archive", &path_or_url);
+        tauri::async_runtime::spawn_blocking(move || inspect_path(&path_or_url))
+            .await
+            .map_err(|e| e.to_string())?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A zip holding one stored entry named `name`.
+    fn zip(name: &str) -> Vec<u8> {
+        let mut data = Vec::new();
+        // Local header and data aren't read by the lister; a signature is
+        // enough for `sniff`.
+        data.extend_from_slice(b"PK\x03\x04");
+        data.resize(30, 0);
+
+        let cd_offset = data.len() as u32;
+        let mut cd = Vec::new();
+        cd.extend_from_slice(b"PK\x01\x02");
+        cd.resize(20, 0);
+        cd.extend_from_slice(&10u32.to_le_bytes()); // compressed
+        cd.extend_from_slice(&10u32.to_le_bytes()); // size
+        cd.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        cd.resize(46, 0);
+        cd.extend_from_slice(name.as_bytes());
+        data.extend_from_slice(&cd);
+
+        data.extend_from_slice(&eocd(1, cd.len() as u32, cd_offset));
+        data
+    }
+
+    fn eocd(entries: u16, cd_size: u32, cd_offset: u32) -> Vec<u8> {
+        let mut record = b"PK\x05\x06".to_vec();
+        record.resize(10, 0);
+        record.extend_from_slice(&entries.to_le_bytes());
+        record.extend_from_slice(&cd_size.to_le_bytes());
+        record.extend_from_slice(&cd_offset.to_le_bytes());
+        record.extend_from_slice(&0u16.to_le_bytes());
+        record
+    }
+
+    /// A ustar header of `kind` announcing `size` bytes of data.
+    fn tar_header(name: &str, kind: u8, size: u64) -> [u8; 512] {
+        let mut header = [0u8; 512];
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        let size = format!("{size:011o}");
+        header[124..124 + size.len()].copy_from_slice(size.as_bytes());
+        header[156] = kind;
+        header[257..262].copy_from_slice(b"ustar");
+        header
+    }
+
+    #[test]
+    fn lists_zip_entries() {
+        let report = inspect_bytes(&zip("docs/invoice.pdf.exe")).unwrap();
+        assert_eq!(report.format, "zip");
+        assert_eq!(report.entry_count, 1);
+        assert_eq!(report.entries[0].path, "docs/invoice.pdf.exe");
+        assert_eq!(
+            report.entries[0].flags,
+            [EntryFlag::Executable, EntryFlag::DoubleExtension]
+        );
+    }
+
+    #[test]
+    fn rejects_central_directory_past_end_of_file() {
+        // A bare EOCD claiming a 4 GB directory must not allocate it.
+        let data = eocd(1, 0xFFFF_FFFE, 0);
+        assert!(inspect_zip_bytes(&data).is_err());
+
+        let dir = std::env::temp_dir().join(format!("ripcord-archive-{}", std::process::id()));
+        std::fs::write(&dir, &data).unwrap();
+        let result = inspect_path(dir.to_str().unwrap());
+        let _ = std::fs::remove_file(&dir);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn rejects_overflowing_zip64_directory() {
+        let mut data = Vec::new();
+        let record_offset = data.len() as u64;
+        data.extend_from_slice(b"PK\x06\x06");
+        data.resize(40, 0);
+        data.extend_from_slice(&16u64.to_le_bytes()); // size
+        data.extend_from_slice(&u64::MAX.to_le_bytes()); // offset
+        data.extend_from_slice(b"PK\x06\x07");
+        data.resize(data.len() + 4, 0);
+        data.extend_from_slice(&record_offset.to_le_bytes());
+        data.resize(data.len() + 4, 0);
+        data.extend_from_slice(&eocd(0xFFFF, 0xFFFF_FFFF, 0xFFFF_FFFF));
+        assert!(inspect_zip_bytes(&data).is_err());
+    }
+
+    #[test]
+    fn lists_tar_entries_with_long_names() {
+        let long = format!("{}/setup.sh", "a".repeat(120));
+        let mut data = tar_header("././@LongLink", b'L', long.len() as u64 + 1).to_vec();
+        let mut name = long.clone().into_bytes();
+        name.resize(512, 0);
+        data.extend_from_slice(&name);
+        data.extend_from_slice(&tar_header("truncated", b'0', 0));
+        data.extend_from_slice(&[0u8; 1024]);
+
+        let report = inspect_bytes(&data).unwrap();
+        assert_eq!(report.format, "tar");
+        assert_eq!(report.entry_count, 1);
+        assert_eq!(report.entries[0].path, long);
+        assert_eq!(report.entries[0].flags, [EntryFlag::Executable]);
+    }
+
+    #[test]
+    fn rejects_oversized_tar_extended_header() {
+        // One header announcing an 8 GB long name must not allocate it.
+        let data = tar_header("././@LongLink", b'L', 8 << 30);
+        assert!(inspect_tar(&mut &data[..]).is_err());
+        let data = tar_header("PaxHeader", b'x', 8 << 30);
+        assert!(inspect_tar(&mut &data[..]).is_err());
+    }
+
+    #[test]
+    fn rejects_oversized_base256_tar_size() {
+        let mut data = tar_header("big.bin", b'0', 0);
+        data[124..136].fill(0xFF);
+        assert!(inspect_tar(&mut &data[..]).is_err());
+        data[124..136].copy_from_slice(&[0x80, 0, 0, 0, 0, 0, 0x02, 0, 0, 0, 0, 0]);
+        assert!(inspect_tar(&mut &data[..]).is_err());
+    }
+
+    #[test]
+    fn flags_path_traversal() {
+        let report = inspect_bytes(&zip("../../etc/cron.d/job")).unwrap();
+        assert_eq!(report.entries[0].flags, [EntryFlag::PathTraversal]);
+    }
+}
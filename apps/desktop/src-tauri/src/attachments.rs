@@ -0,0 +1,139 @@
+use std::io::Read;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::sha256::{self, Sha256};
+use crate::{audit, scheduler, store};
+
+// ===========================================================================
+// Attachment Dedup Index
+// ===========================================================================
+//
+// Before uploading a file the frontend calls `check_attachment_duplicate`,
+// which hashes it (SHA-256, streamed off the main thread) and looks it up in
+// a local index of recently sent attachments. On a hit the composer can offer
+// to post the existing CDN URL instead of re-uploading the same bytes. After
+// a real upload succeeds, `record_uploaded_attachment` adds it to the index.
+//
+// Uploads themselves still go through the webview's API client; there is no
+// native upload engine yet. Entries expire after `REUSE_WINDOW_MS` since CDN
+// attachment URLs are not permanent.
+// ===========================================================================
+
+const STORE_DOC: &str = "sent-attachments";
+const REUSE_WINDOW_MS: u64 = 7 * 24 * 60 * 60 * 1000;
+const MAX_ENTRIES: usize = 1000;
+const READ_CHUNK: usize = 1 << 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SentAttachment {
+    /// Hex SHA-256 of the file contents.
+    pub hash: String,
+    pub size: u64,
+    pub file_name: String,
+    pub url: String,
+    /// Milliseconds since the Unix epoch.
+    pub uploaded_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DuplicateCheck {
+    pub hash: String,
+    pub size: u64,
+    /// A recent upload of identical content, if any.
+    pub existing: Option<SentAttachment>,
+}
+
+static INDEX: Mutex<Option<Vec<SentAttachment>>> = Mutex::new(None);
+
+fn with_index<R>(f: impl FnOnce(&mut Vec<SentAttachment>) -> R) -> R {
+    let mut guard = INDEX.lock().unwrap();
+    f(guard.get_or_insert_with(|| store::load(STORE_DOC)))
+}
+
+/// Drop expired entries and keep at most `MAX_ENTRIES`, newest first.
+fn prune(index: &mut Vec<SentAttachment>, now: u64) {
+    let cutoff = now.saturating_sub(REUSE_WINDOW_MS);
+    index.retain(|a| a.uploaded_at >= cutoff);
+    index.sort_by(|a, b| b.uploaded_at.cmp(&a.uploaded_at));
+    index.truncate(MAX_ENTRIES);
+}
+
+/// Stream a file through SHA-256. Returns `(hex digest, size)`.
+fn hash_file(path: &str) -> Result<(String, u64), String> {
+    let mut file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; READ_CHUNK];
+    let mut size = 0u64;
+    loop {
+        let n = file.read(&mut buf).map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        size += n as u64;
+    }
+    Ok((sha256::hex(&hasher.finalize()), size))
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Hash the file at `path` and look for a recent upload of the same content.
+#[tauri::command]
+pub(crate) async fn check_attachment_duplicate(path: String) -> Result<DuplicateCheck, String> {
+    audit::record_file("fileRead", "check_attachment_duplicate", &path);
+    let (hash, size) = tauri::async_runtime::spawn_blocking(move || hash_file(&path))
+        .await
+        .map_err(|e| e.to_string())??;
+
+    let existing = with_index(|index| {
+        prune(index, scheduler::now_ms());
+        index
+            .iter()
+            .find(|a| a.hash == hash && a.size == size)
+            .cloned()
+    });
+    Ok(DuplicateCheck {
+        hash,
+        size,
+        existing,
+    })
+}
+
+/// Record a completed upload so identical content can be reused later.
+/// `hash` and `size` come from a prior `check_attachment_duplicate`.
+#[tauri::command]
+pub(crate) fn record_uploaded_attachment(
+    hash: String,
+    size: u64,
+    file_name: String,
+    url: String,
+) -> Result<(), String> {
+    let now = scheduler::now_ms();
+    with_index(|index| {
+        index.retain(|a| a.hash != hash);
+        index.push(SentAttachment {
+            hash,
+            size,
+            file_name,
+            url,
+            uploaded_at: now,
+        });
+        prune(index, now);
+        store::save(STORE_DOC, index)
+    })
+}
+
+/// Forget an entry, e.g. after its URL turned out to be dead.
+#[tauri::command]
+pub(crate) fn forget_uploaded_attachment(hash: String) -> Result<(), String> {
+    with_index(|index| {
+        index.retain(|a| a.hash != hash);
+        store::save_cleared(STORE_DOC, index)
+    })
+}
@@ -0,0 +1,157 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{scheduler, store};
+
+// ===========================================================================
+// Audit Log
+// ===========================================================================
+//
+// A local record of what the native layer did on the user's behalf that
+// touches their machine or data beyond the app itself:
+//
+//   fileRead           a user-chosen file was opened (attachments, archives,
+//                      fonts, stickers)
+//   networkRequest     content was sent to a third-party service (archive
+//                      inspection by URL, translation, the first-run STUN
+//                      probe)
+//   processLaunched    an external program was run (local translation)
+//   programConfigured  an external program was chosen to be run later (the
+//                      local translator)
+//   credentialChanged  an API key was stored or cleared (never the key)
+//   inputHookStarted / inputHookStopped
+//                      the system-wide keyboard/mouse hook behind PTT and
+//                      keybinds came up or went down (keys are never logged)
+//
+// File reads record only the file name, not where the file lives.
+//
+// Entries are appended as JSON lines to `audit.log` in the native store
+// directory and never rewritten. Past `MAX_LOG_BYTES` the file is rotated to
+// `audit.log.1`, replacing the previous rotation, so the log keeps between
+// one and two files' worth of history. `get_audit_log` reads both.
+// Clearing user data (a `store::save_cleared`) deletes both files, so the
+// log doesn't outlive what it describes; in guest mode they live in the
+// session directory and go with it.
+//
+// Nothing here leaves the machine.
+// ===========================================================================
+
+const LOG_FILE: &str = "audit.log";
+const ROTATED_FILE: &str = "audit.log.1";
+const MAX_LOG_BYTES: u64 = 2 * 1024 * 1024;
+const DEFAULT_LIMIT: usize = 500;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AuditEntry {
+    /// Milliseconds since the Unix epoch.
+    pub at: u64,
+    /// One of the kinds in the module notes.
+    pub action: String,
+    /// The command or subsystem that did it.
+    pub source: String,
+    /// What it acted on: a file name, URL, program or provider.
+    pub detail: String,
+}
+
+/// Serializes appends and rotation.
+static LOG_LOCK: Mutex<()> = Mutex::new(());
+
+fn log_path(name: &str) -> Option<PathBuf> {
+    Some(store::dir()?.join(name))
+}
+
+/// Append an entry. Failures are ignored — auditing must never stop the
+/// action it records.
+pub(crate) fn record(action: &str, source: &str, detail: impl Into<String>) {
+    let entry = AuditEntry {
+        at: scheduler::now_ms(),
+        action: action.to_string(),
+        source: source.to_string(),
+        detail: detail.into(),
+    };
+    let Ok(mut line) = serde_json::to_vec(&entry) else {
+        return;
+    };
+    line.push(b'\n');
+
+    let _guard = LOG_LOCK.lock().unwrap();
+    let Some(path) = log_path(LOG_FILE) else {
+        return;
+    };
+    let full = std::fs::metadata(&path).is_ok_and(|meta| meta.len() >= MAX_LOG_BYTES);
+    if full {
+        if let Some(rotated) = log_path(ROTATED_FILE) {
+            let _ = std::fs::rename(&path, rotated);
+        }
+    }
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&path) {
+        let _ = file.write_all(&line);
+    }
+}
+
+/// `record` a user-chosen file by its file name only.
+pub(crate) fn record_file(action: &str, source: &str, path: &str) {
+    let name = Path::new(path).file_name().map_or_else(
+        || path.to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    );
+    record(action, source, name);
+}
+
+/// Delete the log and its rotation.
+pub(crate) fn clear() {
+    let _guard = LOG_LOCK.lock().unwrap();
+    for name in [LOG_FILE, ROTATED_FILE] {
+        if let Some(path) = log_path(name) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Entries from one log file, oldest first. Lines that don't parse (a write
+/// cut short by a crash) are skipped.
+fn read_entries(name: &str) -> Vec<AuditEntry> {
+    let Some(file) = log_path(name).and_then(|path| File::open(path).ok()) else {
+        return Vec::new();
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Audit entries between `from` and `to` (epoch ms, inclusive; either may be
+/// omitted), newest first, at most `limit` (default 500).
+#[tauri::command]
+pub(crate) async fn get_audit_log(
+    from: Option<u64>,
+    to: Option<u64>,
+    limit: Option<usize>,
+) -> Result<Vec<AuditEntry>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let _guard = LOG_LOCK.lock().unwrap();
+        let mut entries = read_entries(ROTATED_FILE);
+        entries.extend(read_entries(LOG_FILE));
+        let in_range = |entry: &AuditEntry| {
+            from.is_none_or(|from| entry.at >= from) && to.is_none_or(|to| entry.at <= to)
+        };
+        entries
+            .into_iter()
+            .rev()
+            .filter(in_range)
+            .take(limit.unwrap_or(DEFAULT_LIMIT))
+            .collect()
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
@@ -0,0 +1,70 @@
+use serde::Serialize;
+
+// ===========================================================================
+// Native Capability Discovery
+// ===========================================================================
+//
+// The frontend runs the same bundle on every platform and build flavour. It
+// used to discover missing native features by invoking a command and
+// treating the failure (or a sentinel return value such as `-1`) as
+// "unsupported". `get_native_capabilities` replaces that probing with a
+// single typed answer so settings pages can hide what the current build
+// cannot do.
+//
+// Every field is decided at compile time from `cfg` attributes; nothing here
+// touches the OS. When a subsystem gains support on a new platform, update
+// its entry here in the same change.
+// ===========================================================================
+
+/// Features supported by the running platform and build.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct NativeCapabilities {
+    /// Native low-level PTT keyboard hook (`start_ptt_hook`).
+    pub ptt_hook: bool,
+    /// PTT bound to one keyboard (`start_ptt_hook`'s `deviceFilter`).
+    pub ptt_device_filter: bool,
+    /// Swallowing the PTT key (`suppress`) and keybind keys (`consume`).
+    pub ptt_suppress: bool,
+    /// Gamepad buttons as PTT and keybind keys.
+    pub gamepad_ptt: bool,
+    /// Media and volume keys as keybind keys.
+    pub media_keys: bool,
+    /// Synchronous key-state polling (`check_key_pressed` returns 0/1).
+    pub key_state_polling: bool,
+    /// System audio loopback capture for screen share.
+    pub loopback_capture: bool,
+    /// Hardware video encoders available to native capture (e.g. "nvenc").
+    pub hardware_encode: Vec<String>,
+    /// OS keychain / credential store access.
+    pub keychain: bool,
+    /// OS-level microphone mute sync (`set_system_mic_mute`).
+    pub system_mic_mute: bool,
+    /// Compatibility mode can switch the webview to software rendering;
+    /// elsewhere it only sets the `CompatMode` hints.
+    pub compat_mode: bool,
+}
+
+/// Report which native features this platform/build supports.
+#[tauri::command]
+pub(crate) fn get_native_capabilities() -> NativeCapabilities {
+    NativeCapabilities {
+        ptt_hook: cfg!(any(
+            target_os = "windows",
+            target_os = "macos",
+            target_os = "linux"
+        )),
+        ptt_device_filter: cfg!(any(target_os = "windows", target_os = "linux")),
+        ptt_suppress: cfg!(target_os = "windows"),
+        gamepad_ptt: cfg!(any(target_os = "windows", target_os = "linux")),
+        media_keys: cfg!(any(target_os = "windows", target_os = "linux")),
+        key_state_polling: cfg!(target_os = "windows"),
+        // No native capture or credential store in this build yet — screen
+        // share audio and secrets are handled by the webview.
+        loopback_capture: false,
+        hardware_encode: Vec::new(),
+        keychain: false,
+        system_mic_mute: cfg!(target_os = "windows"),
+        compat_mode: cfg!(any(target_os = "windows", target_os = "linux")),
+    }
+}
@@ -0,0 +1,167 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+// ===========================================================================
+// Synchronized Playback Clock
+// ===========================================================================
+//
+// Watch-together playback needs every participant to agree on "now" to
+// within a few milliseconds, and to keep agreeing over a two-hour movie.
+// Wall clocks disagree by tens to hundreds of ms and drift apart by tens of
+// ppm, so each client estimates its offset to a reference participant
+// (usually the host) NTP-style.
+//
+// The transport stays where it already lives: the webview sends pings over
+// the LiveKit data channel and feeds the four timestamps of each round trip
+// into `record_clock_sample`. This module does the math:
+//
+//   offset = ((t1 - t0) + (t2 - t3)) / 2      delay = (t3 - t0) - (t2 - t1)
+//
+//   t0 = local send, t1 = remote receive, t2 = remote send, t3 = local receive
+//
+// Samples are kept in a short window per reference. The offset is taken from
+// the minimum-delay sample (NTP clock filter — queueing only ever adds
+// delay, and asymmetric queueing is what corrupts the offset), and drift is
+// the least-squares slope of offset over local time across the window.
+// `get_synced_time` extrapolates from the best sample using that drift.
+// ===========================================================================
+
+/// Samples retained per reference clock.
+const WINDOW: usize = 16;
+
+/// Minimum local-time span (ms) before a drift estimate is trusted.
+const MIN_DRIFT_SPAN_MS: f64 = 10_000.0;
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    /// Local receive time (t3), ms since the Unix epoch.
+    local_ms: f64,
+    offset_ms: f64,
+    delay_ms: f64,
+}
+
+/// Offset estimate for one reference clock, as reported to the frontend.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ClockOffset {
+    /// Reference clock minus local clock, at the best sample.
+    pub offset_ms: f64,
+    /// Round-trip delay of the sample the offset was taken from.
+    pub rtt_ms: f64,
+    /// Estimated drift of the reference relative to us, in ppm.
+    pub drift_ppm: f64,
+    /// Samples currently in the window.
+    pub samples: usize,
+}
+
+/// Sample windows keyed by reference id (e.g. the host's participant id).
+static CLOCKS: Mutex<Option<HashMap<String, VecDeque<Sample>>>> = Mutex::new(None);
+
+/// Local wall clock in ms since the Unix epoch.
+fn local_now_ms() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64() * 1000.0)
+        .unwrap_or(0.0)
+}
+
+/// Best sample (minimum delay) and least-squares drift for a window.
+fn estimate(window: &VecDeque<Sample>) -> Option<(Sample, f64)> {
+    let best = *window
+        .iter()
+        .min_by(|a, b| a.delay_ms.total_cmp(&b.delay_ms))?;
+
+    let n = window.len() as f64;
+    let first = window.front()?.local_ms;
+    let span = window.back()?.local_ms - first;
+    let drift = if window.len() >= 3 && span >= MIN_DRIFT_SPAN_MS {
+        let mean_x = window.iter().map(|s| s.local_ms - first).sum::<f64>() / n;
+        let mean_y = window.iter().map(|s| s.offset_ms).sum::<f64>() / n;
+        let (mut num, mut den) = (0.0, 0.0);
+        for s in window {
+            let dx = s.local_ms - first - mean_x;
+            num += dx * (s.offset_ms - mean_y);
+            den += dx * dx;
+        }
+        if den > 0.0 { num / den } else { 0.0 }
+    } else {
+        0.0
+    };
+
+    Some((best, drift))
+}
+
+/// Frontend-facing summary of a window.
+fn summarize(window: &VecDeque<Sample>) -> Option<ClockOffset> {
+    let (best, drift) = estimate(window)?;
+    Some(ClockOffset {
+        offset_ms: best.offset_ms,
+        rtt_ms: best.delay_ms,
+        drift_ppm: drift * 1_000_000.0,
+        samples: window.len(),
+    })
+}
+
+/// Feed one ping round trip (all timestamps in ms since the Unix epoch).
+/// Returns the updated estimate for `reference`.
+#[tauri::command]
+pub(crate) fn record_clock_sample(
+    reference: String,
+    t0: f64,
+    t1: f64,
+    t2: f64,
+    t3: f64,
+) -> Result<ClockOffset, String> {
+    let delay_ms = (t3 - t0) - (t2 - t1);
+    if !delay_ms.is_finite() || delay_ms < 0.0 {
+        return Err(format!("inconsistent clock sample (delay {delay_ms} ms)"));
+    }
+    let sample = Sample {
+        local_ms: t3,
+        offset_ms: ((t1 - t0) + (t2 - t3)) / 2.0,
+        delay_ms,
+    };
+
+    let mut clocks = CLOCKS.lock().unwrap();
+    let window = clocks.get_or_insert_with(HashMap::new).entry(reference).or_default();
+    if window.len() == WINDOW {
+        window.pop_front();
+    }
+    window.push_back(sample);
+
+    Ok(summarize(window).expect("window is non-empty"))
+}
+
+/// Current time on `reference`'s clock in ms since the Unix epoch, or
+/// `None` if no samples have been recorded for it.
+#[tauri::command]
+pub(crate) fn get_synced_time(reference: String) -> Option<f64> {
+    let clocks = CLOCKS.lock().unwrap();
+    let window = clocks.as_ref()?.get(&reference)?;
+    let (best, drift) = estimate(window)?;
+
+    let now = local_now_ms();
+    Some(now + best.offset_ms + drift * (now - best.local_ms))
+}
+
+/// Current estimates for every reference clock.
+#[tauri::command]
+pub(crate) fn get_clock_offsets() -> HashMap<String, ClockOffset> {
+    let clocks = CLOCKS.lock().unwrap();
+    let Some(clocks) = clocks.as_ref() else {
+        return HashMap::new();
+    };
+    clocks
+        .iter()
+        .filter_map(|(id, window)| Some((id.clone(), summarize(window)?)))
+        .collect()
+}
+
+/// Forget all samples (e.g. when leaving a watch-together session).
+#[tauri::command]
+pub(crate) fn reset_clock_sync() {
+    *CLOCKS.lock().unwrap() = None;
+}
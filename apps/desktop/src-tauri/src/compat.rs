@@ -0,0 +1,213 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+use crate::{gpu, scheduler, store, APP_HANDLE};
+
+// ===========================================================================
+// Compatibility Mode
+// ===========================================================================
+//
+// GPU-accelerated compositing in the webview is the usual cause of black or
+// frozen windows under Remote Desktop and in virtual machines. Compatibility
+// mode turns on when either is detected (see `gpu::detect_session`), or
+// when the user forces it, and:
+//
+//   - switches the webview to software rendering. This is done through the
+//     environment the webview reads when it starts, so `init` must run
+//     before the main window is created: `--disable-gpu` for WebView2,
+//     `WEBKIT_DISABLE_COMPOSITING_MODE` / `WEBKIT_DISABLE_DMABUF_RENDERER`
+//     for WebKitGTK. WKWebView has no such switch, so macOS only gets the
+//     hints below.
+//   - tells the frontend to avoid hardware video encode and to reduce
+//     animations, via the `CompatMode` fields.
+//
+// An RDP client can connect to a session that started at the console, so
+// the remote-session bit is re-checked every few seconds on the scheduler.
+// `compat-mode-changed` is emitted with the new `CompatMode` whenever the
+// mode flips; rendering can only change on the next launch, which
+// `restartRequired` reports.
+//
+// The preference ("auto" / "on" / "off") lives in the "compat-mode" store
+// document.
+// ===========================================================================
+
+const STORE_DOC: &str = "compat-mode";
+const CHECK_KEY: &str = "compat-check";
+const CHECK_INTERVAL_MS: u64 = 5_000;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum CompatPreference {
+    /// Follow remote-desktop / VM detection.
+    #[default]
+    Auto,
+    On,
+    Off,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct CompatConfig {
+    preference: CompatPreference,
+}
+
+/// Current compatibility state, as sent with `compat-mode-changed`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CompatMode {
+    pub preference: CompatPreference,
+    /// Compatibility mode is in effect.
+    pub active: bool,
+    /// Why: "user", "remoteDesktop" or "virtualMachine".
+    pub reason: Option<String>,
+    /// The webview was started with GPU rendering disabled.
+    pub software_rendering: bool,
+    /// Prefer software video encoders.
+    pub disable_hardware_encode: bool,
+    /// Reduce or skip animations.
+    pub reduce_motion: bool,
+    /// `software_rendering` no longer matches `active`; it changes on the
+    /// next launch.
+    pub restart_required: bool,
+}
+
+/// Detection results that don't change while the app runs.
+struct State {
+    preference: CompatPreference,
+    virtual_machine: bool,
+    remote_desktop: bool,
+    /// Software rendering was applied at launch.
+    software_rendering: bool,
+}
+
+static STATE: Mutex<Option<State>> = Mutex::new(None);
+
+/// Last `active` value announced, to emit only on changes.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Whether this platform's webview can be switched to software rendering.
+const CAN_DISABLE_GPU: bool = cfg!(any(target_os = "windows", target_os = "linux"));
+
+fn mode(state: &State) -> CompatMode {
+    let reason = match state.preference {
+        CompatPreference::On => Some("user"),
+        CompatPreference::Off => None,
+        CompatPreference::Auto if state.remote_desktop => Some("remoteDesktop"),
+        CompatPreference::Auto if state.virtual_machine => Some("virtualMachine"),
+        CompatPreference::Auto => None,
+    };
+    let active = reason.is_some();
+    CompatMode {
+        preference: state.preference,
+        active,
+        reason: reason.map(str::to_string),
+        software_rendering: state.software_rendering,
+        disable_hardware_encode: active,
+        reduce_motion: active,
+        restart_required: CAN_DISABLE_GPU && state.software_rendering != active,
+    }
+}
+
+fn with_state<R>(f: impl FnOnce(&mut State) -> R) -> Option<R> {
+    STATE.lock().unwrap().as_mut().map(f)
+}
+
+/// Emit `compat-mode-changed` if `active` differs from the last one sent.
+fn announce(mode: &CompatMode) {
+    if ACTIVE.swap(mode.active, Ordering::Relaxed) == mode.active {
+        return;
+    }
+    if let Some(handle) = APP_HANDLE.get() {
+        let _ = handle.emit("compat-mode-changed", mode);
+    }
+}
+
+/// Ask the webview to render without the GPU. Only affects webviews
+/// created afterwards.
+fn disable_gpu_rendering() {
+    #[cfg(target_os = "windows")]
+    {
+        const VAR: &str = "WEBVIEW2_ADDITIONAL_BROWSER_ARGUMENTS";
+        let mut args = std::env::var(VAR).unwrap_or_default();
+        if !args.contains("--disable-gpu") {
+            args.push_str(" --disable-gpu --disable-gpu-compositing");
+            std::env::set_var(VAR, args.trim_start());
+        }
+    }
+    #[cfg(target_os = "linux")]
+    {
+        std::env::set_var("WEBKIT_DISABLE_COMPOSITING_MODE", "1");
+        std::env::set_var("WEBKIT_DISABLE_DMABUF_RENDERER", "1");
+    }
+}
+
+/// Re-check the remote-session bit, announce a change, and re-arm.
+fn check_session(_key: &str) {
+    let remote_desktop = gpu::remote_desktop();
+    let changed = with_state(|state| {
+        let changed = state.remote_desktop != remote_desktop;
+        state.remote_desktop = remote_desktop;
+        changed.then(|| mode(state))
+    })
+    .flatten();
+    if let Some(mode) = changed {
+        announce(&mode);
+    }
+    scheduler::schedule(
+        CHECK_KEY,
+        scheduler::now_ms() + CHECK_INTERVAL_MS,
+        check_session,
+    );
+}
+
+/// Detect the session, apply software rendering if needed, and start
+/// watching for RDP connects. Must run before the main window is created.
+pub(crate) fn init() {
+    let config: CompatConfig = store::load(STORE_DOC);
+    let session = gpu::detect_session();
+    let mut state = State {
+        preference: config.preference,
+        virtual_machine: session.virtual_machine,
+        remote_desktop: session.remote_desktop,
+        software_rendering: false,
+    };
+    if mode(&state).active && CAN_DISABLE_GPU {
+        disable_gpu_rendering();
+        state.software_rendering = true;
+    }
+    ACTIVE.store(mode(&state).active, Ordering::Relaxed);
+    *STATE.lock().unwrap() = Some(state);
+
+    scheduler::schedule(
+        CHECK_KEY,
+        scheduler::now_ms() + CHECK_INTERVAL_MS,
+        check_session,
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// The current compatibility mode and why it is (or isn't) active.
+#[tauri::command]
+pub(crate) fn get_compat_mode() -> Result<CompatMode, String> {
+    with_state(|state| mode(state)).ok_or_else(|| "compatibility mode not initialised".into())
+}
+
+/// Set the preference: "auto" follows detection, "on"/"off" force it.
+/// Rendering follows on the next launch.
+#[tauri::command]
+pub(crate) fn set_compat_mode(preference: CompatPreference) -> Result<CompatMode, String> {
+    store::save(STORE_DOC, &CompatConfig { preference })?;
+    let mode = with_state(|state| {
+        state.preference = preference;
+        mode(state)
+    })
+    .ok_or("compatibility mode not initialised")?;
+    announce(&mode);
+    Ok(mode)
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{scheduler, store};
+
+// ===========================================================================
+// Local Message Edit History
+// ===========================================================================
+//
+// Opt-in retention of earlier message versions. When the gateway delivers an
+// edit, the frontend passes the *previous* content it had cached to
+// `record_message_edit`; the versions are kept in the native store and can
+// be listed with `get_local_edit_history(messageId)`.
+//
+// This only ever contains text the user's client already received — nothing
+// is fetched — and respects privacy controls: it is off by default, entire
+// guilds or channels can be excluded, and versions older than the retention
+// window are pruned on every write. Each message remembers its channel and
+// guild, so excluding one also deletes what was already recorded there.
+// ===========================================================================
+
+const STORE_DOC: &str = "edit-history";
+const MS_PER_DAY: u64 = 24 * 60 * 60 * 1000;
+
+/// Cap on tracked messages, independent of retention.
+const MAX_MESSAGES: usize = 5000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct EditHistoryConfig {
+    pub enabled: bool,
+    pub retention_days: u32,
+    pub excluded_guilds: Vec<String>,
+    pub excluded_channels: Vec<String>,
+}
+
+impl Default for EditHistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            retention_days: 30,
+            excluded_guilds: Vec::new(),
+            excluded_channels: Vec::new(),
+        }
+    }
+}
+
+/// A superseded version of a message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MessageVersion {
+    pub content: String,
+    /// When this version was replaced (ms since the Unix epoch).
+    pub replaced_at: u64,
+}
+
+/// An edit as seen on the gateway.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MessageEdit {
+    pub message_id: String,
+    pub channel_id: String,
+    pub guild_id: Option<String>,
+    /// Content before the edit.
+    pub previous_content: String,
+    /// Edit time (ms since the Unix epoch); defaults to now.
+    pub edited_at: Option<u64>,
+}
+
+/// A message's earlier versions and where it was posted.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrackedMessage {
+    channel_id: String,
+    guild_id: Option<String>,
+    versions: Vec<MessageVersion>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct History {
+    config: EditHistoryConfig,
+    /// By message id.
+    messages: HashMap<String, TrackedMessage>,
+}
+
+impl EditHistoryConfig {
+    fn excludes(&self, channel_id: &str, guild_id: Option<&str>) -> bool {
+        self.excluded_channels.iter().any(|c| c == channel_id)
+            || guild_id.is_some_and(|g| self.excluded_guilds.iter().any(|e| e == g))
+    }
+}
+
+impl History {
+    /// Drop messages in excluded channels and guilds, versions outside the
+    /// retention window, then the oldest messages beyond `MAX_MESSAGES`.
+    /// Returns whether anything was dropped.
+    fn prune(&mut self, now: u64) -> bool {
+        let before = self.messages.len();
+        let config = &self.config;
+        let cutoff = now.saturating_sub(u64::from(config.retention_days) * MS_PER_DAY);
+        let mut expired = false;
+        self.messages.retain(|_, message| {
+            if config.excludes(&message.channel_id, message.guild_id.as_deref()) {
+                return false;
+            }
+            let count = message.versions.len();
+            message.versions.retain(|v| v.replaced_at >= cutoff);
+            expired |= message.versions.len() != count;
+            !message.versions.is_empty()
+        });
+
+        if self.messages.len() > MAX_MESSAGES {
+            let mut newest: Vec<(u64, String)> = self
+                .messages
+                .iter()
+                .map(|(id, m)| (m.versions.last().map_or(0, |v| v.replaced_at), id.clone()))
+                .collect();
+            newest.sort_unstable();
+            let excess = newest.len() - MAX_MESSAGES;
+            for (_, id) in newest.into_iter().take(excess) {
+                self.messages.remove(&id);
+            }
+        }
+        expired || self.messages.len() != before
+    }
+}
+
+static HISTORY: Mutex<Option<History>> = Mutex::new(None);
+
+fn with_history<R>(f: impl FnOnce(&mut History) -> R) -> R {
+    let mut guard = HISTORY.lock().unwrap();
+    f(guard.get_or_insert_with(|| store::load(STORE_DOC)))
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Update the edit-history settings. Disabling clears everything recorded;
+/// newly excluded channels and guilds lose what was recorded in them.
+#[tauri::command]
+pub(crate) fn set_edit_history_config(config: EditHistoryConfig) -> Result<(), String> {
+    with_history(|history| {
+        let mut cleared = !config.enabled && !history.messages.is_empty();
+        if !config.enabled {
+            history.messages.clear();
+        }
+        history.config = config;
+        cleared |= history.prune(scheduler::now_ms());
+        if cleared {
+            store::save_cleared(STORE_DOC, history)
+        } else {
+            store::save(STORE_DOC, history)
+        }
+    })
+}
+
+/// Current edit-history settings.
+#[tauri::command]
+pub(crate) fn get_edit_history_config() -> EditHistoryConfig {
+    with_history(|history| history.config.clone())
+}
+
+/// Record the pre-edit content of a message. Returns `false` if history is
+/// disabled or the message's channel/guild is excluded.
+#[tauri::command]
+pub(crate) fn record_message_edit(edit: MessageEdit) -> Result<bool, String> {
+    with_history(|history| {
+        let config = &history.config;
+        if !config.enabled || config.excludes(&edit.channel_id, edit.guild_id.as_deref()) {
+            return Ok(false);
+        }
+
+        let now = scheduler::now_ms();
+        let message = history.messages.entry(edit.message_id).or_default();
+        message.channel_id = edit.channel_id;
+        message.guild_id = edit.guild_id;
+        let versions = &mut message.versions;
+        // The gateway can redeliver the same edit; skip exact repeats.
+        if versions.last().map(|v| &v.content) != Some(&edit.previous_content) {
+            versions.push(MessageVersion {
+                content: edit.previous_content,
+                replaced_at: edit.edited_at.unwrap_or(now),
+            });
+        }
+        history.prune(now);
+        store::save(STORE_DOC, history).map(|_| true)
+    })
+}
+
+/// Earlier versions of a message, oldest first.
+#[tauri::command]
+pub(crate) fn get_local_edit_history(message_id: String) -> Vec<MessageVersion> {
+    with_history(|history| {
+        history
+            .messages
+            .get(&message_id)
+            .map(|message| message.versions.clone())
+            .unwrap_or_default()
+    })
+}
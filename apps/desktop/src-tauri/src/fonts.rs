@@ -0,0 +1,540 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::http::{Request, Response};
+use tauri::{Manager, Runtime, UriSchemeContext, Webview};
+
+use crate::{audit, store, APP_HANDLE};
+
+// ===========================================================================
+// Fonts
+// ===========================================================================
+//
+// `list_fonts()` enumerates the font families installed on the system so the
+// appearance settings can offer them for the chat and code fonts. The OS
+// font directories are scanned and each file's OpenType `name` table is read
+// directly (only the table directory, `name`, `post` and `OS/2` are touched,
+// not the glyph data), so it behaves the same everywhere:
+//
+//   Windows: `%WINDIR%\Fonts`, `%LOCALAPPDATA%\Microsoft\Windows\Fonts`
+//   macOS:   `/System/Library/Fonts`, `/Library/Fonts`, `~/Library/Fonts`
+//   Linux:   `/usr/share/fonts`, `/usr/local/share/fonts`,
+//            `~/.local/share/fonts`, `~/.fonts`
+//
+// Users can also import font files (CJK fonts, Nerd Fonts, ...) that aren't
+// installed system-wide. `import_font(path)` copies the file into
+// `<native store>/fonts/` and records it; imported fonts are served to the
+// webview over the `rcfont:` protocol and declared with `@font-face` rules
+// injected into every page load of the main window, so they can be used by
+// family name like any installed font.
+//
+// Only TrueType/OpenType files (`.ttf`, `.otf`) and collections (`.ttc`,
+// `.otc`; the first face is used) are accepted — WOFF needs a decompressor
+// that isn't in the dependency tree.
+// ===========================================================================
+
+const STORE_DOC: &str = "custom-fonts";
+
+/// URI scheme imported fonts are served from.
+pub(crate) const SCHEME: &str = "rcfont";
+
+/// Largest font file `import_font` accepts. Full CJK fonts run to ~20 MB.
+const MAX_FONT_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Upper bound on a `name` table, to skip corrupt files cheaply.
+const MAX_NAME_TABLE_BYTES: usize = 1024 * 1024;
+
+/// How deep font directories are walked.
+const MAX_SCAN_DEPTH: usize = 6;
+
+/// `id` of the injected `<style>` element.
+const STYLE_ELEMENT_ID: &str = "ripcord-custom-fonts";
+
+/// A font family available to the webview.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FontFamily {
+    pub family: String,
+    /// Every face is fixed-pitch (`post.isFixedPitch`).
+    pub monospace: bool,
+    /// Imported with `import_font` rather than installed on the system.
+    pub custom: bool,
+}
+
+/// An imported font file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CustomFont {
+    pub id: String,
+    pub family: String,
+    /// Original file name, for display.
+    pub file_name: String,
+    /// CSS weight (`OS/2.usWeightClass`).
+    pub weight: u16,
+    pub italic: bool,
+    pub monospace: bool,
+    /// File extension of the stored copy.
+    pub ext: String,
+}
+
+/// What was read from one face.
+#[derive(Debug, Clone)]
+struct Face {
+    family: String,
+    weight: u16,
+    italic: bool,
+    monospace: bool,
+}
+
+static CUSTOM_FONTS: Mutex<Option<Vec<CustomFont>>> = Mutex::new(None);
+
+/// System families from the last scan.
+static SYSTEM_FONTS: Mutex<Option<Vec<FontFamily>>> = Mutex::new(None);
+
+fn with_custom_fonts<R>(f: impl FnOnce(&mut Vec<CustomFont>) -> R) -> R {
+    let mut guard = CUSTOM_FONTS.lock().unwrap();
+    f(guard.get_or_insert_with(|| store::load(STORE_DOC)))
+}
+
+fn fonts_dir() -> Option<PathBuf> {
+    let dir = store::dir()?.join("fonts");
+    std::fs::create_dir_all(&dir).ok()?;
+    Some(dir)
+}
+
+// ---------------------------------------------------------------------------
+// sfnt parsing
+// ---------------------------------------------------------------------------
+
+fn be_u16(b: &[u8]) -> u16 {
+    u16::from_be_bytes([b[0], b[1]])
+}
+
+fn be_u32(b: &[u8]) -> u32 {
+    u32::from_be_bytes([b[0], b[1], b[2], b[3]])
+}
+
+fn read_at(file: &mut File, offset: u64, len: usize) -> Option<Vec<u8>> {
+    let mut buf = vec![0; len];
+    file.seek(SeekFrom::Start(offset)).ok()?;
+    file.read_exact(&mut buf).ok()?;
+    Some(buf)
+}
+
+/// Whether the extension is one `read_face` understands.
+fn is_font_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            matches!(
+                ext.to_ascii_lowercase().as_str(),
+                "ttf" | "otf" | "ttc" | "otc"
+            )
+        })
+}
+
+/// Best family name from a `name` table: the typographic family (ID 16)
+/// over the legacy family (ID 1), Windows English over other languages
+/// and platforms.
+fn family_name(table: &[u8]) -> Option<String> {
+    let count = usize::from(be_u16(table.get(2..4)?));
+    let strings = usize::from(be_u16(table.get(4..6)?));
+
+    let mut best: Option<(u32, String)> = None;
+    for i in 0..count {
+        let record = table.get(6 + i * 12..18 + i * 12)?;
+        let (platform, language, name_id) =
+            (be_u16(record), be_u16(&record[4..]), be_u16(&record[6..]));
+        let id_rank = match name_id {
+            16 => 0,
+            1 => 4,
+            _ => continue,
+        };
+        let platform_rank = match (platform, language) {
+            (3, 0x0409) => 0,
+            (3, _) => 1,
+            (0, _) => 2,
+            (1, 0) => 3,
+            _ => continue,
+        };
+        let rank = id_rank + platform_rank;
+        if best.as_ref().is_some_and(|(best, _)| *best <= rank) {
+            continue;
+        }
+
+        let start = strings + usize::from(be_u16(&record[10..]));
+        let Some(bytes) = table.get(start..start + usize::from(be_u16(&record[8..]))) else {
+            continue;
+        };
+        let name = match platform {
+            // Mac Roman; family names are ASCII in practice.
+            1 => bytes.iter().map(|&b| char::from(b)).collect(),
+            _ => {
+                let units: Vec<u16> = bytes.chunks_exact(2).map(be_u16).collect();
+                String::from_utf16_lossy(&units)
+            }
+        };
+        let name = name.trim().to_string();
+        if !name.is_empty() {
+            best = Some((rank, name));
+        }
+    }
+    best.map(|(_, name)| name)
+}
+
+/// Read the first face of a font file.
+fn read_face(path: &Path) -> Option<Face> {
+    let mut file = File::open(path).ok()?;
+    let header = read_at(&mut file, 0, 12)?;
+    let offset = match &header[..4] {
+        b"ttcf" => u64::from(be_u32(&read_at(&mut file, 12, 4)?)),
+        [0, 1, 0, 0] | b"OTTO" | b"true" => 0,
+        _ => return None,
+    };
+
+    let directory = read_at(&mut file, offset, 12)?;
+    let tables = usize::from(be_u16(&directory[4..]));
+    let records = read_at(&mut file, offset + 12, tables * 16)?;
+    let table = |tag: &[u8]| {
+        records
+            .chunks_exact(16)
+            .find(|record| &record[..4] == tag)
+            .map(|record| {
+                (
+                    u64::from(be_u32(&record[8..])),
+                    be_u32(&record[12..]) as usize,
+                )
+            })
+    };
+
+    let (name_at, name_len) = table(b"name")?;
+    if name_len > MAX_NAME_TABLE_BYTES {
+        return None;
+    }
+    let family = family_name(&read_at(&mut file, name_at, name_len)?)?;
+
+    let monospace = table(b"post")
+        .and_then(|(at, _)| read_at(&mut file, at + 12, 4))
+        .is_some_and(|fixed| be_u32(&fixed) != 0);
+    // usWeightClass at 4, fsSelection (bit 0 = italic) at 62.
+    let (weight, italic) = table(b"OS/2")
+        .and_then(|(at, len)| (len >= 64).then_some(at))
+        .and_then(|at| read_at(&mut file, at, 64))
+        .map_or((400, false), |os2| {
+            (be_u16(&os2[4..]), be_u16(&os2[62..]) & 1 != 0)
+        });
+
+    Some(Face {
+        family,
+        weight: weight.clamp(1, 1000),
+        italic,
+        monospace,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// System fonts
+// ---------------------------------------------------------------------------
+
+fn system_font_dirs() -> Vec<PathBuf> {
+    let env_dir =
+        |var: &str, rest: &str| std::env::var_os(var).map(|dir| Path::new(&dir).join(rest));
+
+    #[cfg(target_os = "windows")]
+    {
+        [
+            env_dir("WINDIR", "Fonts"),
+            env_dir("LOCALAPPDATA", "Microsoft\\Windows\\Fonts"),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let mut dirs = vec![
+            PathBuf::from("/System/Library/Fonts"),
+            PathBuf::from("/Library/Fonts"),
+        ];
+        dirs.extend(env_dir("HOME", "Library/Fonts"));
+        dirs
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        let mut dirs = vec![
+            PathBuf::from("/usr/share/fonts"),
+            PathBuf::from("/usr/local/share/fonts"),
+        ];
+        dirs.extend(env_dir("HOME", ".local/share/fonts"));
+        dirs.extend(env_dir("HOME", ".fonts"));
+        dirs
+    }
+}
+
+fn scan_dir(dir: &Path, depth: usize, faces: &mut Vec<Face>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(kind) = entry.file_type() else {
+            continue;
+        };
+        if kind.is_dir() && depth < MAX_SCAN_DEPTH {
+            scan_dir(&path, depth + 1, faces);
+        } else if is_font_file(&path) {
+            faces.extend(read_face(&path));
+        }
+    }
+}
+
+/// Scan the system font directories and merge faces into families.
+fn scan_system_fonts() -> Vec<FontFamily> {
+    let mut faces = Vec::new();
+    for dir in system_font_dirs() {
+        scan_dir(&dir, 0, &mut faces);
+    }
+
+    // Keyed case-insensitively; the first spelling seen wins.
+    let mut families: BTreeMap<String, FontFamily> = BTreeMap::new();
+    for face in faces {
+        families
+            .entry(face.family.to_lowercase())
+            .and_modify(|family| family.monospace &= face.monospace)
+            .or_insert(FontFamily {
+                family: face.family,
+                monospace: face.monospace,
+                custom: false,
+            });
+    }
+    families.into_values().collect()
+}
+
+// ---------------------------------------------------------------------------
+// Webview registration
+// ---------------------------------------------------------------------------
+
+/// URL an imported font is served at. Windows and Android webviews reach
+/// custom schemes through `http://<scheme>.localhost`.
+fn font_url(font: &CustomFont) -> String {
+    let file = format!("{}.{}", font.id, font.ext);
+    if cfg!(any(target_os = "windows", target_os = "android")) {
+        format!("http://{SCHEME}.localhost/{file}")
+    } else {
+        format!("{SCHEME}://localhost/{file}")
+    }
+}
+
+/// `@font-face` rules for every imported font.
+fn font_face_css() -> String {
+    with_custom_fonts(|fonts| {
+        fonts
+            .iter()
+            .map(|font| {
+                // Family names come from font files; keep them inside the
+                // CSS string.
+                let family: String = font
+                    .family
+                    .chars()
+                    .filter(|c| !c.is_control() && !matches!(c, '"' | '\\'))
+                    .collect();
+                format!(
+                    "@font-face{{font-family:\"{family}\";src:url(\"{}\");font-weight:{};font-style:{};font-display:swap}}\n",
+                    font_url(font),
+                    font.weight,
+                    if font.italic { "italic" } else { "normal" },
+                )
+            })
+            .collect()
+    })
+}
+
+/// Script that creates or replaces the injected `<style>` element.
+fn register_script() -> String {
+    let css = serde_json::to_string(&font_face_css()).unwrap_or_else(|_| "\"\"".into());
+    format!(
+        "(() => {{\
+            let style = document.getElementById('{STYLE_ELEMENT_ID}');\
+            if (!style) {{\
+                style = document.createElement('style');\
+                style.id = '{STYLE_ELEMENT_ID}';\
+                document.head.appendChild(style);\
+            }}\
+            style.textContent = {css};\
+        }})();"
+    )
+}
+
+/// Declare imported fonts in a freshly loaded page. Called from the
+/// lifecycle page-load hook.
+pub(crate) fn register_with<R: Runtime>(webview: &Webview<R>) {
+    let _ = webview.eval(&register_script());
+}
+
+/// Re-declare imported fonts in the running main window after a change.
+fn refresh_webview() {
+    if let Some(window) = APP_HANDLE
+        .get()
+        .and_then(|handle| handle.get_webview_window("main"))
+    {
+        let _ = window.eval(&register_script());
+    }
+}
+
+/// `rcfont:` protocol handler — serves imported font files by stored name.
+pub(crate) fn serve<R: Runtime>(
+    _ctx: UriSchemeContext<'_, R>,
+    request: Request<Vec<u8>>,
+) -> Response<Vec<u8>> {
+    let not_found = || Response::builder().status(404).body(Vec::new()).unwrap();
+
+    let name = request.uri().path().trim_start_matches('/');
+    let known = with_custom_fonts(|fonts| {
+        fonts
+            .iter()
+            .find(|font| format!("{}.{}", font.id, font.ext) == name)
+            .map(|font| font.ext.clone())
+    });
+    let Some(ext) = known else {
+        return not_found();
+    };
+    let Some(data) = fonts_dir().and_then(|dir| std::fs::read(dir.join(name)).ok()) else {
+        return not_found();
+    };
+
+    let mime = match ext.as_str() {
+        "otf" => "font/otf",
+        "ttc" | "otc" => "font/collection",
+        _ => "font/ttf",
+    };
+    Response::builder()
+        .header("Content-Type", mime)
+        .header("Access-Control-Allow-Origin", "*")
+        .body(data)
+        .unwrap_or_else(|_| not_found())
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Installed and imported font families, sorted by name. The system scan is
+/// cached; pass `refresh` to rescan after installing fonts.
+#[tauri::command]
+pub(crate) async fn list_fonts(refresh: Option<bool>) -> Result<Vec<FontFamily>, String> {
+    let cached = SYSTEM_FONTS.lock().unwrap().clone();
+    let system = match cached {
+        Some(system) if !refresh.unwrap_or(false) => system,
+        _ => {
+            let system = tauri::async_runtime::spawn_blocking(scan_system_fonts)
+                .await
+                .map_err(|e| e.to_string())?;
+            *SYSTEM_FONTS.lock().unwrap() = Some(system.clone());
+            system
+        }
+    };
+
+    let mut families: BTreeMap<String, FontFamily> = system
+        .into_iter()
+        .map(|family| (family.family.to_lowercase(), family))
+        .collect();
+    with_custom_fonts(|fonts| {
+        for font in fonts.iter() {
+            families
+                .entry(font.family.to_lowercase())
+                .and_modify(|family| {
+                    family.monospace &= font.monospace;
+                    family.custom = true;
+                })
+                .or_insert(FontFamily {
+                    family: font.family.clone(),
+                    monospace: font.monospace,
+                    custom: true,
+                });
+        }
+    });
+    Ok(families.into_values().collect())
+}
+
+/// Imported fonts, one entry per file.
+#[tauri::command]
+pub(crate) fn list_custom_fonts() -> Vec<CustomFont> {
+    with_custom_fonts(|fonts| fonts.clone())
+}
+
+/// Copy a font file into the app and make it available to the webview.
+#[tauri::command]
+pub(crate) async fn import_font(path: String) -> Result<CustomFont, String> {
+    audit::record_file("fileRead", "import_font", &path);
+    let source = PathBuf::from(path);
+    let ext = source
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase)
+        .filter(|_| is_font_file(&source))
+        .ok_or("unsupported font file; use TTF, OTF, TTC or OTC")?;
+
+    let font = tauri::async_runtime::spawn_blocking(move || -> Result<CustomFont, String> {
+        let size = std::fs::metadata(&source).map_err(|e| e.to_string())?.len();
+        if size > MAX_FONT_BYTES {
+            return Err(format!(
+                "font file is too large ({} MB, limit {} MB)",
+                size / (1024 * 1024),
+                MAX_FONT_BYTES / (1024 * 1024)
+            ));
+        }
+        let face = read_face(&source).ok_or("not a readable TrueType/OpenType font")?;
+
+        let font = CustomFont {
+            id: store::new_id(),
+            family: face.family,
+            file_name: source
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            weight: face.weight,
+            italic: face.italic,
+            monospace: face.monospace,
+            ext,
+        };
+        let dir = fonts_dir().ok_or("native store directory unavailable")?;
+        std::fs::copy(&source, dir.join(format!("{}.{}", font.id, font.ext)))
+            .map_err(|e| e.to_string())?;
+        Ok(font)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    with_custom_fonts(|fonts| {
+        fonts.push(font.clone());
+        store::save(STORE_DOC, fonts)
+    })?;
+    refresh_webview();
+    Ok(font)
+}
+
+/// Remove an imported font. Returns whether it existed.
+#[tauri::command]
+pub(crate) fn remove_custom_font(id: String) -> Result<bool, String> {
+    let removed = with_custom_fonts(|fonts| {
+        let Some(index) = fonts.iter().position(|font| font.id == id) else {
+            return Ok(None);
+        };
+        let font = fonts.remove(index);
+        store::save(STORE_DOC, fonts)?;
+        Ok::<_, String>(Some(font))
+    })?;
+    let Some(font) = removed else {
+        return Ok(false);
+    };
+
+    if let Some(dir) = fonts_dir() {
+        let _ = std::fs::remove_file(dir.join(format!("{}.{}", font.id, font.ext)));
+    }
+    refresh_webview();
+    Ok(true)
+}
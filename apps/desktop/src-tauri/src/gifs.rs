@@ -0,0 +1,363 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri_plugin_http::reqwest;
+
+use crate::{audit, scheduler, store};
+
+// ===========================================================================
+// GIF Search Proxy
+// ===========================================================================
+//
+// `gif_search(query, provider)` queries Tenor or Giphy from native code so
+// the provider API keys live in the native store instead of the frontend
+// bundle. An empty query returns the provider's trending/featured set.
+//
+// Results are cached in memory per (provider, query, limit) for a few
+// minutes, and the last trending set per provider is also persisted so the
+// picker can show something immediately on launch (and offline) while it
+// refreshes. Provider requests time out after `REQUEST_TIMEOUT` and read at
+// most `MAX_RESPONSE_BYTES`.
+//
+// Previews use the providers' own small WebP renditions (Tenor `tinywebp`,
+// Giphy `fixed_width` WebP), falling back to their small GIFs. Transcoding
+// locally is blocked (see tasks/todo.md): there is no image codec in the
+// dependency tree.
+// ===========================================================================
+
+const KEYS_DOC: &str = "gif-providers";
+const TRENDING_DOC: &str = "gif-trending";
+const CACHE_TTL_MS: u64 = 5 * 60 * 1000;
+const CACHE_CAPACITY: usize = 256;
+const DEFAULT_LIMIT: u32 = 30;
+const MAX_LIMIT: u32 = 50;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_RESPONSE_BYTES: usize = 2 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum GifProvider {
+    Tenor,
+    Giphy,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct ProviderKeys {
+    tenor: Option<String>,
+    giphy: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GifResult {
+    pub id: String,
+    pub title: String,
+    /// Full-size GIF to post.
+    pub url: String,
+    /// Small rendition for the picker grid.
+    pub preview_url: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrendingSnapshot {
+    fetched_at: u64,
+    results: Vec<GifResult>,
+}
+
+struct State {
+    keys: ProviderKeys,
+}
+
+static STATE: Mutex<Option<State>> = Mutex::new(None);
+
+/// Serializes writes of the trending snapshots.
+static TRENDING_SAVE: Mutex<()> = Mutex::new(());
+
+fn with_state<R>(f: impl FnOnce(&mut State) -> R) -> R {
+    let mut guard = STATE.lock().unwrap();
+    f(guard.get_or_insert_with(|| State {
+        keys: store::load(KEYS_DOC),
+    }))
+}
+
+/// (provider, lowercased query, clamped limit)
+type CacheKey = (GifProvider, String, u32);
+
+#[derive(Default)]
+struct Cache {
+    entries: HashMap<CacheKey, (u64, Vec<GifResult>)>,
+    order: VecDeque<CacheKey>,
+}
+
+static CACHE: Mutex<Option<Cache>> = Mutex::new(None);
+
+fn cache_get(key: &CacheKey, now: u64) -> Option<Vec<GifResult>> {
+    let guard = CACHE.lock().unwrap();
+    let (at, results) = guard.as_ref()?.entries.get(key)?;
+    (now.saturating_sub(*at) < CACHE_TTL_MS).then(|| results.clone())
+}
+
+fn cache_put(key: CacheKey, results: Vec<GifResult>, now: u64) {
+    let mut guard = CACHE.lock().unwrap();
+    let cache = guard.get_or_insert_with(Cache::default);
+    if cache.entries.insert(key.clone(), (now, results)).is_none() {
+        cache.order.push_back(key);
+    }
+    while cache.order.len() > CACHE_CAPACITY {
+        if let Some(oldest) = cache.order.pop_front() {
+            cache.entries.remove(&oldest);
+        }
+    }
+}
+
+fn trending_doc(provider: GifProvider) -> String {
+    match provider {
+        GifProvider::Tenor => format!("{TRENDING_DOC}-tenor"),
+        GifProvider::Giphy => format!("{TRENDING_DOC}-giphy"),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Providers
+// ---------------------------------------------------------------------------
+
+#[derive(Deserialize)]
+struct TenorResponse {
+    results: Vec<TenorGif>,
+}
+
+#[derive(Deserialize)]
+struct TenorGif {
+    id: String,
+    #[serde(default)]
+    content_description: String,
+    media_formats: HashMap<String, TenorMedia>,
+}
+
+#[derive(Deserialize)]
+struct TenorMedia {
+    url: String,
+    #[serde(default)]
+    dims: Vec<u32>,
+}
+
+#[derive(Deserialize)]
+struct GiphyResponse {
+    data: Vec<GiphyGif>,
+}
+
+#[derive(Deserialize)]
+struct GiphyGif {
+    id: String,
+    #[serde(default)]
+    title: String,
+    images: HashMap<String, GiphyImage>,
+}
+
+#[derive(Deserialize)]
+struct GiphyImage {
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    webp: Option<String>,
+    /// Giphy sends dimensions as strings.
+    #[serde(default)]
+    width: String,
+    #[serde(default)]
+    height: String,
+}
+
+async fn get_json(request: reqwest::RequestBuilder) -> Result<Vec<u8>, String> {
+    let mut response = request
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| format!("GIF request failed: {e}"))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("GIF provider returned {status}"));
+    }
+    let too_large = || "GIF provider response is too large".to_string();
+    if response
+        .content_length()
+        .is_some_and(|len| len > MAX_RESPONSE_BYTES as u64)
+    {
+        return Err(too_large());
+    }
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        if bytes.len() + chunk.len() > MAX_RESPONSE_BYTES {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+async fn search_tenor(key: &str, query: &str, limit: u32) -> Result<Vec<GifResult>, String> {
+    let client = reqwest::Client::new();
+    let request = if query.is_empty() {
+        client.get("https://tenor.googleapis.com/v2/featured")
+    } else {
+        client
+            .get("https://tenor.googleapis.com/v2/search")
+            .query(&[("q", query)])
+    };
+    let limit = limit.to_string();
+    let bytes = get_json(request.query(&[
+        ("key", key),
+        ("limit", limit.as_str()),
+        ("media_filter", "gif,tinywebp,tinygif"),
+    ]))
+    .await?;
+
+    let parsed: TenorResponse = serde_json::from_slice(&bytes).map_err(|e| e.to_string())?;
+    Ok(parsed
+        .results
+        .into_iter()
+        .filter_map(|gif| {
+            let full = gif.media_formats.get("gif")?;
+            let preview = gif
+                .media_formats
+                .get("tinywebp")
+                .or_else(|| gif.media_formats.get("tinygif"))
+                .unwrap_or(full);
+            Some(GifResult {
+                id: gif.id,
+                title: gif.content_description,
+                url: full.url.clone(),
+                preview_url: preview.url.clone(),
+                width: full.dims.first().copied().unwrap_or(0),
+                height: full.dims.get(1).copied().unwrap_or(0),
+            })
+        })
+        .collect())
+}
+
+async fn search_giphy(key: &str, query: &str, limit: u32) -> Result<Vec<GifResult>, String> {
+    let client = reqwest::Client::new();
+    let request = if query.is_empty() {
+        client.get("https://api.giphy.com/v1/gifs/trending")
+    } else {
+        client
+            .get("https://api.giphy.com/v1/gifs/search")
+            .query(&[("q", query)])
+    };
+    let limit = limit.to_string();
+    let bytes = get_json(request.query(&[("api_key", key), ("limit", limit.as_str())])).await?;
+
+    let parsed: GiphyResponse = serde_json::from_slice(&bytes).map_err(|e| e.to_string())?;
+    Ok(parsed
+        .data
+        .into_iter()
+        .filter_map(|gif| {
+            let full = gif.images.get("original")?;
+            let preview = gif
+                .images
+                .get("fixed_width")
+                .and_then(|p| p.webp.clone().or_else(|| Some(p.url.clone())))
+                .unwrap_or_else(|| full.url.clone());
+            Some(GifResult {
+                id: gif.id,
+                title: gif.title,
+                url: full.url.clone(),
+                preview_url: preview,
+                width: full.width.parse().unwrap_or(0),
+                height: full.height.parse().unwrap_or(0),
+            })
+        })
+        .collect())
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Store (or clear, with `None`) the API key for a GIF provider.
+#[tauri::command]
+pub(crate) fn set_gif_provider_key(
+    provider: GifProvider,
+    api_key: Option<String>,
+) -> Result<(), String> {
+    let api_key = api_key.filter(|k| !k.trim().is_empty());
+    let change = if api_key.is_some() {
+        "stored"
+    } else {
+        "cleared"
+    };
+    audit::record(
+        "credentialChanged",
+        "set_gif_provider_key",
+        format!("{provider:?} API key {change}"),
+    );
+    with_state(|state| {
+        match provider {
+            GifProvider::Tenor => state.keys.tenor = api_key,
+            GifProvider::Giphy => state.keys.giphy = api_key,
+        }
+        store::save(KEYS_DOC, &state.keys)
+    })
+}
+
+/// Search `provider` for `query`; an empty query returns trending GIFs.
+#[tauri::command]
+pub(crate) async fn gif_search(
+    query: String,
+    provider: GifProvider,
+    limit: Option<u32>,
+) -> Result<Vec<GifResult>, String> {
+    let query = query.trim().to_string();
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let now = scheduler::now_ms();
+    let cache_key = (provider, query.to_lowercase(), limit);
+    if let Some(hit) = cache_get(&cache_key, now) {
+        return Ok(hit);
+    }
+
+    let key = with_state(|state| match provider {
+        GifProvider::Tenor => state.keys.tenor.clone(),
+        GifProvider::Giphy => state.keys.giphy.clone(),
+    })
+    .ok_or("no API key configured for this GIF provider")?;
+
+    let results = match provider {
+        GifProvider::Tenor => search_tenor(&key, &query, limit).await,
+        GifProvider::Giphy => search_giphy(&key, &query, limit).await,
+    };
+    let results = match results {
+        Ok(results) => results,
+        // Offline or rate-limited: fall back to the last trending snapshot.
+        Err(e) if query.is_empty() => {
+            let snapshot: Option<TrendingSnapshot> = store::load(&trending_doc(provider));
+            return snapshot.map(|s| s.results).ok_or(e);
+        }
+        Err(e) => return Err(e),
+    };
+
+    if query.is_empty() {
+        let snapshot = Some(TrendingSnapshot {
+            fetched_at: now,
+            results: results.clone(),
+        });
+        let _ = tauri::async_runtime::spawn_blocking(move || {
+            let _guard = TRENDING_SAVE.lock().unwrap();
+            store::save(&trending_doc(provider), &snapshot)
+        })
+        .await;
+    }
+    cache_put(cache_key, results.clone(), now);
+    Ok(results)
+}
+
+/// Last persisted trending set for `provider`, for instant display on open.
+#[tauri::command]
+pub(crate) fn get_cached_trending_gifs(provider: GifProvider) -> Vec<GifResult> {
+    let snapshot: Option<TrendingSnapshot> = store::load(&trending_doc(provider));
+    snapshot.map(|s| s.results).unwrap_or_default()
+}
@@ -0,0 +1,552 @@
+use serde::Serialize;
+
+// ===========================================================================
+// GPU and Session Diagnostics
+// ===========================================================================
+//
+// `get_gpu_info()` lists the graphics adapters and describes the session the
+// app is running in, so settings can pick sensible hardware-acceleration and
+// encoder defaults and support can see what a user's machine looks like:
+//
+//   Windows: DXGI adapter enumeration (name, PCI ids, dedicated memory, UMD
+//            driver version); `SM_REMOTESESSION` for Remote Desktop
+//   macOS:   `system_profiler SPDisplaysDataType` (name, ids, VRAM, Metal
+//            family); `kern.hv_vmm_present` for virtualization
+//   Linux:   `/sys/class/drm` (PCI ids, kernel driver and its version, names
+//            from `pci.ids` when installed); xrdp/X2Go session variables
+//
+// `webview` marks the adapter the webview renders with by default: DXGI
+// adapter 0 on Windows (the one driving the primary display, which WebView2
+// uses unless the user set a per-app GPU preference), the GPU driving the
+// main display on macOS, and the boot VGA device on Linux.
+//
+// Virtual machines are detected from the CPUID hypervisor vendor (x86) or
+// DMI strings. A Hyper-V hypervisor alone is not enough, since Windows runs
+// the host itself under Hyper-V when VBS is on; it only counts when there is
+// no hardware GPU either.
+// ===========================================================================
+
+/// One graphics adapter.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GpuAdapter {
+    pub name: String,
+    /// "NVIDIA", "AMD", "Intel", ... or the PCI vendor id in hex.
+    pub vendor: String,
+    pub vendor_id: Option<u32>,
+    pub device_id: Option<u32>,
+    pub driver: Option<String>,
+    pub driver_version: Option<String>,
+    pub dedicated_memory_mb: Option<u64>,
+    /// A software rasterizer or basic display driver rather than real
+    /// hardware.
+    pub software: bool,
+    /// The adapter the webview renders with by default.
+    pub webview: bool,
+}
+
+/// How the app is being displayed.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SessionInfo {
+    /// Running in a Remote Desktop (or xrdp/X2Go) session.
+    pub remote_desktop: bool,
+    /// Running inside a virtual machine.
+    pub virtual_machine: bool,
+    /// Hypervisor vendor, if one is present (may be the host's own Hyper-V).
+    pub hypervisor: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GpuInfo {
+    pub adapters: Vec<GpuAdapter>,
+    pub session: SessionInfo,
+}
+
+/// Display name for a PCI vendor id.
+fn vendor_name(id: u32) -> String {
+    match id {
+        0x10DE => "NVIDIA",
+        0x1002 | 0x1022 => "AMD",
+        0x8086 => "Intel",
+        0x106B => "Apple",
+        0x1414 => "Microsoft",
+        0x5143 => "Qualcomm",
+        0x15AD => "VMware",
+        0x80EE => "VirtualBox",
+        0x1AF4 => "Red Hat (virtio)",
+        0x1234 => "QEMU",
+        _ => return format!("0x{id:04x}"),
+    }
+    .to_string()
+}
+
+// ---------------------------------------------------------------------------
+// Virtualization
+// ---------------------------------------------------------------------------
+
+/// CPUID hypervisor vendor string, if the hypervisor bit is set.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[allow(unused_unsafe)] // `__cpuid` is only safe on newer toolchains
+fn hypervisor_vendor() -> Option<String> {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::__cpuid;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::__cpuid;
+
+    // Leaf 1, ECX bit 31: running under a hypervisor.
+    let features = unsafe { __cpuid(1) };
+    if features.ecx & (1 << 31) == 0 {
+        return None;
+    }
+    let vendor = unsafe { __cpuid(0x4000_0000) };
+    let bytes: Vec<u8> = [vendor.ebx, vendor.ecx, vendor.edx]
+        .iter()
+        .flat_map(|reg| reg.to_le_bytes())
+        .collect();
+    let vendor = String::from_utf8_lossy(&bytes)
+        .trim_matches(char::from(0))
+        .trim()
+        .to_string();
+    Some(vendor)
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+fn hypervisor_vendor() -> Option<String> {
+    #[cfg(target_os = "macos")]
+    {
+        // Apple Silicon has no CPUID; the kernel says whether it is a guest.
+        let mut present: i32 = 0;
+        let mut len = std::mem::size_of::<i32>();
+        let ok = unsafe {
+            macos::sysctlbyname(
+                c"kern.hv_vmm_present".as_ptr(),
+                (&mut present as *mut i32).cast(),
+                &mut len,
+                std::ptr::null(),
+                0,
+            )
+        } == 0;
+        if ok && present != 0 {
+            return Some("Apple Virtualization".into());
+        }
+    }
+    None
+}
+
+/// Hypervisor vendors that only run guests. Hyper-V ("Microsoft Hv") is
+/// left out: it also hosts Windows itself.
+fn is_guest_hypervisor(vendor: &str) -> bool {
+    const GUEST_VENDORS: [&str; 9] = [
+        "VMwareVMware",
+        "KVMKVMKVM",
+        "VBoxVBoxVBox",
+        "XenVMMXenVMM",
+        "prl hyperv",
+        "TCGTCGTCGTCG",
+        "bhyve bhyve",
+        "QNXQVMBSQG",
+        "Apple Virtualization",
+    ];
+    GUEST_VENDORS.iter().any(|guest| vendor.starts_with(guest))
+}
+
+/// Firmware vendor/product strings that identify a VM (Linux).
+#[cfg(target_os = "linux")]
+fn dmi_says_vm() -> bool {
+    const MARKERS: [&str; 7] = [
+        "VirtualBox",
+        "VMware",
+        "QEMU",
+        "KVM",
+        "Xen",
+        "Parallels",
+        "Virtual Machine",
+    ];
+    ["sys_vendor", "product_name"].iter().any(|field| {
+        std::fs::read_to_string(format!("/sys/class/dmi/id/{field}"))
+            .is_ok_and(|value| MARKERS.iter().any(|marker| value.contains(marker)))
+    })
+}
+
+/// Whether the app is being shown over Remote Desktop, xrdp or X2Go. Cheap
+/// enough to poll.
+pub(crate) fn remote_desktop() -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        unsafe { win32::GetSystemMetrics(win32::SM_REMOTESESSION) != 0 }
+    }
+    #[cfg(target_os = "linux")]
+    {
+        std::env::var_os("XRDP_SESSION").is_some() || std::env::var_os("X2GO_SESSION").is_some()
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        false
+    }
+}
+
+/// Describe the current session. `adapters` is only called for the
+/// ambiguous Hyper-V case, since listing them is slow on macOS.
+fn session(adapters: impl FnOnce() -> Vec<GpuAdapter>) -> SessionInfo {
+    let hypervisor = hypervisor_vendor();
+
+    #[allow(unused_mut)]
+    let mut virtual_machine = match &hypervisor {
+        Some(vendor) => {
+            is_guest_hypervisor(vendor) || adapters().iter().all(|adapter| adapter.software)
+        }
+        None => false,
+    };
+    #[cfg(target_os = "linux")]
+    {
+        virtual_machine |= dmi_says_vm();
+    }
+
+    SessionInfo {
+        remote_desktop: remote_desktop(),
+        virtual_machine,
+        hypervisor,
+    }
+}
+
+/// Remote-desktop and VM detection, without the adapter details.
+pub(crate) fn detect_session() -> SessionInfo {
+    session(list_adapters)
+}
+
+// ---------------------------------------------------------------------------
+// Windows (DXGI)
+// ---------------------------------------------------------------------------
+
+#[cfg(target_os = "windows")]
+#[allow(non_camel_case_types, non_snake_case, clippy::upper_case_acronyms)]
+mod win32 {
+    use std::ffi::c_void;
+
+    pub const SM_REMOTESESSION: i32 = 0x1000;
+    pub const DXGI_ADAPTER_FLAG_SOFTWARE: u32 = 2;
+
+    #[repr(C)]
+    pub struct GUID {
+        pub data1: u32,
+        pub data2: u16,
+        pub data3: u16,
+        pub data4: [u8; 8],
+    }
+
+    pub const IID_IDXGIFactory1: GUID = GUID {
+        data1: 0x770a_ae78,
+        data2: 0xf26f,
+        data3: 0x4dba,
+        data4: [0xa8, 0x29, 0x25, 0x3c, 0x83, 0xd1, 0xb3, 0x87],
+    };
+    pub const IID_IDXGIDevice: GUID = GUID {
+        data1: 0x54ec_77fa,
+        data2: 0x1377,
+        data3: 0x44e6,
+        data4: [0x8c, 0x32, 0x88, 0xfd, 0x5f, 0x44, 0xc8, 0x4c],
+    };
+
+    #[repr(C)]
+    pub struct DXGI_ADAPTER_DESC1 {
+        pub description: [u16; 128],
+        pub vendor_id: u32,
+        pub device_id: u32,
+        pub sub_sys_id: u32,
+        pub revision: u32,
+        pub dedicated_video_memory: usize,
+        pub dedicated_system_memory: usize,
+        pub shared_system_memory: usize,
+        pub adapter_luid: [u32; 2],
+        pub flags: u32,
+    }
+
+    /// `IDXGIFactory1` vtable, up to `EnumAdapters1`.
+    #[repr(C)]
+    pub struct IDXGIFactory1Vtbl {
+        pub query_interface: usize,
+        pub add_ref: usize,
+        pub release: unsafe extern "system" fn(*mut c_void) -> u32,
+        /// IDXGIObject (4) and IDXGIFactory (5) methods.
+        pub _inherited: [usize; 9],
+        pub enum_adapters1: unsafe extern "system" fn(*mut c_void, u32, *mut *mut c_void) -> i32,
+    }
+
+    /// `IDXGIAdapter1` vtable, up to `GetDesc1`.
+    #[repr(C)]
+    pub struct IDXGIAdapter1Vtbl {
+        pub query_interface: usize,
+        pub add_ref: usize,
+        pub release: unsafe extern "system" fn(*mut c_void) -> u32,
+        /// IDXGIObject methods, `EnumOutputs`, `GetDesc`.
+        pub _inherited: [usize; 6],
+        pub check_interface_support:
+            unsafe extern "system" fn(*mut c_void, *const GUID, *mut i64) -> i32,
+        pub get_desc1: unsafe extern "system" fn(*mut c_void, *mut DXGI_ADAPTER_DESC1) -> i32,
+    }
+
+    #[link(name = "dxgi")]
+    extern "system" {
+        pub fn CreateDXGIFactory1(riid: *const GUID, factory: *mut *mut c_void) -> i32;
+    }
+
+    extern "system" {
+        pub fn GetSystemMetrics(index: i32) -> i32;
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn list_adapters() -> Vec<GpuAdapter> {
+    use std::ffi::c_void;
+
+    let mut adapters = Vec::new();
+    let mut factory: *mut c_void = std::ptr::null_mut();
+    if unsafe { win32::CreateDXGIFactory1(&win32::IID_IDXGIFactory1, &mut factory) } < 0 {
+        return adapters;
+    }
+    let factory_vtbl = unsafe { &**(factory as *const *const win32::IDXGIFactory1Vtbl) };
+
+    for index in 0.. {
+        let mut adapter: *mut c_void = std::ptr::null_mut();
+        // Fails with DXGI_ERROR_NOT_FOUND past the last adapter.
+        if unsafe { (factory_vtbl.enum_adapters1)(factory, index, &mut adapter) } < 0 {
+            break;
+        }
+        let vtbl = unsafe { &**(adapter as *const *const win32::IDXGIAdapter1Vtbl) };
+
+        let mut desc: win32::DXGI_ADAPTER_DESC1 = unsafe { std::mem::zeroed() };
+        if unsafe { (vtbl.get_desc1)(adapter, &mut desc) } >= 0 {
+            // The UMD version, packed as four 16-bit parts.
+            let mut umd: i64 = 0;
+            let driver_version = (unsafe {
+                (vtbl.check_interface_support)(adapter, &win32::IID_IDXGIDevice, &mut umd)
+            } >= 0)
+                .then(|| {
+                    let v = umd as u64;
+                    format!(
+                        "{}.{}.{}.{}",
+                        v >> 48,
+                        (v >> 32) & 0xFFFF,
+                        (v >> 16) & 0xFFFF,
+                        v & 0xFFFF
+                    )
+                });
+            let end = desc.description.iter().position(|&c| c == 0).unwrap_or(128);
+
+            adapters.push(GpuAdapter {
+                name: String::from_utf16_lossy(&desc.description[..end]),
+                vendor: vendor_name(desc.vendor_id),
+                vendor_id: Some(desc.vendor_id),
+                device_id: Some(desc.device_id),
+                driver: None,
+                driver_version,
+                dedicated_memory_mb: Some(desc.dedicated_video_memory as u64 / (1024 * 1024)),
+                software: desc.flags & win32::DXGI_ADAPTER_FLAG_SOFTWARE != 0
+                    // "Microsoft Basic Display Adapter" and Hyper-V video.
+                    || desc.vendor_id == 0x1414,
+                webview: index == 0,
+            });
+        }
+        unsafe { (vtbl.release)(adapter) };
+    }
+
+    unsafe { (factory_vtbl.release)(factory) };
+    adapters
+}
+
+// ---------------------------------------------------------------------------
+// macOS (system_profiler)
+// ---------------------------------------------------------------------------
+
+#[cfg(target_os = "macos")]
+mod macos {
+    extern "C" {
+        pub fn sysctlbyname(
+            name: *const std::ffi::c_char,
+            oldp: *mut std::ffi::c_void,
+            oldlenp: *mut usize,
+            newp: *const std::ffi::c_void,
+            newlen: usize,
+        ) -> i32;
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub(crate) fn list_adapters() -> Vec<GpuAdapter> {
+    use serde_json::Value;
+
+    let Ok(output) = std::process::Command::new("/usr/sbin/system_profiler")
+        .args(["SPDisplaysDataType", "-json"])
+        .output()
+    else {
+        return Vec::new();
+    };
+    let Ok(report) = serde_json::from_slice::<Value>(&output.stdout) else {
+        return Vec::new();
+    };
+    let Some(gpus) = report["SPDisplaysDataType"].as_array() else {
+        return Vec::new();
+    };
+
+    let text = |gpu: &Value, key: &str| gpu[key].as_str().map(str::to_string);
+    // "0x67df" or "NVIDIA (0x10de)".
+    let hex_id = |value: Option<String>| {
+        let value = value?;
+        let hex = value.rsplit("0x").next()?.trim_end_matches(')');
+        u32::from_str_radix(hex, 16).ok()
+    };
+    // "8 GB" / "1536 MB".
+    let megabytes = |value: Option<String>| {
+        let value = value?;
+        let (amount, unit) = value.split_once(' ')?;
+        let amount: u64 = amount.parse().ok()?;
+        Some(if unit.starts_with("GB") {
+            amount * 1024
+        } else {
+            amount
+        })
+    };
+
+    gpus.iter()
+        .map(|gpu| {
+            let vendor_text = text(gpu, "spdisplays_vendor");
+            let vendor_id = match vendor_text.as_deref() {
+                Some("sppci_vendor_Apple") => Some(0x106B),
+                other => hex_id(other.map(str::to_string)),
+            };
+            let drives_main_display = gpu["spdisplays_ndrvs"].as_array().is_some_and(|displays| {
+                displays
+                    .iter()
+                    .any(|display| display["spdisplays_main"] == "spdisplays_yes")
+            });
+            GpuAdapter {
+                name: text(gpu, "sppci_model").unwrap_or_else(|| "Unknown GPU".into()),
+                vendor: vendor_id.map(vendor_name).unwrap_or_default(),
+                vendor_id,
+                device_id: hex_id(text(gpu, "spdisplays_device-id")),
+                driver: text(gpu, "spdisplays_mtlgpufamilysupport")
+                    .map(|family| family.replace("spdisplays_", "").replace("metal", "Metal ")),
+                driver_version: None,
+                dedicated_memory_mb: megabytes(
+                    text(gpu, "spdisplays_vram").or_else(|| text(gpu, "spdisplays_vram_shared")),
+                ),
+                software: false,
+                webview: drives_main_display,
+            }
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Linux (sysfs)
+// ---------------------------------------------------------------------------
+
+/// "Vendor Device" from the PCI id database, if one is installed.
+#[cfg(target_os = "linux")]
+fn pci_name(vendor_id: u32, device_id: u32) -> Option<String> {
+    let db = ["/usr/share/hwdata/pci.ids", "/usr/share/misc/pci.ids"]
+        .iter()
+        .find_map(|path| std::fs::read_to_string(path).ok())?;
+
+    let vendor_prefix = format!("{vendor_id:04x}  ");
+    let device_prefix = format!("\t{device_id:04x}  ");
+    let mut lines = db.lines();
+    let vendor = lines.find_map(|line| line.strip_prefix(&vendor_prefix))?;
+    let device = lines
+        .take_while(|line| line.starts_with('\t') || line.starts_with('#') || line.is_empty())
+        .find_map(|line| line.strip_prefix(&device_prefix))?;
+    Some(format!("{vendor} {device}"))
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn list_adapters() -> Vec<GpuAdapter> {
+    use std::path::Path;
+
+    let read = |path: &Path| {
+        std::fs::read_to_string(path)
+            .ok()
+            .map(|s| s.trim().to_string())
+    };
+    let hex = |path: &Path| {
+        read(path).and_then(|s| u32::from_str_radix(s.trim_start_matches("0x"), 16).ok())
+    };
+
+    let Ok(entries) = std::fs::read_dir("/sys/class/drm") else {
+        return Vec::new();
+    };
+    let mut cards: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        // `card0`, not connectors like `card0-DP-1`.
+        .filter(|name| {
+            name.strip_prefix("card")
+                .is_some_and(|n| n.parse::<u32>().is_ok())
+        })
+        .collect();
+    cards.sort();
+
+    cards
+        .iter()
+        .filter_map(|card| {
+            let device = Path::new("/sys/class/drm").join(card).join("device");
+            let driver = std::fs::read_link(device.join("driver"))
+                .ok()
+                .and_then(|target| Some(target.file_name()?.to_string_lossy().into_owned()));
+            let vendor_id = hex(&device.join("vendor"));
+            let device_id = hex(&device.join("device"));
+            if vendor_id.is_none() && driver.is_none() {
+                return None;
+            }
+
+            // Out-of-tree drivers (nvidia) report a module version; in-tree
+            // ones are versioned with the kernel.
+            let driver_version = driver
+                .as_ref()
+                .and_then(|driver| read(&Path::new("/sys/module").join(driver).join("version")))
+                .or_else(|| read(Path::new("/proc/sys/kernel/osrelease")));
+            let name = match (vendor_id, device_id) {
+                (Some(vendor), Some(device)) => pci_name(vendor, device),
+                _ => None,
+            }
+            .or_else(|| driver.clone())
+            .unwrap_or_else(|| card.clone());
+
+            Some(GpuAdapter {
+                name,
+                vendor: vendor_id.map(vendor_name).unwrap_or_default(),
+                vendor_id,
+                device_id,
+                software: matches!(driver.as_deref(), Some("simpledrm" | "vkms")),
+                driver,
+                driver_version,
+                dedicated_memory_mb: read(&device.join("mem_info_vram_total"))
+                    .and_then(|bytes| bytes.parse::<u64>().ok())
+                    .map(|bytes| bytes / (1024 * 1024)),
+                webview: read(&device.join("boot_vga")).as_deref() == Some("1"),
+            })
+        })
+        .collect()
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+pub(crate) fn list_adapters() -> Vec<GpuAdapter> {
+    Vec::new()
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Graphics adapters and session details (remote desktop, VM).
+#[tauri::command]
+pub(crate) async fn get_gpu_info() -> Result<GpuInfo, String> {
+    tauri::async_runtime::spawn_blocking(|| {
+        let adapters = list_adapters();
+        let session = session(|| adapters.clone());
+        GpuInfo { adapters, session }
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
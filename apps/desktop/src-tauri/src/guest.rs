@@ -0,0 +1,127 @@
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+// ===========================================================================
+// Guest Mode
+// ===========================================================================
+//
+// `ripcord --guest` for shared computers. Nothing from the session should
+// outlive the process:
+//
+//   - the main webview is created incognito, so cookies, localStorage and
+//     the HTTP cache stay in memory (the access token already lives only in
+//     the webview's memory)
+//   - the native store is redirected to a per-process temp directory
+//   - that directory is deleted on exit; directories left behind by a guest
+//     session that crashed are swept on the next launch, as soon as the
+//     process id in their name is no longer running
+//
+// The frontend asks `is_guest_session()` to hide "remember me", skip the
+// updater prompt and label the window.
+// ===========================================================================
+
+const DIR_PREFIX: &str = "ripcord-guest-";
+
+static GUEST_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// Per-process data directory when running as a guest.
+pub(crate) fn data_dir() -> Option<&'static PathBuf> {
+    GUEST_DIR
+        .get_or_init(|| {
+            std::env::args()
+                .any(|arg| arg == "--guest")
+                .then(|| std::env::temp_dir().join(format!("{DIR_PREFIX}{}", std::process::id())))
+        })
+        .as_ref()
+}
+
+pub(crate) fn is_guest() -> bool {
+    data_dir().is_some()
+}
+
+#[cfg(unix)]
+mod ffi {
+    /// `EPERM`: the process exists but belongs to another user.
+    pub const EPERM: i32 = 1;
+
+    extern "C" {
+        pub fn kill(pid: i32, signal: i32) -> i32;
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod ffi {
+    pub const PROCESS_QUERY_LIMITED_INFORMATION: u32 = 0x1000;
+    pub const ERROR_ACCESS_DENIED: i32 = 5;
+    pub const STILL_ACTIVE: u32 = 259;
+
+    extern "system" {
+        pub fn OpenProcess(desired_access: u32, inherit_handle: i32, process_id: u32) -> isize;
+        pub fn GetExitCodeProcess(process: isize, exit_code: *mut u32) -> i32;
+        pub fn CloseHandle(handle: isize) -> i32;
+    }
+}
+
+/// Whether a process with this id is running.
+#[cfg(unix)]
+fn process_running(pid: u32) -> bool {
+    let Ok(pid) = i32::try_from(pid) else {
+        return false;
+    };
+    // Signal 0 only checks that the process exists.
+    pid > 0
+        && (unsafe { ffi::kill(pid, 0) } == 0
+            || std::io::Error::last_os_error().raw_os_error() == Some(ffi::EPERM))
+}
+
+/// Whether a process with this id is running.
+#[cfg(target_os = "windows")]
+fn process_running(pid: u32) -> bool {
+    let process = unsafe { ffi::OpenProcess(ffi::PROCESS_QUERY_LIMITED_INFORMATION, 0, pid) };
+    if process == 0 {
+        return std::io::Error::last_os_error().raw_os_error() == Some(ffi::ERROR_ACCESS_DENIED);
+    }
+    let mut code = 0u32;
+    let ok = unsafe { ffi::GetExitCodeProcess(process, &mut code) };
+    unsafe { ffi::CloseHandle(process) };
+    ok != 0 && code == ffi::STILL_ACTIVE
+}
+
+/// Remove guest directories from earlier sessions that didn't exit cleanly:
+/// any whose process id is no longer running. A directory whose id has
+/// been reused stays until that process exits.
+pub(crate) fn sweep_stale() {
+    let Ok(entries) = std::fs::read_dir(std::env::temp_dir()) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let Some(pid) = name
+            .to_str()
+            .and_then(|name| name.strip_prefix(DIR_PREFIX))
+            .and_then(|pid| pid.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        if pid != std::process::id() && !process_running(pid) {
+            let _ = std::fs::remove_dir_all(entry.path());
+        }
+    }
+}
+
+/// Delete this session's data. Called on exit.
+pub(crate) fn wipe() {
+    if let Some(dir) = data_dir() {
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Whether the app was launched with `--guest`.
+#[tauri::command]
+pub(crate) fn is_guest_session() -> bool {
+    is_guest()
+}
@@ -0,0 +1,77 @@
+// ===========================================================================
+// Idle Detection
+// ===========================================================================
+//
+// Reports how long it has been since the user last touched the keyboard or
+// mouse anywhere on the system — not just in Ripcord — using the OS's own
+// input timestamps:
+//
+//   Windows: `GetLastInputInfo` (tick count of the last input event)
+//   macOS:   `CGEventSourceSecondsSinceLastEventType` (any event type)
+//   Linux:   unsupported (X11 needs XScreenSaver, Wayland an idle portal)
+// ===========================================================================
+
+#[cfg(target_os = "windows")]
+mod win32 {
+    #[repr(C)]
+    pub struct LASTINPUTINFO {
+        pub cb_size: u32,
+        pub dw_time: u32,
+    }
+
+    extern "system" {
+        pub fn GetLastInputInfo(plii: *mut LASTINPUTINFO) -> i32;
+        pub fn GetTickCount() -> u32;
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    /// `kCGEventSourceStateCombinedSessionState`
+    pub const COMBINED_SESSION_STATE: i32 = 0;
+    /// `kCGAnyInputEventType`
+    pub const ANY_INPUT_EVENT_TYPE: u32 = !0;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        pub fn CGEventSourceSecondsSinceLastEventType(state_id: i32, event_type: u32) -> f64;
+    }
+}
+
+/// Seconds since the last system-wide user input, or `None` if the platform
+/// cannot tell.
+pub(crate) fn idle_seconds() -> Option<u64> {
+    #[cfg(target_os = "windows")]
+    {
+        let mut info = win32::LASTINPUTINFO {
+            cb_size: std::mem::size_of::<win32::LASTINPUTINFO>() as u32,
+            dw_time: 0,
+        };
+        if unsafe { win32::GetLastInputInfo(&mut info) } == 0 {
+            return None;
+        }
+        // Both are 32-bit tick counts; wrapping_sub handles the 49.7-day wrap.
+        let idle_ms = unsafe { win32::GetTickCount() }.wrapping_sub(info.dw_time);
+        Some(u64::from(idle_ms) / 1000)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let secs = unsafe {
+            macos::CGEventSourceSecondsSinceLastEventType(
+                macos::COMBINED_SESSION_STATE,
+                macos::ANY_INPUT_EVENT_TYPE,
+            )
+        };
+        secs.is_finite().then(|| secs.max(0.0) as u64)
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        None
+    }
+}
+
+/// Seconds since the last system-wide user input (`null` if unsupported).
+#[tauri::command]
+pub(crate) fn get_idle_seconds() -> Option<u64> {
+    idle_seconds()
+}
@@ -0,0 +1,444 @@
+use std::sync::Once;
+
+// ===========================================================================
+// Layout-aware Key Labels
+// ===========================================================================
+//
+// PTT and keybind settings store Windows virtual-key codes, which are
+// layout-independent: VK_OEM_3 is "`" on US, "Ö" on Swedish and "²" on
+// French AZERTY. `get_key_display_name(vk, scanCode)` resolves a key to the
+// label printed on the user's keycap for the *active* keyboard layout, so
+// the settings UI never has to show raw VK numbers.
+//
+// Keys whose label doesn't depend on the layout — numpad, media and gamepad
+// keys — skip the layout lookup, which would otherwise label Numpad 5 as
+// plain "5". For the rest, resolution order (Windows):
+//   1. `ToUnicodeEx` with an empty modifier state — yields the character the
+//      key types ("Ö", "²", "Q"). Used for all printable keys.
+//   2. `GetKeyNameTextW` — the layout's own name for non-character keys
+//      ("Caps Lock", "Right Ctrl").
+//   3. The static fallback table below.
+//
+// The active layout belongs to the foreground thread, not to Ripcord, so a
+// watcher thread polls it and emits `keyboard-layout-changed` whenever the
+// user switches layouts; the frontend re-queries its labels in response.
+//
+// macOS asks the current keyboard layout via `UCKeyTranslate`; Linux asks
+// X11's XKB for the keysym in the active group (libX11 is loaded at runtime,
+// so a Wayland session without XWayland just falls back). Both only produce
+// labels for printable keys — everything else comes from the fallback
+// table — and neither runs the layout watcher yet. There, `scan_code` is the
+// platform key code (`kVK_*` / evdev) as reported by `capture_next_key`.
+// ===========================================================================
+
+/// Guards the one-time spawn of the layout watcher.
+static LAYOUT_WATCHER: Once = Once::new();
+
+#[cfg(target_os = "windows")]
+mod win32 {
+    pub const MAPVK_VK_TO_VSC_EX: u32 = 4;
+    /// `ToUnicodeEx` flag: do not change keyboard state (Windows 10 1607+).
+    pub const TU_NO_STATE_CHANGE: u32 = 0x4;
+
+    extern "system" {
+        pub fn GetForegroundWindow() -> isize;
+        pub fn GetWindowThreadProcessId(hwnd: isize, process_id: *mut u32) -> u32;
+        pub fn GetKeyboardLayout(thread_id: u32) -> isize;
+        pub fn MapVirtualKeyExW(code: u32, map_type: u32, hkl: isize) -> u32;
+        pub fn ToUnicodeEx(
+            vk: u32,
+            scan_code: u32,
+            key_state: *const u8,
+            buf: *mut u16,
+            buf_len: i32,
+            flags: u32,
+            hkl: isize,
+        ) -> i32;
+        pub fn GetKeyNameTextW(l_param: i32, buf: *mut u16, buf_len: i32) -> i32;
+    }
+
+    /// Keyboard layout of the foreground window's thread.
+    pub fn active_layout() -> isize {
+        unsafe {
+            let hwnd = GetForegroundWindow();
+            let tid = GetWindowThreadProcessId(hwnd, std::ptr::null_mut());
+            GetKeyboardLayout(tid)
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use std::ffi::c_void;
+
+    pub const KEY_ACTION_DISPLAY: u16 = 3;
+    pub const TRANSLATE_NO_DEAD_KEYS: u32 = 1;
+
+    #[link(name = "Carbon", kind = "framework")]
+    extern "C" {
+        pub static kTISPropertyUnicodeKeyLayoutData: *const c_void;
+        pub fn TISCopyCurrentKeyboardLayoutInputSource() -> *mut c_void;
+        pub fn TISGetInputSourceProperty(source: *mut c_void, key: *const c_void) -> *const c_void;
+        pub fn LMGetKbdType() -> u8;
+        pub fn UCKeyTranslate(
+            layout: *const u8,
+            virtual_key_code: u16,
+            key_action: u16,
+            modifier_key_state: u32,
+            keyboard_type: u32,
+            key_translate_options: u32,
+            dead_key_state: *mut u32,
+            max_string_length: usize,
+            actual_string_length: *mut usize,
+            unicode_string: *mut u16,
+        ) -> i32;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        pub fn CFDataGetBytePtr(data: *const c_void) -> *const u8;
+        pub fn CFRelease(cf: *const c_void);
+    }
+
+    extern "C" {
+        pub fn pthread_main_np() -> i32;
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use std::ffi::{c_char, c_void, CStr};
+    use std::sync::Mutex;
+
+    const RTLD_NOW: i32 = 2;
+    const XKB_USE_CORE_KBD: u32 = 0x0100;
+
+    extern "C" {
+        fn dlopen(filename: *const c_char, flag: i32) -> *mut c_void;
+        fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+    }
+
+    type XOpenDisplay = unsafe extern "C" fn(*const c_char) -> *mut c_void;
+    type XkbGetState = unsafe extern "C" fn(*mut c_void, u32, *mut u8) -> i32;
+    type XkbKeycodeToKeysym = unsafe extern "C" fn(*mut c_void, u8, i32, i32) -> u64;
+
+    /// Our own display connection. Xlib calls are serialised by the mutex.
+    pub struct Xlib {
+        display: *mut c_void,
+        get_state: XkbGetState,
+        keycode_to_keysym: XkbKeycodeToKeysym,
+    }
+
+    // SAFETY: the connection is private to this module and only used while
+    // holding `XLIB`'s lock.
+    unsafe impl Send for Xlib {}
+
+    pub static XLIB: Mutex<Option<Option<Xlib>>> = Mutex::new(None);
+
+    unsafe fn symbol<T>(lib: *mut c_void, name: &CStr) -> Option<T> {
+        let ptr = unsafe { dlsym(lib, name.as_ptr()) };
+        (!ptr.is_null()).then(|| unsafe { std::mem::transmute_copy(&ptr) })
+    }
+
+    pub fn open() -> Option<Xlib> {
+        unsafe {
+            let lib = dlopen(c"libX11.so.6".as_ptr(), RTLD_NOW);
+            if lib.is_null() {
+                return None;
+            }
+            let open_display: XOpenDisplay = symbol(lib, c"XOpenDisplay")?;
+            let display = open_display(std::ptr::null());
+            if display.is_null() {
+                return None;
+            }
+            Some(Xlib {
+                display,
+                get_state: symbol(lib, c"XkbGetState")?,
+                keycode_to_keysym: symbol(lib, c"XkbKeycodeToKeysym")?,
+            })
+        }
+    }
+
+    impl Xlib {
+        /// Unshifted keysym for an evdev key code in the active layout group.
+        pub fn keysym(&self, evdev_code: u32) -> Option<u64> {
+            // X keycodes are evdev codes offset by 8.
+            let keycode = u8::try_from(evdev_code + 8).ok()?;
+            // `XkbStateRec` starts with the effective group; the buffer is
+            // larger than the whole struct.
+            let mut state = [0u8; 32];
+            let group = unsafe {
+                if (self.get_state)(self.display, XKB_USE_CORE_KBD, state.as_mut_ptr()) == 0 {
+                    i32::from(state[0])
+                } else {
+                    0
+                }
+            };
+            let keysym = unsafe { (self.keycode_to_keysym)(self.display, keycode, group, 0) };
+            (keysym != 0).then_some(keysym)
+        }
+    }
+}
+
+/// Resolve a virtual-key code to a display label for the active layout.
+/// `scan_code` may be 0, in which case it is derived from the layout.
+#[tauri::command]
+pub(crate) fn get_key_display_name(vk: u32, scan_code: u32) -> String {
+    LAYOUT_WATCHER.call_once(|| {
+        #[cfg(target_os = "windows")]
+        std::thread::spawn(watch_layout);
+    });
+
+    // Numpad, media and gamepad keys have no layout-dependent label, and
+    // the layout would name the numpad keys after the character they type.
+    let fixed = matches!(vk, 0x60..=0x6F | 0xAD..=0xB3 | 0xC3..=0xDA | 0xE8);
+    #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+    if !fixed {
+        if let Some(label) = native_key_name(vk, scan_code) {
+            return label;
+        }
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    let _ = (scan_code, fixed);
+
+    fallback_key_name(vk)
+}
+
+/// `get_key_display_name` under the name the keybind settings use.
+#[tauri::command]
+pub(crate) fn key_code_to_name(vk: u32, scan: Option<u32>) -> String {
+    get_key_display_name(vk, scan.unwrap_or(0))
+}
+
+/// A label made of printable characters, uppercased like a keycap.
+#[cfg_attr(
+    not(any(target_os = "windows", target_os = "macos", target_os = "linux")),
+    allow(dead_code)
+)]
+fn printable_label(text: &str) -> Option<String> {
+    (!text.is_empty() && text.chars().all(|c| !c.is_control() && !c.is_whitespace()))
+        .then(|| text.to_uppercase())
+}
+
+/// Ask the current macOS keyboard layout what the key types.
+#[cfg(target_os = "macos")]
+fn native_key_name(vk: u32, scan_code: u32) -> Option<String> {
+    let keycode = match scan_code {
+        0 => crate::ptt::platform_keycode(vk as i32)?,
+        code => code,
+    };
+    let keycode = u16::try_from(keycode).ok()?;
+
+    // Text Input Sources must be used from the main thread; key capture
+    // resolves names from the event tap thread.
+    let translate = move || unsafe {
+        let source = macos::TISCopyCurrentKeyboardLayoutInputSource();
+        if source.is_null() {
+            return None;
+        }
+        let data =
+            macos::TISGetInputSourceProperty(source, macos::kTISPropertyUnicodeKeyLayoutData);
+        let mut buf = [0u16; 8];
+        let mut len = 0usize;
+        let mut dead_key_state = 0u32;
+        let status = if data.is_null() {
+            -1
+        } else {
+            macos::UCKeyTranslate(
+                macos::CFDataGetBytePtr(data),
+                keycode,
+                macos::KEY_ACTION_DISPLAY,
+                0,
+                u32::from(macos::LMGetKbdType()),
+                macos::TRANSLATE_NO_DEAD_KEYS,
+                &mut dead_key_state,
+                buf.len(),
+                &mut len,
+                buf.as_mut_ptr(),
+            )
+        };
+        macos::CFRelease(source);
+        (status == 0).then(|| String::from_utf16_lossy(&buf[..len.min(buf.len())]))
+    };
+
+    let text = if unsafe { macos::pthread_main_np() } != 0 {
+        translate()
+    } else {
+        let (tx, rx) = std::sync::mpsc::channel();
+        crate::APP_HANDLE
+            .get()?
+            .run_on_main_thread(move || {
+                let _ = tx.send(translate());
+            })
+            .ok()?;
+        rx.recv_timeout(std::time::Duration::from_millis(500))
+            .ok()?
+    }?;
+    printable_label(&text)
+}
+
+/// Ask XKB which character the key types in the active layout group.
+#[cfg(target_os = "linux")]
+fn native_key_name(vk: u32, scan_code: u32) -> Option<String> {
+    let code = match scan_code {
+        0 => crate::ptt::platform_keycode(vk as i32)?,
+        code => code,
+    };
+    let mut guard = x11::XLIB.lock().unwrap();
+    let keysym = guard.get_or_insert_with(x11::open).as_ref()?.keysym(code)?;
+
+    // Latin-1 keysyms are their code point; Unicode keysyms are offset by
+    // 0x0100_0000. Anything else (Shift_L, KP_5, …) is left to the fallback
+    // table, whose names read better than X's.
+    let c = match keysym {
+        0x20..=0x7E | 0xA0..=0xFF => char::from_u32(keysym as u32),
+        0x0100_0000..=0x0110_FFFF => char::from_u32((keysym - 0x0100_0000) as u32),
+        _ => None,
+    }?;
+    printable_label(&c.to_string())
+}
+
+/// Ask Windows for the key's label under the active layout.
+#[cfg(target_os = "windows")]
+fn native_key_name(vk: u32, scan_code: u32) -> Option<String> {
+    let hkl = win32::active_layout();
+
+    // The `_EX` mapping puts the 0xE0 extended prefix in the high byte.
+    let mapped = unsafe { win32::MapVirtualKeyExW(vk, win32::MAPVK_VK_TO_VSC_EX, hkl) };
+    let scan = if scan_code != 0 {
+        scan_code & 0xFF
+    } else {
+        mapped & 0xFF
+    };
+    // Captured scan codes carry the prefix themselves (0xE01D).
+    let extended = (scan_code & 0xFF00) == 0xE000 || (mapped & 0xFF00) == 0xE000;
+
+    // 1. Printable character
+    let key_state = [0u8; 256];
+    let mut buf = [0u16; 8];
+    let n = unsafe {
+        win32::ToUnicodeEx(
+            vk,
+            scan,
+            key_state.as_ptr(),
+            buf.as_mut_ptr(),
+            buf.len() as i32,
+            win32::TU_NO_STATE_CHANGE,
+            hkl,
+        )
+    };
+    // Dead keys return -1 but still write the accent character.
+    let len = if n < 0 { 1 } else { n as usize };
+    if let Some(label) = printable_label(&String::from_utf16_lossy(&buf[..len])) {
+        return Some(label);
+    }
+
+    // 2. Layout-provided key name
+    let mut l_param = (scan as i32) << 16;
+    if extended {
+        l_param |= 1 << 24;
+    }
+    let mut name = [0u16; 64];
+    let n = unsafe { win32::GetKeyNameTextW(l_param, name.as_mut_ptr(), name.len() as i32) };
+    if n > 0 {
+        return Some(String::from_utf16_lossy(&name[..n as usize]));
+    }
+
+    None
+}
+
+/// Layout-independent label for keys every platform agrees on.
+fn fallback_key_name(vk: u32) -> String {
+    let name = match vk {
+        0x04 => "Middle Mouse",
+        0x05 => "Mouse 4",
+        0x06 => "Mouse 5",
+        0xC3 => "Gamepad A",
+        0xC4 => "Gamepad B",
+        0xC5 => "Gamepad X",
+        0xC6 => "Gamepad Y",
+        0xC7 => "Gamepad RB",
+        0xC8 => "Gamepad LB",
+        0xC9 => "Gamepad LT",
+        0xCA => "Gamepad RT",
+        0xCB => "D-Pad Up",
+        0xCC => "D-Pad Down",
+        0xCD => "D-Pad Left",
+        0xCE => "D-Pad Right",
+        0xCF => "Gamepad Menu",
+        0xD0 => "Gamepad View",
+        0xD1 => "Left Stick",
+        0xD2 => "Right Stick",
+        0xAD => "Mute",
+        0xAE => "Volume Down",
+        0xAF => "Volume Up",
+        0xB0 => "Next Track",
+        0xB1 => "Previous Track",
+        0xB2 => "Stop",
+        0xB3 => "Play/Pause",
+        0xE8 => "Mic Mute",
+        0x08 => "Backspace",
+        0x09 => "Tab",
+        0x0D => "Enter",
+        0x10 => "Shift",
+        0x11 => "Ctrl",
+        0x12 => "Alt",
+        0x13 => "Pause",
+        0x14 => "Caps Lock",
+        0x1B => "Esc",
+        0x20 => "Space",
+        0x21 => "Page Up",
+        0x22 => "Page Down",
+        0x23 => "End",
+        0x24 => "Home",
+        0x25 => "Left",
+        0x26 => "Up",
+        0x27 => "Right",
+        0x28 => "Down",
+        0x2D => "Insert",
+        0x2E => "Delete",
+        0x5B => "Left Win",
+        0x5C => "Right Win",
+        0x6A => "Num *",
+        0x6B => "Num +",
+        0x6C => "Num Separator",
+        0x6D => "Num -",
+        0x6E => "Num .",
+        0x6F => "Num /",
+        0x90 => "Num Lock",
+        0x91 => "Scroll Lock",
+        0xA0 => "Left Shift",
+        0xA1 => "Right Shift",
+        0xA2 => "Left Ctrl",
+        0xA3 => "Right Ctrl",
+        0xA4 => "Left Alt",
+        0xA5 => "Right Alt",
+        0x30..=0x39 | 0x41..=0x5A => {
+            return char::from_u32(vk).map(String::from).unwrap_or_default()
+        }
+        0x60..=0x69 => return format!("Num {}", vk - 0x60),
+        0x70..=0x87 => return format!("F{}", vk - 0x6F),
+        _ => return format!("Key {vk}"),
+    };
+    name.to_string()
+}
+
+/// Poll the foreground keyboard layout and emit `keyboard-layout-changed`
+/// when it changes. Runs for the life of the process.
+#[cfg(target_os = "windows")]
+fn watch_layout() {
+    use tauri::Emitter;
+
+    let mut last = win32::active_layout();
+    loop {
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        let current = win32::active_layout();
+        // The foreground thread may briefly report 0 during window switches.
+        if current != 0 && current != last {
+            last = current;
+            if let Some(handle) = crate::APP_HANDLE.get() {
+                let _ = handle.emit("keyboard-layout-changed", ());
+            }
+        }
+    }
+}
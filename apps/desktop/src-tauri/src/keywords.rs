@@ -0,0 +1,286 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::store;
+
+// ===========================================================================
+// Keyword Filter Engine
+// ===========================================================================
+//
+// Classifies incoming messages against user-configured terms before the
+// frontend decides whether to notify: profanity or spoiler terms suppress
+// the toast, watched keywords emphasize it. All rules are compiled into one
+// Aho-Corasick automaton, so classification is a single pass over the
+// message regardless of how many terms are configured — no per-rule JS
+// regexes on every incoming message.
+//
+// Matching is case-insensitive (both sides are lowercased). Whole-word rules
+// additionally require non-alphanumeric characters (or the text edge) on
+// both sides of the match. When rules disagree, suppression wins.
+// ===========================================================================
+
+const STORE_DOC: &str = "keyword-filters";
+
+/// What a matching rule does to the notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum FilterAction {
+    None,
+    Emphasize,
+    Suppress,
+}
+
+/// One configured term.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FilterRule {
+    pub pattern: String,
+    pub action: FilterAction,
+    #[serde(default)]
+    pub whole_word: bool,
+}
+
+/// Classification result for one message.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Classification {
+    pub action: FilterAction,
+    /// Patterns that matched, in rule order, without duplicates.
+    pub matched: Vec<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Aho-Corasick automaton
+// ---------------------------------------------------------------------------
+
+#[derive(Default)]
+struct State {
+    next: HashMap<u8, usize>,
+    fail: usize,
+    /// Indices of rules whose pattern ends at this state.
+    outputs: Vec<usize>,
+}
+
+/// Byte-level Aho-Corasick automaton over lowercased patterns.
+pub(crate) struct Matcher {
+    states: Vec<State>,
+    rules: Vec<FilterRule>,
+    /// Lowercased pattern byte lengths, indexed like `rules`.
+    lens: Vec<usize>,
+}
+
+impl Matcher {
+    pub(crate) fn new(rules: Vec<FilterRule>) -> Self {
+        let mut states = vec![State::default()];
+        let mut lens = Vec::with_capacity(rules.len());
+
+        // Trie
+        for (index, rule) in rules.iter().enumerate() {
+            let pattern = rule.pattern.to_lowercase();
+            lens.push(pattern.len());
+            if pattern.is_empty() {
+                continue;
+            }
+            let mut cur = 0;
+            for &b in pattern.as_bytes() {
+                cur = match states[cur].next.get(&b) {
+                    Some(&s) => s,
+                    None => {
+                        states.push(State::default());
+                        let s = states.len() - 1;
+                        states[cur].next.insert(b, s);
+                        s
+                    }
+                };
+            }
+            states[cur].outputs.push(index);
+        }
+
+        // Failure links, breadth-first
+        let mut queue: VecDeque<usize> = states[0].next.values().copied().collect();
+        while let Some(s) = queue.pop_front() {
+            let edges: Vec<(u8, usize)> = states[s].next.iter().map(|(&b, &t)| (b, t)).collect();
+            for (b, t) in edges {
+                let mut f = states[s].fail;
+                let fail = loop {
+                    if let Some(&n) = states[f].next.get(&b) {
+                        break n;
+                    }
+                    if f == 0 {
+                        break 0;
+                    }
+                    f = states[f].fail;
+                };
+                states[t].fail = fail;
+                let inherited = states[fail].outputs.clone();
+                states[t].outputs.extend(inherited);
+                queue.push_back(t);
+            }
+        }
+
+        Self {
+            states,
+            rules,
+            lens,
+        }
+    }
+
+    pub(crate) fn rules(&self) -> &[FilterRule] {
+        &self.rules
+    }
+
+    /// Indices of matching rules (each at most once), honouring whole-word.
+    pub(crate) fn find(&self, text: &str) -> Vec<usize> {
+        let text = text.to_lowercase();
+        let bytes = text.as_bytes();
+        let mut hit = vec![false; self.rules.len()];
+        let mut cur = 0;
+
+        for (i, &b) in bytes.iter().enumerate() {
+            loop {
+                if let Some(&n) = self.states[cur].next.get(&b) {
+                    cur = n;
+                    break;
+                }
+                if cur == 0 {
+                    break;
+                }
+                cur = self.states[cur].fail;
+            }
+            for &rule in &self.states[cur].outputs {
+                if hit[rule] {
+                    continue;
+                }
+                let start = i + 1 - self.lens[rule];
+                if !self.rules[rule].whole_word || is_word_bounded(&text, start, i + 1) {
+                    hit[rule] = true;
+                }
+            }
+        }
+
+        (0..self.rules.len()).filter(|&i| hit[i]).collect()
+    }
+
+    pub(crate) fn classify(&self, text: &str) -> Classification {
+        let mut action = FilterAction::None;
+        let mut matched: Vec<String> = Vec::new();
+        for index in self.find(text) {
+            let rule = &self.rules[index];
+            action = action.max(rule.action);
+            if !matched.contains(&rule.pattern) {
+                matched.push(rule.pattern.clone());
+            }
+        }
+        Classification { action, matched }
+    }
+}
+
+/// Whether `text[start..end]` is flanked by non-word characters.
+fn is_word_bounded(text: &str, start: usize, end: usize) -> bool {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let before = text.get(..start).and_then(|s| s.chars().next_back());
+    let after = text.get(end..).and_then(|s| s.chars().next());
+    !before.is_some_and(is_word) && !after.is_some_and(is_word)
+}
+
+// ---------------------------------------------------------------------------
+// Global filter set
+// ---------------------------------------------------------------------------
+
+static MATCHER: RwLock<Option<Matcher>> = RwLock::new(None);
+
+/// Run `f` against the active matcher, compiling it from the store on first
+/// use.
+pub(crate) fn with_matcher<R>(f: impl FnOnce(&Matcher) -> R) -> R {
+    if let Some(matcher) = MATCHER.read().unwrap().as_ref() {
+        return f(matcher);
+    }
+    let mut guard = MATCHER.write().unwrap();
+    let matcher = guard.get_or_insert_with(|| Matcher::new(store::load(STORE_DOC)));
+    f(matcher)
+}
+
+/// Replace the configured filter rules.
+#[tauri::command]
+pub(crate) fn set_keyword_filters(rules: Vec<FilterRule>) -> Result<(), String> {
+    store::save(STORE_DOC, &rules)?;
+    *MATCHER.write().unwrap() = Some(Matcher::new(rules));
+    Ok(())
+}
+
+/// Currently configured filter rules.
+#[tauri::command]
+pub(crate) fn get_keyword_filters() -> Vec<FilterRule> {
+    with_matcher(|m| m.rules().to_vec())
+}
+
+/// Classify one message for notification handling.
+#[tauri::command]
+pub(crate) fn classify_message(text: String) -> Classification {
+    with_matcher(|m| m.classify(&text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str, action: FilterAction, whole_word: bool) -> FilterRule {
+        FilterRule {
+            pattern: pattern.into(),
+            action,
+            whole_word,
+        }
+    }
+
+    #[test]
+    fn finds_overlapping_patterns_through_failure_links() {
+        let matcher = Matcher::new(
+            ["he", "she", "his", "hers"]
+                .iter()
+                .map(|p| rule(p, FilterAction::Emphasize, false))
+                .collect(),
+        );
+        assert_eq!(matcher.find("ushers"), [0, 1, 3]);
+        assert_eq!(matcher.find("this"), [2]);
+        assert!(matcher.find("hx").is_empty());
+    }
+
+    #[test]
+    fn matching_ignores_case() {
+        let matcher = Matcher::new(vec![rule("Release", FilterAction::Emphasize, false)]);
+        assert_eq!(matcher.find("new RELEASE out"), [0]);
+        assert_eq!(matcher.find("ÉTÉ release"), [0]);
+    }
+
+    #[test]
+    fn whole_word_rules_need_boundaries() {
+        let matcher = Matcher::new(vec![
+            rule("ass", FilterAction::Suppress, true),
+            rule("café", FilterAction::Emphasize, true),
+        ]);
+        assert!(matcher.find("first class").is_empty());
+        assert!(matcher.find("snake_ass").is_empty());
+        assert_eq!(matcher.find("class, ass."), [0]);
+        assert_eq!(matcher.find("«café»"), [1]);
+        assert!(matcher.find("cafés").is_empty());
+    }
+
+    #[test]
+    fn suppression_wins_and_matches_are_listed_once() {
+        let matcher = Matcher::new(vec![
+            rule("deploy", FilterAction::Emphasize, false),
+            rule("spoiler", FilterAction::Suppress, false),
+            rule("deploy", FilterAction::Emphasize, true),
+            rule("", FilterAction::Suppress, false),
+        ]);
+        let result = matcher.classify("spoiler: deploy deploy");
+        assert_eq!(result.action, FilterAction::Suppress);
+        assert_eq!(result.matched, ["deploy", "spoiler"]);
+
+        let result = matcher.classify("nothing here");
+        assert_eq!(result.action, FilterAction::None);
+        assert!(result.matched.is_empty());
+    }
+}
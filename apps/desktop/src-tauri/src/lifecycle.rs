@@ -0,0 +1,90 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tauri::{webview::PageLoadEvent, Emitter, Runtime, Webview};
+
+use crate::{clock, fonts, ptt, APP_HANDLE};
+
+// ===========================================================================
+// Webview Lifecycle Watchdog
+// ===========================================================================
+//
+// Native threads outlive the page that started them. When the frontend
+// hot-reloads (dev) or the webview recovers from a renderer crash, the new
+// page starts with fresh JS state while the native side still holds the old
+// page's PTT hook, pressed-key latches and event subscriptions. The result is
+// "zombie" state: a hook emitting to listeners that no longer exist, or a key
+// stuck as pressed until the next full restart.
+//
+// The watchdog hooks Tauri's page-load callback for the main window. The
+// first `Started` event is the initial load; every subsequent one is a
+// reload. On reload it resets every native subsystem back to its startup
+// state before the new page's scripts run, then emits `native-state-reset`
+// once the page has `Finished` loading so it knows it must re-register
+// anything it needs (PTT key, listeners, ...). The page may not be listening
+// yet by then, so it should also call `take_native_state_reset` once at
+// startup, which reports the same reset.
+//
+// Every `Finished` load of the main window also re-declares the user's
+// imported fonts (see `fonts`), since injected styles die with the page.
+// ===========================================================================
+
+/// Label of the window whose reloads trigger a reset.
+const MAIN_WINDOW: &str = "main";
+
+/// Whether the main window has completed its initial page load.
+static INITIAL_LOAD_SEEN: AtomicBool = AtomicBool::new(false);
+
+/// Set when a reset ran during `Started` and the notification is still owed
+/// to the new page.
+static RESET_PENDING: AtomicBool = AtomicBool::new(false);
+
+/// Set by a reset until the page collects it with `take_native_state_reset`.
+static RESET_UNTAKEN: AtomicBool = AtomicBool::new(false);
+
+/// Page-load callback — registered via `Builder::on_page_load`.
+pub(crate) fn on_page_load<R: Runtime>(webview: &Webview<R>, event: PageLoadEvent) {
+    if webview.label() != MAIN_WINDOW {
+        return;
+    }
+
+    match event {
+        PageLoadEvent::Started => {
+            // First load of the session — nothing to reset yet.
+            if INITIAL_LOAD_SEEN.swap(true, Ordering::Relaxed) {
+                reset_native_state();
+                RESET_PENDING.store(true, Ordering::Relaxed);
+                RESET_UNTAKEN.store(true, Ordering::Relaxed);
+            }
+        }
+        PageLoadEvent::Finished => {
+            fonts::register_with(webview);
+            if RESET_PENDING.swap(false, Ordering::Relaxed) {
+                if let Some(handle) = APP_HANDLE.get() {
+                    let _ = handle.emit("native-state-reset", ());
+                }
+            }
+        }
+    }
+}
+
+/// Reset every native subsystem back to its startup state.
+///
+/// Each subsystem exposes a reset function that stops its threads and returns
+/// its statics to their startup values. New subsystems with long-lived native
+/// state must be added here.
+pub(crate) fn reset_native_state() {
+    ptt::reset();
+    clock::reset_clock_sync();
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Whether native state was reset for this page load, like
+/// `native-state-reset`. Returns true once per reset, so a page that calls
+/// it at startup doesn't miss an event emitted before it was listening.
+#[tauri::command]
+pub(crate) fn take_native_state_reset() -> bool {
+    RESET_UNTAKEN.swap(false, Ordering::Relaxed)
+}
@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::timezone::{self, LocalTime};
+use crate::{scheduler, store};
+
+// ===========================================================================
+// Text Macros
+// ===========================================================================
+//
+// User-defined "slash" snippets expanded on send. `set_text_macros(map)`
+// stores `name -> template`; `expand_macros(text, context)` replaces every
+// `/name` that starts a word with its template.
+//
+// Templates may reference other macros and these placeholders:
+//
+//   {date}            local date, YYYY-MM-DD
+//   {time}            local time, HH:MM
+//   {clipboard}       clipboard text (supplied by the webview)
+//   {lastAttachment}  URL of the user's most recent upload
+//
+// Placeholders are only substituted inside templates, never in what the
+// user typed, and substituted values are not scanned for further macros.
+// Cycles (`/a` -> `/b` -> `/a`) and excessive nesting are left unexpanded
+// and reported, and output is capped so a fan-out chain can't explode.
+// ===========================================================================
+
+const STORE_DOC: &str = "text-macros";
+const MAX_DEPTH: usize = 8;
+const MAX_OUTPUT: usize = 16 * 1024;
+const MAX_NAME_LEN: usize = 32;
+
+/// Values the webview knows and native code doesn't.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct MacroContext {
+    pub clipboard: Option<String>,
+    pub last_attachment: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Expansion {
+    pub text: String,
+    /// Names of macros that were expanded, in order.
+    pub expanded: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+static MACROS: Mutex<Option<HashMap<String, String>>> = Mutex::new(None);
+
+fn with_macros<R>(f: impl FnOnce(&mut HashMap<String, String>) -> R) -> R {
+    let mut guard = MACROS.lock().unwrap();
+    f(guard.get_or_insert_with(|| store::load(STORE_DOC)))
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_'
+}
+
+struct Expander<'a> {
+    macros: &'a HashMap<String, String>,
+    context: &'a MacroContext,
+    /// Now, in the OS zone.
+    local: LocalTime,
+    out: String,
+    stack: Vec<&'a str>,
+    expanded: Vec<String>,
+    errors: Vec<String>,
+    truncated: bool,
+}
+
+impl<'a> Expander<'a> {
+    fn placeholder(&self, name: &str) -> Option<String> {
+        match name {
+            "date" => {
+                let local = &self.local;
+                Some(format!(
+                    "{:04}-{:02}-{:02}",
+                    local.year, local.month, local.day
+                ))
+            }
+            "time" => Some(format!("{:02}:{:02}", self.local.hour, self.local.minute)),
+            "clipboard" => Some(self.context.clipboard.clone().unwrap_or_default()),
+            "lastAttachment" => Some(self.context.last_attachment.clone().unwrap_or_default()),
+            _ => None,
+        }
+    }
+
+    /// Expand `text` into `self.out`. Placeholders are honoured only for
+    /// template text.
+    fn expand(&mut self, text: &'a str, in_template: bool) {
+        let mut rest = text;
+        let mut prev: Option<char> = None;
+        while let Some(c) = rest.chars().next() {
+            if self.out.len() > MAX_OUTPUT {
+                if !self.truncated {
+                    self.truncated = true;
+                    self.errors
+                        .push(format!("expansion exceeds {MAX_OUTPUT} bytes; truncated"));
+                }
+                return;
+            }
+
+            if in_template && c == '{' {
+                if let Some(end) = rest[1..].find('}').filter(|&e| e <= MAX_NAME_LEN) {
+                    if let Some(value) = self.placeholder(&rest[1..=end]) {
+                        self.out.push_str(&value);
+                        rest = &rest[end + 2..];
+                        prev = Some('}');
+                        continue;
+                    }
+                }
+            }
+
+            if c == '/' && prev.is_none_or(char::is_whitespace) {
+                let name_len = rest[1..]
+                    .find(|c| !is_name_char(c))
+                    .unwrap_or(rest.len() - 1);
+                let name = &rest[1..=name_len];
+                if let Some((key, template)) = self.macros.get_key_value(name) {
+                    if self.stack.contains(&name) {
+                        self.errors
+                            .push(format!("/{name} refers to itself; left unexpanded"));
+                    } else if self.stack.len() >= MAX_DEPTH {
+                        self.errors.push(format!(
+                            "/{name} is nested more than {MAX_DEPTH} deep; left unexpanded"
+                        ));
+                    } else {
+                        self.stack.push(key);
+                        self.expanded.push(key.clone());
+                        self.expand(template, true);
+                        self.stack.pop();
+                        rest = &rest[1 + name_len..];
+                        prev = name.chars().last();
+                        continue;
+                    }
+                }
+            }
+
+            self.out.push(c);
+            prev = Some(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+}
+
+/// Expand `text` against `macros` at local time `local`.
+fn expand_text(
+    macros: &HashMap<String, String>,
+    context: &MacroContext,
+    local: LocalTime,
+    text: &str,
+) -> Expansion {
+    if macros.is_empty() || !text.contains('/') {
+        return Expansion {
+            text: text.to_string(),
+            expanded: Vec::new(),
+            errors: Vec::new(),
+        };
+    }
+    let mut expander = Expander {
+        macros,
+        context,
+        local,
+        out: String::with_capacity(text.len()),
+        stack: Vec::new(),
+        expanded: Vec::new(),
+        errors: Vec::new(),
+        truncated: false,
+    };
+    expander.expand(text, false);
+    Expansion {
+        text: expander.out,
+        expanded: expander.expanded,
+        errors: expander.errors,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Replace all macros. Names are case-sensitive and limited to
+/// `[A-Za-z0-9_-]`, up to 32 characters.
+#[tauri::command]
+pub(crate) fn set_text_macros(macros: HashMap<String, String>) -> Result<(), String> {
+    for name in macros.keys() {
+        if name.is_empty() || name.len() > MAX_NAME_LEN || !name.chars().all(is_name_char) {
+            return Err(format!("invalid macro name \"{name}\""));
+        }
+    }
+    with_macros(|current| {
+        *current = macros;
+        store::save(STORE_DOC, current)
+    })
+}
+
+/// The stored macros.
+#[tauri::command]
+pub(crate) fn get_text_macros() -> HashMap<String, String> {
+    with_macros(|macros| macros.clone())
+}
+
+/// Expand macros in an outgoing message.
+#[tauri::command]
+pub(crate) fn expand_macros(text: String, context: Option<MacroContext>) -> Expansion {
+    let context = context.unwrap_or_default();
+    let local = timezone::local_time_at((scheduler::now_ms() / 1000) as i64);
+    with_macros(|macros| expand_text(macros, &context, local, &text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(macros: &[(&str, &str)], text: &str) -> Expansion {
+        let macros = macros
+            .iter()
+            .map(|(name, template)| (name.to_string(), template.to_string()))
+            .collect();
+        let context = MacroContext {
+            clipboard: Some("/sig from clipboard".into()),
+            last_attachment: Some("https://cdn.example/a.png".into()),
+        };
+        let local = LocalTime {
+            year: 2026,
+            month: 3,
+            day: 9,
+            hour: 7,
+            minute: 5,
+            second: 0,
+            weekday: 1,
+        };
+        expand_text(&macros, &context, local, text)
+    }
+
+    #[test]
+    fn expands_words_that_start_with_a_slash() {
+        let macros = [("sig", "-- Sam"), ("shrug", r"¯\_(ツ)_/¯")];
+        let result = run(
+            &macros,
+            "/shrug ok
+/sig",
+        );
+        assert_eq!(
+            result.text,
+            "¯\\_(ツ)_/¯ ok
+-- Sam"
+        );
+        assert_eq!(result.expanded, ["shrug", "sig"]);
+
+        // Not at a word start, not the whole name, or unknown.
+        let result = run(&macros, "a/sig /signature /nope https://x/sig");
+        assert_eq!(result.text, "a/sig /signature /nope https://x/sig");
+        assert!(result.expanded.is_empty());
+    }
+
+    #[test]
+    fn placeholders_only_apply_inside_templates() {
+        let macros = [
+            ("stamp", "{date} {time}"),
+            ("paste", "{clipboard} {lastAttachment} {unknown}"),
+        ];
+        assert_eq!(
+            run(&macros, "/stamp {date}").text,
+            "2026-03-09 07:05 {date}"
+        );
+        // Substituted values aren't scanned for macros.
+        assert_eq!(
+            run(&macros, "/paste").text,
+            "/sig from clipboard https://cdn.example/a.png {unknown}"
+        );
+    }
+
+    #[test]
+    fn nesting_cycles_and_depth() {
+        let result = run(&[("a", "A /b"), ("b", "B /c"), ("c", "C")], "/a");
+        assert_eq!(result.text, "A B C");
+        assert_eq!(result.expanded, ["a", "b", "c"]);
+
+        let result = run(&[("a", "x /b"), ("b", "y /a")], "/a");
+        assert_eq!(result.text, "x y /a");
+        assert_eq!(result.errors, ["/a refers to itself; left unexpanded"]);
+
+        let chain: Vec<(String, String)> = (0..10)
+            .map(|i| (format!("m{i}"), format!("{i} /m{}", i + 1)))
+            .collect();
+        let chain: Vec<(&str, &str)> = chain
+            .iter()
+            .map(|(n, t)| (n.as_str(), t.as_str()))
+            .collect();
+        let result = run(&chain, "/m0");
+        assert_eq!(result.text, "0 1 2 3 4 5 6 7 /m8");
+        assert_eq!(result.errors.len(), 1);
+    }
+
+    #[test]
+    fn fan_out_is_capped() {
+        let ten_b = "/b ".repeat(10);
+        let ten_c = "/c ".repeat(10);
+        let long = "x".repeat(200);
+        let macros = [("a", &*ten_b), ("b", &*ten_c), ("c", &*long)];
+        let result = run(&macros, "/a");
+        assert!(result.text.len() <= MAX_OUTPUT + 64);
+        assert_eq!(
+            result.errors,
+            [format!("expansion exceeds {MAX_OUTPUT} bytes; truncated")]
+        );
+    }
+}
@@ -0,0 +1,741 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+// ===========================================================================
+// Markdown Render Cache
+// ===========================================================================
+//
+// Parses chat-flavored Markdown into a small AST the webview renders
+// directly, so scrolling a large channel doesn't re-run a JS Markdown parser
+// over thousands of messages. Parsed trees are kept in an LRU cache keyed by
+// a hash of (text, options); re-rendering a message that scrolled back into
+// view is a hash lookup.
+//
+// Supported syntax (mirrors what `message-content.tsx` renders, plus the
+// chat extensions):
+//   Blocks:  paragraphs, `#`/`##`/`###` headings, ``` fenced code (with
+//            language), `>` and `>>>` block quotes, `-`/`*`/`1.` lists
+//   Inline:  **strong**, *em*/_em_, __underline__, ~~strike~~, `code`,
+//            ||spoiler||, [text](url), bare/`<angle>` URLs,
+//            <@user> <@!user> <@&role> <#channel> @everyone @here,
+//            <t:unix> / <t:unix:style> timestamps, `\` escapes
+//
+// Like the chat clients it imitates, this is a pragmatic delimiter parser
+// rather than a CommonMark implementation: an unmatched delimiter is text.
+// Quotes don't nest (a `>` inside a quote is text), and inline spans nest
+// at most `MAX_INLINE_DEPTH` deep, so a hostile message can't recurse the
+// parser into a stack overflow.
+// ===========================================================================
+
+/// Maximum cached trees. Each entry is a few hundred bytes for typical
+/// messages, so the cache stays in the low megabytes.
+const CACHE_CAPACITY: usize = 4096;
+
+/// Inputs longer than this are parsed but not cached (pasted logs etc.).
+const MAX_CACHED_LEN: usize = 16 * 1024;
+
+/// Quote levels; chat clients render one.
+const MAX_QUOTE_DEPTH: usize = 1;
+
+/// Nested inline spans (`**_~~…~~_**`, link labels) before delimiters are
+/// left as text.
+const MAX_INLINE_DEPTH: usize = 16;
+
+/// Parser options.
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct MarkdownOptions {
+    /// Treat the input as a single paragraph (notification previews,
+    /// replies) — block syntax is rendered as text.
+    pub inline_only: bool,
+    /// Do not turn bare URLs into links.
+    pub no_autolink: bool,
+}
+
+/// AST node. Serialized with a `type` tag for the renderer.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum Node {
+    Paragraph { children: Vec<Node> },
+    Heading { level: u8, children: Vec<Node> },
+    CodeBlock { lang: Option<String>, code: String },
+    BlockQuote { children: Vec<Node> },
+    List { ordered: bool, start: u32, items: Vec<Vec<Node>> },
+    Text { text: String },
+    Strong { children: Vec<Node> },
+    Emphasis { children: Vec<Node> },
+    Underline { children: Vec<Node> },
+    Strikethrough { children: Vec<Node> },
+    Spoiler { children: Vec<Node> },
+    InlineCode { code: String },
+    Link { url: String, children: Vec<Node> },
+    UserMention { id: String },
+    RoleMention { id: String },
+    ChannelMention { id: String },
+    EveryoneMention,
+    HereMention,
+    Timestamp { unix: i64, style: Option<String> },
+    LineBreak,
+}
+
+// ---------------------------------------------------------------------------
+// LRU cache
+// ---------------------------------------------------------------------------
+
+struct CacheEntry {
+    text: String,
+    options: MarkdownOptions,
+    tree: Arc<Vec<Node>>,
+    tick: u64,
+}
+
+#[derive(Default)]
+struct Cache {
+    entries: HashMap<u64, CacheEntry>,
+    /// Last-use tick → key, oldest first.
+    recency: BTreeMap<u64, u64>,
+    tick: u64,
+}
+
+impl Cache {
+    fn get(&mut self, key: u64, text: &str, options: &MarkdownOptions) -> Option<Arc<Vec<Node>>> {
+        self.tick += 1;
+        let tick = self.tick;
+        let entry = self.entries.get_mut(&key)?;
+        // Guard against hash collisions.
+        if entry.text != text || entry.options != *options {
+            return None;
+        }
+        self.recency.remove(&entry.tick);
+        self.recency.insert(tick, key);
+        entry.tick = tick;
+        Some(entry.tree.clone())
+    }
+
+    fn insert(&mut self, key: u64, text: &str, options: &MarkdownOptions, tree: Arc<Vec<Node>>) {
+        self.tick += 1;
+        if let Some(old) = self.entries.remove(&key) {
+            self.recency.remove(&old.tick);
+        }
+        while self.entries.len() >= CACHE_CAPACITY {
+            let Some((_, oldest)) = self.recency.pop_first() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+        self.recency.insert(self.tick, key);
+        self.entries.insert(
+            key,
+            CacheEntry {
+                text: text.to_string(),
+                options: options.clone(),
+                tree,
+                tick: self.tick,
+            },
+        );
+    }
+}
+
+static CACHE: Mutex<Option<Cache>> = Mutex::new(None);
+
+fn cache_key(text: &str, options: &MarkdownOptions) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    options.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Parse `text`, consulting the cache first.
+pub(crate) fn render(text: &str, options: &MarkdownOptions) -> Arc<Vec<Node>> {
+    if text.len() > MAX_CACHED_LEN {
+        return Arc::new(parse(text, options));
+    }
+
+    let key = cache_key(text, options);
+    if let Some(tree) = CACHE
+        .lock()
+        .unwrap()
+        .get_or_insert_with(Cache::default)
+        .get(key, text, options)
+    {
+        return tree;
+    }
+
+    // Parse outside the lock so concurrent renders don't serialize.
+    let tree = Arc::new(parse(text, options));
+    CACHE
+        .lock()
+        .unwrap()
+        .get_or_insert_with(Cache::default)
+        .insert(key, text, options, tree.clone());
+    tree
+}
+
+// ---------------------------------------------------------------------------
+// Block parser
+// ---------------------------------------------------------------------------
+
+/// Parse a whole message.
+pub(crate) fn parse(text: &str, options: &MarkdownOptions) -> Vec<Node> {
+    if options.inline_only {
+        return vec![Node::Paragraph {
+            children: parse_inline(text, options, 0),
+        }];
+    }
+    let lines: Vec<&str> = text.split('\n').collect();
+    parse_blocks(&lines, options, 0)
+}
+
+/// `depth` is the quote level `lines` sit at.
+fn parse_blocks(lines: &[&str], options: &MarkdownOptions, depth: usize) -> Vec<Node> {
+    let mut blocks = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut i = 0;
+
+    let flush = |paragraph: &mut Vec<&str>, blocks: &mut Vec<Node>| {
+        if !paragraph.is_empty() {
+            blocks.push(Node::Paragraph {
+                children: parse_inline(&paragraph.join("\n"), options, 0),
+            });
+            paragraph.clear();
+        }
+    };
+
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim_start();
+
+        // Fenced code block
+        if let Some(rest) = trimmed.strip_prefix("```") {
+            // Single-line form: ```code```
+            if let Some(code) = rest.strip_suffix("```").filter(|code| !code.is_empty()) {
+                flush(&mut paragraph, &mut blocks);
+                blocks.push(Node::CodeBlock {
+                    lang: None,
+                    code: code.to_string(),
+                });
+                i += 1;
+                continue;
+            }
+            if let Some(len) = lines[i + 1..].iter().position(|l| l.trim_end().ends_with("```")) {
+                flush(&mut paragraph, &mut blocks);
+                let lang = rest.trim();
+                let mut body: Vec<&str> = lines[i + 1..i + 1 + len].to_vec();
+                let last = lines[i + 1 + len].trim_end();
+                let tail = &last[..last.len() - 3];
+                if !tail.is_empty() {
+                    body.push(tail);
+                }
+                blocks.push(Node::CodeBlock {
+                    lang: (!lang.is_empty()).then(|| lang.to_string()),
+                    code: body.join("\n"),
+                });
+                i += len + 2;
+                continue;
+            }
+        }
+
+        let quotable = depth < MAX_QUOTE_DEPTH;
+
+        // `>>>` quotes the rest of the message
+        if let Some(rest) = trimmed.strip_prefix(">>> ").filter(|_| quotable) {
+            flush(&mut paragraph, &mut blocks);
+            let mut quoted = vec![rest];
+            quoted.extend_from_slice(&lines[i + 1..]);
+            blocks.push(Node::BlockQuote {
+                children: parse_blocks(&quoted, options, depth + 1),
+            });
+            break;
+        }
+
+        // `>` quotes consecutive lines
+        if quotable && quote_line(trimmed).is_some() {
+            flush(&mut paragraph, &mut blocks);
+            let mut quoted = Vec::new();
+            while let Some(rest) = lines.get(i).and_then(|l| quote_line(l.trim_start())) {
+                quoted.push(rest);
+                i += 1;
+            }
+            blocks.push(Node::BlockQuote {
+                children: parse_blocks(&quoted, options, depth + 1),
+            });
+            continue;
+        }
+
+        // Headings
+        if let Some((level, rest)) = heading_line(trimmed) {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Node::Heading {
+                level,
+                children: parse_inline(rest, options, 0),
+            });
+            i += 1;
+            continue;
+        }
+
+        // Lists
+        if let Some((ordered, start, _)) = list_line(trimmed) {
+            flush(&mut paragraph, &mut blocks);
+            let mut items = Vec::new();
+            while let Some((o, _, rest)) = lines.get(i).and_then(|l| list_line(l.trim_start())) {
+                if o != ordered {
+                    break;
+                }
+                items.push(parse_inline(rest, options, 0));
+                i += 1;
+            }
+            blocks.push(Node::List {
+                ordered,
+                start,
+                items,
+            });
+            continue;
+        }
+
+        if line.trim().is_empty() {
+            flush(&mut paragraph, &mut blocks);
+        } else {
+            paragraph.push(line);
+        }
+        i += 1;
+    }
+
+    flush(&mut paragraph, &mut blocks);
+    blocks
+}
+
+fn quote_line(line: &str) -> Option<&str> {
+    if line.starts_with(">>>") {
+        return None;
+    }
+    line.strip_prefix("> ").or_else(|| (line == ">").then_some(""))
+}
+
+fn heading_line(line: &str) -> Option<(u8, &str)> {
+    let level = line.bytes().take_while(|&b| b == b'#').count();
+    let rest = line[level..].strip_prefix(' ')?;
+    ((1..=3).contains(&level) && !rest.trim().is_empty()).then_some((level as u8, rest))
+}
+
+fn list_line(line: &str) -> Option<(bool, u32, &str)> {
+    if let Some(rest) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+        return Some((false, 1, rest));
+    }
+    let digits = line.bytes().take_while(u8::is_ascii_digit).count();
+    if digits == 0 || digits > 9 {
+        return None;
+    }
+    let rest = line[digits..].strip_prefix(". ")?;
+    Some((true, line[..digits].parse().ok()?, rest))
+}
+
+// ---------------------------------------------------------------------------
+// Inline parser
+// ---------------------------------------------------------------------------
+
+/// Paired delimiters, longest first so `**` wins over `*`.
+const DELIMITERS: &[&str] = &["||", "**", "__", "~~", "*", "_"];
+
+/// Wrap parsed children in the node for `delim`.
+fn delimited(delim: &str, children: Vec<Node>) -> Node {
+    match delim {
+        "||" => Node::Spoiler { children },
+        "**" => Node::Strong { children },
+        "__" => Node::Underline { children },
+        "~~" => Node::Strikethrough { children },
+        _ => Node::Emphasis { children },
+    }
+}
+
+/// `depth` is the number of spans `text` is nested in.
+fn parse_inline(text: &str, options: &MarkdownOptions, depth: usize) -> Vec<Node> {
+    let nestable = depth < MAX_INLINE_DEPTH;
+    let mut nodes = Vec::new();
+    let mut plain = String::new();
+    let mut i = 0;
+
+    'outer: while i < text.len() {
+        let rest = &text[i..];
+        let c = rest.chars().next().expect("i is a char boundary");
+
+        // Escapes
+        if c == '\\' {
+            if let Some(next) = rest[1..].chars().next().filter(|n| n.is_ascii_punctuation()) {
+                plain.push(next);
+                i += 1 + next.len_utf8();
+                continue;
+            }
+        }
+
+        if c == '\n' {
+            push_text(&mut nodes, &mut plain);
+            nodes.push(Node::LineBreak);
+            i += 1;
+            continue;
+        }
+
+        // Inline code — matched by an equal-length backtick run
+        if c == '`' {
+            let ticks = rest.bytes().take_while(|&b| b == b'`').count();
+            let fence = &rest[..ticks];
+            if let Some(end) = rest[ticks..].find(fence) {
+                let code = rest[ticks..ticks + end].trim();
+                if !code.is_empty() {
+                    push_text(&mut nodes, &mut plain);
+                    nodes.push(Node::InlineCode {
+                        code: code.to_string(),
+                    });
+                    i += ticks * 2 + end;
+                    continue;
+                }
+            }
+        }
+
+        // Angle-bracket forms: mentions, timestamps, suppressed-embed URLs
+        if c == '<' {
+            if let Some(end) = rest.find('>') {
+                if let Some(node) = angle_node(&rest[1..end]) {
+                    push_text(&mut nodes, &mut plain);
+                    nodes.push(node);
+                    i += end + 1;
+                    continue;
+                }
+            }
+        }
+
+        if c == '@' && at_word_start(text, i) {
+            for (word, node) in [("@everyone", Node::EveryoneMention), ("@here", Node::HereMention)] {
+                if rest.starts_with(word) {
+                    push_text(&mut nodes, &mut plain);
+                    nodes.push(node);
+                    i += word.len();
+                    continue 'outer;
+                }
+            }
+        }
+
+        // [text](url)
+        if c == '[' && nestable {
+            if let Some((label, url, len)) = masked_link(rest) {
+                push_text(&mut nodes, &mut plain);
+                nodes.push(Node::Link {
+                    url: url.to_string(),
+                    children: parse_inline(label, options, depth + 1),
+                });
+                i += len;
+                continue;
+            }
+        }
+
+        // Bare URLs
+        if !options.no_autolink && c == 'h' && at_word_start(text, i) {
+            if let Some(len) = bare_url_len(rest) {
+                push_text(&mut nodes, &mut plain);
+                let url = &rest[..len];
+                nodes.push(Node::Link {
+                    url: url.to_string(),
+                    children: vec![Node::Text {
+                        text: url.to_string(),
+                    }],
+                });
+                i += len;
+                continue;
+            }
+        }
+
+        // Paired delimiters
+        for &delim in DELIMITERS.iter().filter(|_| nestable) {
+            if !rest.starts_with(delim) {
+                continue;
+            }
+            // `_emphasis_` only at word boundaries, so snake_case stays text.
+            if delim == "_" && !at_word_start(text, i) {
+                continue;
+            }
+            let body = &rest[delim.len()..];
+            if let Some(end) = find_closing(body, delim) {
+                let after = i + delim.len() * 2 + end;
+                if delim == "_" && text[after..].chars().next().is_some_and(char::is_alphanumeric) {
+                    continue;
+                }
+                push_text(&mut nodes, &mut plain);
+                nodes.push(delimited(delim, parse_inline(&body[..end], options, depth + 1)));
+                i = after;
+                continue 'outer;
+            }
+        }
+
+        plain.push(c);
+        i += c.len_utf8();
+    }
+
+    push_text(&mut nodes, &mut plain);
+    nodes
+}
+
+fn push_text(nodes: &mut Vec<Node>, plain: &mut String) {
+    if !plain.is_empty() {
+        nodes.push(Node::Text {
+            text: std::mem::take(plain),
+        });
+    }
+}
+
+/// Whether byte offset `i` starts a word (start of text or after a
+/// non-alphanumeric character).
+fn at_word_start(text: &str, i: usize) -> bool {
+    text[..i].chars().next_back().is_none_or(|c| !c.is_alphanumeric())
+}
+
+/// Offset of the closing `delim` in `body`: not escaped, not immediately at
+/// the start (no empty spans), and not preceded by whitespace.
+fn find_closing(body: &str, delim: &str) -> Option<usize> {
+    let mut from = 0;
+    while let Some(pos) = body[from..].find(delim) {
+        let end = from + pos;
+        let prev = body[..end].chars().next_back();
+        let escaped = prev == Some('\\');
+        if end > 0 && !escaped && !prev.is_some_and(char::is_whitespace) {
+            return Some(end);
+        }
+        from = end + delim.len();
+    }
+    None
+}
+
+/// Mention / timestamp / URL inside `<...>`.
+fn angle_node(inner: &str) -> Option<Node> {
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+
+    if let Some(id) = inner.strip_prefix("@&").filter(|s| digits(s)) {
+        return Some(Node::RoleMention { id: id.to_string() });
+    }
+    if let Some(id) = inner
+        .strip_prefix("@!")
+        .or_else(|| inner.strip_prefix('@'))
+        .filter(|s| digits(s))
+    {
+        return Some(Node::UserMention { id: id.to_string() });
+    }
+    if let Some(id) = inner.strip_prefix('#').filter(|s| digits(s)) {
+        return Some(Node::ChannelMention { id: id.to_string() });
+    }
+    if let Some(ts) = inner.strip_prefix("t:") {
+        let (unix, style) = match ts.split_once(':') {
+            Some((unix, style)) => (unix, Some(style)),
+            None => (ts, None),
+        };
+        let style_ok = style.is_none_or(|s| s.len() == 1 && "tTdDfFR".contains(s));
+        if let (Ok(unix), true) = (unix.parse::<i64>(), style_ok) {
+            return Some(Node::Timestamp {
+                unix,
+                style: style.map(str::to_string),
+            });
+        }
+    }
+    if (inner.starts_with("https://") || inner.starts_with("http://"))
+        && !inner.contains(char::is_whitespace)
+    {
+        return Some(Node::Link {
+            url: inner.to_string(),
+            children: vec![Node::Text {
+                text: inner.to_string(),
+            }],
+        });
+    }
+    None
+}
+
+/// `[label](http…)` → (label, url, total length).
+fn masked_link(rest: &str) -> Option<(&str, &str, usize)> {
+    let close = rest.find("](")?;
+    let label = &rest[1..close];
+    if label.is_empty() || label.contains('\n') {
+        return None;
+    }
+    let after = &rest[close + 2..];
+    let end = after.find(')')?;
+    let url = after[..end].trim();
+    let is_web = url.starts_with("https://") || url.starts_with("http://");
+    (is_web && !url.contains(char::is_whitespace)).then_some((label, url, close + 2 + end + 1))
+}
+
+/// Length of a bare http(s) URL at the start of `rest`, trimming trailing
+/// punctuation that is almost always sentence punctuation.
+fn bare_url_len(rest: &str) -> Option<usize> {
+    let scheme = if rest.starts_with("https://") {
+        8
+    } else if rest.starts_with("http://") {
+        7
+    } else {
+        return None;
+    };
+
+    let mut len = rest
+        .find(|c: char| c.is_whitespace() || c == '<')
+        .unwrap_or(rest.len());
+    loop {
+        let url = &rest[..len];
+        let last = url.chars().next_back()?;
+        let unbalanced_paren = last == ')' && url.matches('(').count() < url.matches(')').count();
+        if ".,:;!?'\"".contains(last) || unbalanced_paren {
+            len -= last.len_utf8();
+        } else {
+            break;
+        }
+    }
+    (len > scheme).then_some(len)
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Parse chat Markdown to an AST (cached by content hash).
+#[tauri::command]
+pub(crate) async fn render_markdown(
+    text: String,
+    options: Option<MarkdownOptions>,
+) -> Result<Vec<Node>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        render(&text, &options.unwrap_or_default()).to_vec()
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Parse many messages in one IPC round trip (channel backfill).
+#[tauri::command]
+pub(crate) async fn render_markdown_batch(
+    texts: Vec<String>,
+    options: Option<MarkdownOptions>,
+) -> Result<Vec<Vec<Node>>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let options = options.unwrap_or_default();
+        texts
+            .iter()
+            .map(|text| render(text, &options).to_vec())
+            .collect()
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(text: &str) -> Node {
+        Node::Text {
+            text: text.to_string(),
+        }
+    }
+
+    fn blocks(input: &str) -> Vec<Node> {
+        parse(input, &MarkdownOptions::default())
+    }
+
+    #[test]
+    fn parses_blocks() {
+        assert_eq!(
+            blocks("# Title\n- a\n- b\n```rs\nfn x() {}\n```"),
+            [
+                Node::Heading {
+                    level: 1,
+                    children: vec![text("Title")],
+                },
+                Node::List {
+                    ordered: false,
+                    start: 1,
+                    items: vec![vec![text("a")], vec![text("b")]],
+                },
+                Node::CodeBlock {
+                    lang: Some("rs".into()),
+                    code: "fn x() {}".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_inline_spans() {
+        assert_eq!(
+            blocks("**bold _em_** ||s|| <@123> snake_case_name"),
+            [Node::Paragraph {
+                children: vec![
+                    Node::Strong {
+                        children: vec![
+                            text("bold "),
+                            Node::Emphasis {
+                                children: vec![text("em")],
+                            },
+                        ],
+                    },
+                    text(" "),
+                    Node::Spoiler {
+                        children: vec![text("s")],
+                    },
+                    text(" "),
+                    Node::UserMention { id: "123".into() },
+                    text(" snake_case_name"),
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn quotes_do_not_nest() {
+        assert_eq!(
+            blocks("> > inner\n> outer"),
+            [Node::BlockQuote {
+                children: vec![Node::Paragraph {
+                    children: vec![text("> inner"), Node::LineBreak, text("outer")],
+                }],
+            }]
+        );
+        assert_eq!(
+            blocks(">>> >>> x"),
+            [Node::BlockQuote {
+                children: vec![Node::Paragraph {
+                    children: vec![text(">>> x")],
+                }],
+            }]
+        );
+    }
+
+    #[test]
+    fn deep_nesting_does_not_overflow() {
+        // Run on a small stack, like the main thread on Windows.
+        std::thread::Builder::new()
+            .stack_size(256 * 1024)
+            .spawn(|| {
+                let quotes = format!("{}x", "> ".repeat(4000));
+                assert!(matches!(blocks(&quotes)[..], [Node::BlockQuote { .. }]));
+
+                let spans = format!("{}x{}", "**_".repeat(4000), "_**".repeat(4000));
+                blocks(&spans);
+
+                let links = format!("{}x{}", "[".repeat(4000), "](https://a.b)".repeat(4000));
+                blocks(&links);
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn caches_by_content() {
+        let options = MarkdownOptions::default();
+        let first = render("cached *text*", &options);
+        assert!(Arc::ptr_eq(&first, &render("cached *text*", &options)));
+        let inline = MarkdownOptions {
+            inline_only: true,
+            ..MarkdownOptions::default()
+        };
+        assert!(!Arc::ptr_eq(&first, &render("cached *text*", &inline)));
+    }
+}
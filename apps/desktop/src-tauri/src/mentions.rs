@@ -0,0 +1,217 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::keywords::{self, FilterAction};
+use crate::store;
+
+// ===========================================================================
+// Mentions Inbox
+// ===========================================================================
+//
+// A cross-server index of messages that mention the user or hit one of
+// their emphasized keywords. The frontend passes every incoming message to
+// `index_message`; anything relevant is recorded in the native store, so the
+// Mentions panel can list hits from servers that are no longer loaded — or
+// from before a restart — without refetching history.
+//
+// A message is recorded when it contains, for the configured identity:
+//   - a user mention `<@id>` / `<@!id>`
+//   - a role mention `<@&role>` for one of the user's roles
+//   - `@everyone` / `@here` (unless the caller marks them suppressed)
+//   - a keyword rule with the `emphasize` action (see `keywords`)
+//
+// The inbox keeps the most recent `MAX_ENTRIES` hits.
+// ===========================================================================
+
+const STORE_DOC: &str = "mentions";
+const MAX_ENTRIES: usize = 2000;
+
+/// Who "me" is, for mention detection.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MentionIdentity {
+    pub user_id: String,
+    pub role_ids: Vec<String>,
+}
+
+/// An incoming message offered to the inbox.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct IndexedMessage {
+    pub message_id: String,
+    pub channel_id: String,
+    pub guild_id: Option<String>,
+    pub author: String,
+    pub content: String,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    /// Server-side "suppress @everyone/@here" setting for this guild.
+    #[serde(default)]
+    pub suppress_everyone: bool,
+}
+
+/// Why a message was recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum MentionKind {
+    User,
+    Role,
+    Everyone,
+    Keyword,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MentionEntry {
+    pub message: IndexedMessage,
+    pub kinds: Vec<MentionKind>,
+    /// Keyword patterns that matched, if any.
+    pub keywords: Vec<String>,
+}
+
+/// Query for `get_recent_mentions`. All fields optional.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct MentionFilters {
+    pub guild_id: Option<String>,
+    pub kinds: Option<Vec<MentionKind>>,
+    /// Only entries newer than this (ms since the Unix epoch).
+    pub since: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Inbox {
+    identity: MentionIdentity,
+    /// Oldest first.
+    entries: VecDeque<MentionEntry>,
+}
+
+static INBOX: Mutex<Option<Inbox>> = Mutex::new(None);
+
+fn with_inbox<R>(f: impl FnOnce(&mut Inbox) -> R) -> R {
+    let mut guard = INBOX.lock().unwrap();
+    f(guard.get_or_insert_with(|| store::load(STORE_DOC)))
+}
+
+/// Mention kinds present in `message` for `identity`.
+fn mention_kinds(identity: &MentionIdentity, message: &IndexedMessage) -> Vec<MentionKind> {
+    let content = &message.content;
+    let mut kinds = Vec::new();
+
+    let me = &identity.user_id;
+    if !me.is_empty()
+        && (content.contains(&format!("<@{me}>")) || content.contains(&format!("<@!{me}>")))
+    {
+        kinds.push(MentionKind::User);
+    }
+    if identity
+        .role_ids
+        .iter()
+        .any(|role| content.contains(&format!("<@&{role}>")))
+    {
+        kinds.push(MentionKind::Role);
+    }
+    if !message.suppress_everyone && (content.contains("@everyone") || content.contains("@here")) {
+        kinds.push(MentionKind::Everyone);
+    }
+    kinds
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Set the identity used for mention detection (call after login).
+#[tauri::command]
+pub(crate) fn set_mention_identity(identity: MentionIdentity) -> Result<(), String> {
+    with_inbox(|inbox| {
+        inbox.identity = identity;
+        store::save(STORE_DOC, inbox)
+    })
+}
+
+/// Offer an incoming message to the inbox. Returns the entry if recorded.
+#[tauri::command]
+pub(crate) fn index_message(message: IndexedMessage) -> Result<Option<MentionEntry>, String> {
+    let classification = keywords::with_matcher(|m| m.classify(&message.content));
+    let keywords = if classification.action == FilterAction::Emphasize {
+        classification.matched
+    } else {
+        Vec::new()
+    };
+
+    with_inbox(|inbox| {
+        let mut kinds = mention_kinds(&inbox.identity, &message);
+        if !keywords.is_empty() {
+            kinds.push(MentionKind::Keyword);
+        }
+        if kinds.is_empty() {
+            return Ok(None);
+        }
+        // Gateway replays (reconnect/resume) deliver the same message again.
+        if inbox
+            .entries
+            .iter()
+            .any(|e| e.message.message_id == message.message_id)
+        {
+            return Ok(None);
+        }
+
+        let entry = MentionEntry {
+            message,
+            kinds,
+            keywords,
+        };
+        inbox.entries.push_back(entry.clone());
+        while inbox.entries.len() > MAX_ENTRIES {
+            inbox.entries.pop_front();
+        }
+        store::save(STORE_DOC, inbox)?;
+        Ok(Some(entry))
+    })
+}
+
+/// Recorded mentions, newest first.
+#[tauri::command]
+pub(crate) fn get_recent_mentions(filters: Option<MentionFilters>) -> Vec<MentionEntry> {
+    let filters = filters.unwrap_or_default();
+    with_inbox(|inbox| {
+        inbox
+            .entries
+            .iter()
+            .rev()
+            .filter(|e| {
+                filters
+                    .guild_id
+                    .as_ref()
+                    .is_none_or(|g| e.message.guild_id.as_ref() == Some(g))
+            })
+            .filter(|e| {
+                filters
+                    .kinds
+                    .as_ref()
+                    .is_none_or(|kinds| e.kinds.iter().any(|k| kinds.contains(k)))
+            })
+            .filter(|e| {
+                filters
+                    .since
+                    .is_none_or(|since| e.message.timestamp > since)
+            })
+            .take(filters.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect()
+    })
+}
+
+/// Remove one entry (e.g. "mark as read" in the Mentions panel).
+#[tauri::command]
+pub(crate) fn dismiss_mention(message_id: String) -> Result<(), String> {
+    with_inbox(|inbox| {
+        inbox.entries.retain(|e| e.message.message_id != message_id);
+        store::save_cleared(STORE_DOC, inbox)
+    })
+}
@@ -0,0 +1,321 @@
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::Emitter;
+
+use crate::{scheduler, APP_HANDLE};
+
+// ===========================================================================
+// System Microphone Mute
+// ===========================================================================
+//
+// Keeps Ripcord's mute and the OS mic mute in step (Windows), so muting in
+// the app shows as muted in the quick settings and Sound settings, and
+// muting from there shows up as muted in Ripcord.
+//
+// The state lives on the default communications capture endpoint and is
+// read and written through its `IAudioEndpointVolume` — the device-level
+// mute every capturing app sees, rather than a per-session
+// `ISimpleAudioVolume` mute that would only silence Ripcord's own stream.
+// The taskbar's in-call mute button (Win+Alt+K) is a different thing: it is
+// only offered to apps using the Teams-style call APIs, which have no
+// public Win32 surface.
+//
+// The endpoint is re-resolved on every read, so switching the default mic
+// follows along. The state is polled on the scheduler and
+// `system-mic-mute-changed` is emitted with the new `SystemMicMute` when it
+// flips. `set_system_mic_mute` records what it wrote, so Ripcord's own
+// changes are not reported back to it.
+//
+// Other platforms report the mute as unavailable.
+// ===========================================================================
+
+const CHECK_KEY: &str = "mic-mute-check";
+const CHECK_INTERVAL_MS: u64 = 1_000;
+
+/// The OS mic mute, as sent with `system-mic-mute-changed`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SystemMicMute {
+    /// Whether there is a capture device whose mute could be read.
+    pub available: bool,
+    pub muted: bool,
+}
+
+static STATE: Mutex<Option<SystemMicMute>> = Mutex::new(None);
+
+// ---------------------------------------------------------------------------
+// Windows (Core Audio)
+// ---------------------------------------------------------------------------
+
+#[cfg(target_os = "windows")]
+#[allow(non_upper_case_globals, clippy::upper_case_acronyms)]
+mod win32 {
+    use std::ffi::c_void;
+
+    pub const COINIT_MULTITHREADED: u32 = 0;
+    pub const CLSCTX_ALL: u32 = 0x17;
+    /// `EDataFlow::eCapture`.
+    pub const E_CAPTURE: i32 = 1;
+    /// `ERole::eCommunications`.
+    pub const E_COMMUNICATIONS: i32 = 2;
+
+    #[repr(C)]
+    pub struct GUID {
+        pub data1: u32,
+        pub data2: u16,
+        pub data3: u16,
+        pub data4: [u8; 8],
+    }
+
+    pub const CLSID_MMDeviceEnumerator: GUID = GUID {
+        data1: 0xbcde_0395,
+        data2: 0xe52f,
+        data3: 0x467c,
+        data4: [0x8e, 0x3d, 0xc4, 0x57, 0x92, 0x91, 0x69, 0x2e],
+    };
+    pub const IID_IMMDeviceEnumerator: GUID = GUID {
+        data1: 0xa956_64d2,
+        data2: 0x9614,
+        data3: 0x4f35,
+        data4: [0xa7, 0x46, 0xde, 0x8d, 0xb6, 0x36, 0x17, 0xe6],
+    };
+    pub const IID_IAudioEndpointVolume: GUID = GUID {
+        data1: 0x5cdf_2c82,
+        data2: 0x841e,
+        data3: 0x4546,
+        data4: [0x97, 0x22, 0x0c, 0xf7, 0x40, 0x78, 0x22, 0x9a],
+    };
+
+    /// `IMMDeviceEnumerator` vtable, up to `GetDefaultAudioEndpoint`.
+    #[repr(C)]
+    pub struct IMMDeviceEnumeratorVtbl {
+        pub query_interface: usize,
+        pub add_ref: usize,
+        pub release: unsafe extern "system" fn(*mut c_void) -> u32,
+        pub enum_audio_endpoints: usize,
+        pub get_default_audio_endpoint:
+            unsafe extern "system" fn(*mut c_void, i32, i32, *mut *mut c_void) -> i32,
+    }
+
+    /// `IMMDevice` vtable, up to `Activate`.
+    #[repr(C)]
+    pub struct IMMDeviceVtbl {
+        pub query_interface: usize,
+        pub add_ref: usize,
+        pub release: unsafe extern "system" fn(*mut c_void) -> u32,
+        pub activate: unsafe extern "system" fn(
+            *mut c_void,
+            *const GUID,
+            u32,
+            *mut c_void,
+            *mut *mut c_void,
+        ) -> i32,
+    }
+
+    /// `IAudioEndpointVolume` vtable, up to `GetMute`.
+    #[repr(C)]
+    pub struct IAudioEndpointVolumeVtbl {
+        pub query_interface: usize,
+        pub add_ref: usize,
+        pub release: unsafe extern "system" fn(*mut c_void) -> u32,
+        /// Change notifications, channel count, master and channel volume.
+        pub _inherited: [usize; 11],
+        pub set_mute: unsafe extern "system" fn(*mut c_void, i32, *const GUID) -> i32,
+        pub get_mute: unsafe extern "system" fn(*mut c_void, *mut i32) -> i32,
+    }
+
+    #[link(name = "ole32")]
+    extern "system" {
+        pub fn CoInitializeEx(reserved: *mut c_void, co_init: u32) -> i32;
+        pub fn CoUninitialize();
+        pub fn CoCreateInstance(
+            clsid: *const GUID,
+            outer: *mut c_void,
+            cls_context: u32,
+            iid: *const GUID,
+            out: *mut *mut c_void,
+        ) -> i32;
+    }
+}
+
+/// `IAudioEndpointVolume` of the default communications mic. COM must be
+/// initialized on the calling thread.
+#[cfg(target_os = "windows")]
+fn open_endpoint() -> Result<*mut std::ffi::c_void, String> {
+    use std::ffi::c_void;
+    use std::ptr::null_mut;
+
+    let mut enumerator: *mut c_void = null_mut();
+    let hr = unsafe {
+        win32::CoCreateInstance(
+            &win32::CLSID_MMDeviceEnumerator,
+            null_mut(),
+            win32::CLSCTX_ALL,
+            &win32::IID_IMMDeviceEnumerator,
+            &mut enumerator,
+        )
+    };
+    if hr < 0 {
+        return Err(format!(
+            "audio devices are unavailable (0x{:08x})",
+            hr as u32
+        ));
+    }
+    let enumerator_vtbl =
+        unsafe { &**(enumerator as *const *const win32::IMMDeviceEnumeratorVtbl) };
+
+    let mut device: *mut c_void = null_mut();
+    let hr = unsafe {
+        (enumerator_vtbl.get_default_audio_endpoint)(
+            enumerator,
+            win32::E_CAPTURE,
+            win32::E_COMMUNICATIONS,
+            &mut device,
+        )
+    };
+    unsafe { (enumerator_vtbl.release)(enumerator) };
+    if hr < 0 {
+        return Err("no microphone is connected".to_string());
+    }
+    let device_vtbl = unsafe { &**(device as *const *const win32::IMMDeviceVtbl) };
+
+    let mut volume: *mut c_void = null_mut();
+    let hr = unsafe {
+        (device_vtbl.activate)(
+            device,
+            &win32::IID_IAudioEndpointVolume,
+            win32::CLSCTX_ALL,
+            null_mut(),
+            &mut volume,
+        )
+    };
+    unsafe { (device_vtbl.release)(device) };
+    if hr < 0 {
+        return Err(format!(
+            "microphone volume is unavailable (0x{:08x})",
+            hr as u32
+        ));
+    }
+    Ok(volume)
+}
+
+/// Run `f` against the default communications mic's endpoint volume.
+#[cfg(target_os = "windows")]
+fn with_endpoint<R>(
+    f: impl FnOnce(*mut std::ffi::c_void, &win32::IAudioEndpointVolumeVtbl) -> Result<R, String>,
+) -> Result<R, String> {
+    // Fails with RPC_E_CHANGED_MODE on a thread that is already STA, where
+    // COM is usable as-is and must not be uninitialized by us.
+    let initialized =
+        unsafe { win32::CoInitializeEx(std::ptr::null_mut(), win32::COINIT_MULTITHREADED) } >= 0;
+
+    let result = open_endpoint().and_then(|volume| {
+        let vtbl = unsafe { &**(volume as *const *const win32::IAudioEndpointVolumeVtbl) };
+        let result = f(volume, vtbl);
+        unsafe { (vtbl.release)(volume) };
+        result
+    });
+
+    if initialized {
+        unsafe { win32::CoUninitialize() };
+    }
+    result
+}
+
+#[cfg(target_os = "windows")]
+fn read_state() -> SystemMicMute {
+    let muted = with_endpoint(|volume, vtbl| {
+        let mut muted = 0;
+        if unsafe { (vtbl.get_mute)(volume, &mut muted) } < 0 {
+            return Err("GetMute failed".to_string());
+        }
+        Ok(muted != 0)
+    });
+    match muted {
+        Ok(muted) => SystemMicMute {
+            available: true,
+            muted,
+        },
+        Err(_) => SystemMicMute::default(),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn write_mute(muted: bool) -> Result<(), String> {
+    with_endpoint(|volume, vtbl| {
+        let hr = unsafe { (vtbl.set_mute)(volume, i32::from(muted), std::ptr::null()) };
+        if hr < 0 {
+            return Err(format!(
+                "could not change the microphone mute (0x{:08x})",
+                hr as u32
+            ));
+        }
+        Ok(())
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Other platforms
+// ---------------------------------------------------------------------------
+
+#[cfg(not(target_os = "windows"))]
+fn read_state() -> SystemMicMute {
+    SystemMicMute::default()
+}
+
+#[cfg(not(target_os = "windows"))]
+fn write_mute(_muted: bool) -> Result<(), String> {
+    Err("the system microphone mute is only available on Windows".to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Polling
+// ---------------------------------------------------------------------------
+
+fn check(_key: &str) {
+    let state = read_state();
+    let previous = STATE.lock().unwrap().replace(state);
+    if previous != Some(state) {
+        if let Some(handle) = APP_HANDLE.get() {
+            let _ = handle.emit("system-mic-mute-changed", state);
+        }
+    }
+    scheduler::schedule(CHECK_KEY, scheduler::now_ms() + CHECK_INTERVAL_MS, check);
+}
+
+/// Start watching the OS mic mute (Windows only; elsewhere there is
+/// nothing to watch).
+pub(crate) fn init() {
+    if cfg!(target_os = "windows") {
+        scheduler::schedule(CHECK_KEY, scheduler::now_ms(), check);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// The OS microphone mute.
+#[tauri::command]
+pub(crate) async fn get_system_mic_mute() -> Result<SystemMicMute, String> {
+    tauri::async_runtime::spawn_blocking(read_state)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Mute or unmute the microphone at the OS level, so the system indicator
+/// matches Ripcord's mute.
+#[tauri::command]
+pub(crate) async fn set_system_mic_mute(muted: bool) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        write_mute(muted)?;
+        *STATE.lock().unwrap() = Some(SystemMicMute {
+            available: true,
+            muted,
+        });
+        Ok(())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
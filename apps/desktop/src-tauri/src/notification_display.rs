@@ -0,0 +1,248 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::Monitor;
+
+use crate::{store, APP_HANDLE};
+
+// ===========================================================================
+// Notification Display Placement
+// ===========================================================================
+//
+// Lets the user pick which monitor (and which corner of it) the app's own
+// pop-ups appear on — in-app toasts and the incoming-call window — so they
+// don't land over a fullscreen game. The frontend creates those windows;
+// it asks `get_notification_position` where to put one of a given size.
+// OS notification-center toasts are placed by the OS and can't be moved.
+//
+// Monitors are identified by an id that survives reboots and re-arranging:
+// on Windows the monitor's device interface path (EDID vendor/product plus
+// the connector), elsewhere the name the windowing system reports (the
+// connector, e.g. "DP-1", on Linux; the display's product name on macOS).
+// If the chosen monitor is unplugged, pop-ups fall back to the primary one
+// until it comes back.
+//
+// The choice lives in the "notification-display" store document.
+// ===========================================================================
+
+const STORE_DOC: &str = "notification-display";
+
+/// Gap between a pop-up and the edges of the work area, in logical pixels.
+const EDGE_MARGIN: f64 = 16.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct NotificationDisplay {
+    /// `MonitorInfo::id`; `None` follows the primary monitor.
+    pub monitor_id: Option<String>,
+    pub corner: Corner,
+}
+
+/// A connected monitor.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MonitorInfo {
+    /// Stable id — pass this to `set_notification_display`.
+    pub id: String,
+    pub name: String,
+    pub primary: bool,
+    /// Physical pixels, in desktop coordinates.
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub scale_factor: f64,
+}
+
+/// Where to place a pop-up window.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PopupPosition {
+    pub monitor_id: String,
+    /// Top-left corner in physical pixels.
+    pub x: i32,
+    pub y: i32,
+    pub scale_factor: f64,
+}
+
+static DISPLAY: Mutex<Option<NotificationDisplay>> = Mutex::new(None);
+
+fn with_display<R>(f: impl FnOnce(&mut NotificationDisplay) -> R) -> R {
+    let mut guard = DISPLAY.lock().unwrap();
+    f(guard.get_or_insert_with(|| store::load(STORE_DOC)))
+}
+
+#[cfg(target_os = "windows")]
+#[allow(clippy::upper_case_acronyms)]
+mod win32 {
+    pub const EDD_GET_DEVICE_INTERFACE_NAME: u32 = 0x1;
+
+    #[repr(C)]
+    pub struct DISPLAY_DEVICEW {
+        pub cb: u32,
+        pub device_name: [u16; 32],
+        pub device_string: [u16; 128],
+        pub state_flags: u32,
+        pub device_id: [u16; 128],
+        pub device_key: [u16; 128],
+    }
+
+    extern "system" {
+        pub fn EnumDisplayDevicesW(
+            device: *const u16,
+            index: u32,
+            display_device: *mut DISPLAY_DEVICEW,
+            flags: u32,
+        ) -> i32;
+    }
+}
+
+/// Stable id for a monitor whose GDI name (`\\.\DISPLAY1`) is `name`. The
+/// GDI name follows enumeration order; the interface path of the monitor
+/// attached to it does not.
+#[cfg(target_os = "windows")]
+fn stable_id(name: &str) -> String {
+    let wide: Vec<u16> = name.encode_utf16().chain(std::iter::once(0)).collect();
+    let mut device: win32::DISPLAY_DEVICEW = unsafe { std::mem::zeroed() };
+    device.cb = std::mem::size_of::<win32::DISPLAY_DEVICEW>() as u32;
+    let found = unsafe {
+        win32::EnumDisplayDevicesW(
+            wide.as_ptr(),
+            0,
+            &mut device,
+            win32::EDD_GET_DEVICE_INTERFACE_NAME,
+        )
+    } != 0;
+    let end = device.device_id.iter().position(|&c| c == 0).unwrap_or(128);
+    if !found || end == 0 {
+        return name.to_string();
+    }
+    String::from_utf16_lossy(&device.device_id[..end])
+}
+
+#[cfg(not(target_os = "windows"))]
+fn stable_id(name: &str) -> String {
+    name.to_string()
+}
+
+/// Connected monitors with their ids, primary first.
+fn monitors() -> Result<Vec<(MonitorInfo, Monitor)>, String> {
+    let handle = APP_HANDLE.get().ok_or("app not initialised")?;
+    let primary = handle
+        .primary_monitor()
+        .map_err(|e| e.to_string())?
+        .and_then(|monitor| monitor.name().cloned());
+    let mut monitors: Vec<_> = handle
+        .available_monitors()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .enumerate()
+        .map(|(index, monitor)| {
+            let name = monitor
+                .name()
+                .cloned()
+                .unwrap_or_else(|| format!("Display {}", index + 1));
+            let info = MonitorInfo {
+                id: stable_id(&name),
+                primary: primary.as_ref() == monitor.name(),
+                x: monitor.position().x,
+                y: monitor.position().y,
+                width: monitor.size().width,
+                height: monitor.size().height,
+                scale_factor: monitor.scale_factor(),
+                name,
+            };
+            (info, monitor)
+        })
+        .collect();
+    monitors.sort_by_key(|(info, _)| !info.primary);
+    Ok(monitors)
+}
+
+/// Top-left of a `width` x `height` (logical) pop-up in `corner` of the
+/// monitor's work area.
+fn place(monitor: &Monitor, corner: Corner, width: f64, height: f64) -> (i32, i32) {
+    let scale = monitor.scale_factor();
+    let area = monitor.work_area();
+    let margin = (EDGE_MARGIN * scale).round() as i32;
+    let (w, h) = (
+        (width * scale).round() as i32,
+        (height * scale).round() as i32,
+    );
+    let (left, top) = (area.position.x, area.position.y);
+    let right = left + area.size.width as i32;
+    let bottom = top + area.size.height as i32;
+
+    let x = match corner {
+        Corner::TopLeft | Corner::BottomLeft => left + margin,
+        Corner::TopRight | Corner::BottomRight => right - margin - w,
+    };
+    let y = match corner {
+        Corner::TopLeft | Corner::TopRight => top + margin,
+        Corner::BottomLeft | Corner::BottomRight => bottom - margin - h,
+    };
+    // A pop-up larger than the work area keeps its top-left on screen.
+    (x.max(left), y.max(top))
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Connected monitors, primary first.
+#[tauri::command]
+pub(crate) fn list_monitors() -> Result<Vec<MonitorInfo>, String> {
+    Ok(monitors()?.into_iter().map(|(info, _)| info).collect())
+}
+
+/// Choose the monitor (`None` = primary) and corner for pop-ups. `corner`
+/// is one of "topLeft", "topRight", "bottomLeft", "bottomRight".
+#[tauri::command]
+pub(crate) fn set_notification_display(
+    monitor_id: Option<String>,
+    corner: Corner,
+) -> Result<(), String> {
+    with_display(|display| {
+        let updated = NotificationDisplay { monitor_id, corner };
+        store::save(STORE_DOC, &updated)?;
+        *display = updated;
+        Ok(())
+    })
+}
+
+/// The current monitor and corner choice.
+#[tauri::command]
+pub(crate) fn get_notification_display() -> NotificationDisplay {
+    with_display(|display| display.clone())
+}
+
+/// Where to open a pop-up of `width` x `height` logical pixels: the chosen
+/// corner of the chosen monitor, or of the primary one if it isn't
+/// connected.
+#[tauri::command]
+pub(crate) fn get_notification_position(width: f64, height: f64) -> Result<PopupPosition, String> {
+    let display = with_display(|display| display.clone());
+    let monitors = monitors()?;
+    let (info, monitor) = monitors
+        .iter()
+        .find(|(info, _)| display.monitor_id.as_ref() == Some(&info.id))
+        .or_else(|| monitors.first())
+        .ok_or("no monitors connected")?;
+    let (x, y) = place(monitor, display.corner, width, height);
+    Ok(PopupPosition {
+        monitor_id: info.id.clone(),
+        x,
+        y,
+        scale_factor: info.scale_factor,
+    })
+}
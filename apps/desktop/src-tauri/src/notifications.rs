@@ -0,0 +1,465 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+use std::sync::{Mutex, Once};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+use tauri_plugin_notification::NotificationExt;
+
+use crate::{idle, os_dnd, scheduler, store, timezone, usage_policy, APP_HANDLE};
+
+// ===========================================================================
+// Notification Digests
+// ===========================================================================
+//
+// For busy channels the user can opt into a digest instead of a toast per
+// message. The frontend still decides *whether* a message is notification-
+// worthy; it then calls `queue_digest_notification`, which either accepts the
+// message into the digest (returns `true`) or declines because the channel
+// is not digested (returns `false`, frontend toasts immediately as before).
+//
+// At each configured time of day the scheduler fires one summary toast for
+// everything queued since the last digest. Config, queue and the last fire
+// time live in the native store, so a queue survives restarts and a digest
+// slot that passed while the app was closed fires on the next launch.
+//
+// Times are local "HH:MM" strings, resolved through the OS time zone (see
+// `timezone`) so slots follow the user's wall clock across DST changes.
+//
+// Snooze uses the same path: while `snooze_notifications(until)` is active
+// every offered message is held (regardless of channel), and when the
+// snooze expires — on time, or at the next launch if the app was closed —
+// a single "You're back" summary covers everything that was held.
+//
+// Held messages are kept in full up to `MAX_HELD` per queue and only
+// counted per channel after that: the queue documents are rewritten on
+// every offered message, so a long snooze on busy servers must not grow
+// them without bound. Summaries count everything; the
+// `notification-digest-fired` / `notifications-snooze-ended` payloads list
+// the messages kept in full.
+//
+// Mirroring suppression avoids the desktop and the phone both buzzing for
+// the same message. In `active-device` mode a watcher samples the system
+// idle time and emits `suppress-mobile-push` (`true` while the user is
+// active at this computer, `false` once they go idle); the frontend
+// forwards the hint to the gateway, which gates mobile push on it.
+//
+// The OS's own Do Not Disturb / Focus state is tracked in `os_dnd`; while
+// it is on, offered messages are held there and summarized when it ends.
+// ===========================================================================
+
+const STORE_DOC: &str = "notification-digest";
+const SCHEDULER_KEY: &str = "notifications:digest";
+const SNOOZE_STORE_DOC: &str = "notification-snooze";
+const SNOOZE_SCHEDULER_KEY: &str = "notifications:snooze";
+const MIRRORING_STORE_DOC: &str = "notification-mirroring";
+const MS_PER_MINUTE: i64 = 60_000;
+const MS_PER_DAY: i64 = 24 * 60 * MS_PER_MINUTE;
+
+/// Messages a held queue keeps in full before it only counts them.
+const MAX_HELD: usize = 200;
+
+/// Which channels are digested and when digests fire.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DigestConfig {
+    pub channels: Vec<String>,
+    /// Local times of day, "HH:MM", in the OS zone.
+    pub times: Vec<String>,
+}
+
+/// A message held back for the next digest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DigestItem {
+    pub channel_id: String,
+    pub channel_name: String,
+    pub author: String,
+    pub preview: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DigestState {
+    config: DigestConfig,
+    queue: Vec<DigestItem>,
+    /// Messages queued past `MAX_HELD`, by channel name.
+    #[serde(default)]
+    queue_overflow: BTreeMap<String, usize>,
+    last_fired_ms: u64,
+}
+
+static DIGEST: Mutex<Option<DigestState>> = Mutex::new(None);
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SnoozeState {
+    /// Snooze end, ms since the Unix epoch. `None` = not snoozed.
+    until_ms: Option<u64>,
+    /// Messages held while snoozed.
+    missed: Vec<DigestItem>,
+    /// Messages held past `MAX_HELD`, by channel name.
+    #[serde(default)]
+    missed_overflow: BTreeMap<String, usize>,
+}
+
+static SNOOZE: Mutex<Option<SnoozeState>> = Mutex::new(None);
+
+/// Mirroring modes (`MIRRORING_*`).
+const MIRRORING_OFF: u8 = 0;
+const MIRRORING_ACTIVE_DEVICE: u8 = 1;
+
+const DEFAULT_IDLE_THRESHOLD_SECS: u64 = 5 * 60;
+const MIRRORING_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MirroringConfig {
+    mode: String,
+    idle_threshold_secs: u64,
+}
+
+impl Default for MirroringConfig {
+    fn default() -> Self {
+        Self {
+            mode: "off".to_string(),
+            idle_threshold_secs: DEFAULT_IDLE_THRESHOLD_SECS,
+        }
+    }
+}
+
+static MIRRORING_MODE: AtomicU8 = AtomicU8::new(MIRRORING_OFF);
+static MIRRORING_IDLE_THRESHOLD_SECS: AtomicU64 = AtomicU64::new(DEFAULT_IDLE_THRESHOLD_SECS);
+
+/// Last hint emitted, so the watcher only emits on transitions.
+static SUPPRESSING_MOBILE: AtomicBool = AtomicBool::new(false);
+
+/// Guards the one-time spawn of the mirroring watcher.
+static MIRRORING_WATCHER: Once = Once::new();
+
+/// Run `f` against the snooze state, loading it from the store on first use.
+fn with_snooze<R>(f: impl FnOnce(&mut SnoozeState) -> R) -> R {
+    let mut guard = SNOOZE.lock().unwrap();
+    f(guard.get_or_insert_with(|| store::load(SNOOZE_STORE_DOC)))
+}
+
+/// Run `f` against the digest state, loading it from the store on first use.
+fn with_digest<R>(f: impl FnOnce(&mut DigestState) -> R) -> R {
+    let mut guard = DIGEST.lock().unwrap();
+    f(guard.get_or_insert_with(|| store::load(STORE_DOC)))
+}
+
+/// Hold `item` for a later summary: in full while fewer than `MAX_HELD` are
+/// held, otherwise only as a count for its channel.
+pub(crate) fn hold(
+    items: &mut Vec<DigestItem>,
+    overflow: &mut BTreeMap<String, usize>,
+    item: DigestItem,
+) {
+    if items.len() < MAX_HELD {
+        items.push(item);
+    } else {
+        *overflow.entry(item.channel_name).or_default() += 1;
+    }
+}
+
+/// Number of held messages, counted ones included.
+pub(crate) fn held_count(items: &[DigestItem], overflow: &BTreeMap<String, usize>) -> usize {
+    items.len() + overflow.values().sum::<usize>()
+}
+
+/// Parse "HH:MM" into minutes after midnight.
+pub(crate) fn parse_time(time: &str) -> Option<i64> {
+    let (h, m) = time.split_once(':')?;
+    let (h, m): (i64, i64) = (h.trim().parse().ok()?, m.trim().parse().ok()?);
+    ((0..24).contains(&h) && (0..60).contains(&m)).then_some(h * 60 + m)
+}
+
+/// Most recent slot at or before `now_ms`, and the next slot after it.
+fn slots_around(config: &DigestConfig, now_ms: u64) -> Option<(u64, u64)> {
+    let offset = i64::from(timezone::utc_offset_at((now_ms / 1000) as i64)) * 1000;
+    let local_midnight = (now_ms as i64 + offset).div_euclid(MS_PER_DAY) * MS_PER_DAY;
+
+    let mut minutes: Vec<i64> = config.times.iter().filter_map(|t| parse_time(t)).collect();
+    minutes.sort_unstable();
+    minutes.dedup();
+    if minutes.is_empty() {
+        return None;
+    }
+
+    // Candidate slots yesterday, today and tomorrow, in UTC.
+    let candidates = (-1..=1).flat_map(|day| {
+        minutes.iter().map(move |m| {
+            let local = local_midnight + day * MS_PER_DAY + m * MS_PER_MINUTE;
+            timezone::local_to_utc(local / 1000) * 1000
+        })
+    });
+    let (mut prev, mut next) = (i64::MIN, i64::MAX);
+    for slot in candidates {
+        if slot <= now_ms as i64 {
+            prev = prev.max(slot);
+        } else {
+            next = next.min(slot);
+        }
+    }
+    Some((prev.max(0) as u64, next as u64))
+}
+
+/// Arm the scheduler for the next slot (or disarm if there are none).
+fn arm(state: &DigestState) {
+    match slots_around(&state.config, scheduler::now_ms()) {
+        Some((_, next)) => scheduler::schedule(SCHEDULER_KEY, next, fire_digest),
+        None => scheduler::cancel(SCHEDULER_KEY),
+    }
+}
+
+/// Scheduler task — show one summary toast for everything queued.
+fn fire_digest(_key: &str) {
+    let (items, overflow) = with_digest(|state| {
+        state.last_fired_ms = scheduler::now_ms();
+        let items = std::mem::take(&mut state.queue);
+        let overflow = std::mem::take(&mut state.queue_overflow);
+        let _ = store::save(STORE_DOC, state);
+        arm(state);
+        (items, overflow)
+    });
+    if items.is_empty() {
+        return;
+    }
+
+    let title = match held_count(&items, &overflow) {
+        1 => "1 new message".to_string(),
+        n => format!("{n} new messages"),
+    };
+    if let Some(handle) = APP_HANDLE.get() {
+        let _ = handle
+            .notification()
+            .builder()
+            .title(&title)
+            .body(summarize_channels(&items, &overflow))
+            .show();
+        let _ = handle.emit("notification-digest-fired", &items);
+    }
+}
+
+/// Scheduler task — end the snooze and summarize what was held.
+fn end_snooze(_key: &str) {
+    let (missed, overflow) = with_snooze(|state| {
+        state.until_ms = None;
+        let missed = std::mem::take(&mut state.missed);
+        let overflow = std::mem::take(&mut state.missed_overflow);
+        let _ = store::save(SNOOZE_STORE_DOC, state);
+        (missed, overflow)
+    });
+
+    if let Some(handle) = APP_HANDLE.get() {
+        let body = match held_count(&missed, &overflow) {
+            0 => "Notifications are back on.".to_string(),
+            n => format!(
+                "{n} while snoozed: {}",
+                summarize_channels(&missed, &overflow)
+            ),
+        };
+        let _ = handle
+            .notification()
+            .builder()
+            .title("You're back")
+            .body(body)
+            .show();
+        let _ = handle.emit("notifications-snooze-ended", &missed);
+    }
+}
+
+/// "#general (3), #random (1)" — per-channel counts in a stable order,
+/// including messages only counted in `overflow`.
+pub(crate) fn summarize_channels(
+    items: &[DigestItem],
+    overflow: &BTreeMap<String, usize>,
+) -> String {
+    let mut per_channel: BTreeMap<&str, usize> = BTreeMap::new();
+    for item in items {
+        *per_channel.entry(item.channel_name.as_str()).or_default() += 1;
+    }
+    for (name, n) in overflow {
+        *per_channel.entry(name.as_str()).or_default() += n;
+    }
+    per_channel
+        .iter()
+        .map(|(name, n)| format!("#{name} ({n})"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Background loop emitting `suppress-mobile-push` on activity transitions.
+fn mirroring_watcher() {
+    loop {
+        let suppress = MIRRORING_MODE.load(Ordering::Relaxed) == MIRRORING_ACTIVE_DEVICE
+            && idle::idle_seconds()
+                .is_some_and(|idle| idle < MIRRORING_IDLE_THRESHOLD_SECS.load(Ordering::Relaxed));
+
+        if SUPPRESSING_MOBILE.swap(suppress, Ordering::Relaxed) != suppress {
+            if let Some(handle) = APP_HANDLE.get() {
+                let _ = handle.emit("suppress-mobile-push", suppress);
+            }
+        }
+        std::thread::sleep(MIRRORING_POLL_INTERVAL);
+    }
+}
+
+/// Apply a mirroring config to the watcher.
+fn apply_mirroring(config: &MirroringConfig) -> Result<(), String> {
+    let mode = match config.mode.as_str() {
+        "off" => MIRRORING_OFF,
+        "active-device" => MIRRORING_ACTIVE_DEVICE,
+        other => return Err(format!("unknown notification mirroring mode: {other}")),
+    };
+    MIRRORING_IDLE_THRESHOLD_SECS.store(config.idle_threshold_secs, Ordering::Relaxed);
+    MIRRORING_MODE.store(mode, Ordering::Relaxed);
+    if mode != MIRRORING_OFF {
+        MIRRORING_WATCHER.call_once(|| {
+            std::thread::spawn(mirroring_watcher);
+        });
+    }
+    Ok(())
+}
+
+/// Restore digest state and timers at startup.
+pub(crate) fn init() {
+    let _ = apply_mirroring(&store::load(MIRRORING_STORE_DOC));
+
+    let overdue = with_digest(|state| {
+        arm(state);
+        // A slot passed while the app was closed and there is still a queue.
+        matches!(
+            slots_around(&state.config, scheduler::now_ms()),
+            Some((prev, _)) if prev > state.last_fired_ms && !state.queue.is_empty()
+        )
+    });
+    if overdue {
+        fire_digest(SCHEDULER_KEY);
+    }
+
+    // An expired snooze is scheduled in the past and so ends immediately.
+    if let Some(until) = with_snooze(|state| state.until_ms) {
+        scheduler::schedule(SNOOZE_SCHEDULER_KEY, until, end_snooze);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Replace the digest configuration and re-arm the schedule.
+#[tauri::command]
+pub(crate) fn set_digest_config(config: DigestConfig) -> Result<(), String> {
+    if let Some(bad) = config.times.iter().find(|t| parse_time(t).is_none()) {
+        return Err(format!("invalid digest time: {bad}"));
+    }
+    with_digest(|state| {
+        state.config = config;
+        // Messages from channels no longer digested would never be shown.
+        // Counted ones can't be told apart by channel id, so they stay.
+        let channels = &state.config.channels;
+        state
+            .queue
+            .retain(|item| channels.contains(&item.channel_id));
+        arm(state);
+        store::save(STORE_DOC, state)
+    })
+}
+
+/// Current digest configuration.
+#[tauri::command]
+pub(crate) fn get_digest_config() -> DigestConfig {
+    with_digest(|state| state.config.clone())
+}
+
+/// Offer a message to the digest/snooze queues. Returns `true` if it was
+/// held (or dropped during policy quiet hours), `false` if it should be
+/// shown immediately. While the OS is in Do Not Disturb every message is
+/// held by `os_dnd` instead.
+#[tauri::command]
+pub(crate) async fn queue_digest_notification(item: DigestItem) -> Result<bool, String> {
+    // Holding a message saves a store document; keep that off the main
+    // thread.
+    tauri::async_runtime::spawn_blocking(move || queue_item(item))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn queue_item(item: DigestItem) -> Result<bool, String> {
+    if usage_policy::notifications_suppressed() {
+        return Ok(true);
+    }
+    if os_dnd::defer(&item)? {
+        return Ok(true);
+    }
+
+    let snoozed = with_snooze(|state| {
+        if state.until_ms.is_none() {
+            return Ok(None);
+        }
+        hold(&mut state.missed, &mut state.missed_overflow, item.clone());
+        store::save(SNOOZE_STORE_DOC, state).map(|_| Some(true))
+    })?;
+    if let Some(held) = snoozed {
+        return Ok(held);
+    }
+
+    with_digest(|state| {
+        if !state.config.channels.contains(&item.channel_id) {
+            return Ok(false);
+        }
+        hold(&mut state.queue, &mut state.queue_overflow, item);
+        store::save(STORE_DOC, state).map(|_| true)
+    })
+}
+
+/// Snooze all notifications until `until_ms` (ms since the Unix epoch).
+/// Pass `None` to end the snooze now, which still shows the summary.
+#[tauri::command]
+pub(crate) fn snooze_notifications(until_ms: Option<u64>) -> Result<(), String> {
+    match until_ms {
+        Some(until) => {
+            with_snooze(|state| {
+                state.until_ms = Some(until);
+                store::save(SNOOZE_STORE_DOC, state)
+            })?;
+            scheduler::schedule(SNOOZE_SCHEDULER_KEY, until, end_snooze);
+        }
+        None => {
+            scheduler::cancel(SNOOZE_SCHEDULER_KEY);
+            if with_snooze(|state| state.until_ms.is_some()) {
+                end_snooze(SNOOZE_SCHEDULER_KEY);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// End of the active snooze (ms since the Unix epoch), if any.
+#[tauri::command]
+pub(crate) fn get_notification_snooze() -> Option<u64> {
+    with_snooze(|state| state.until_ms)
+}
+
+/// Set the desktop/mobile notification mirroring policy.
+///
+///   `"off"`           — no hints; every device notifies
+///   `"active-device"` — suppress mobile push while the user is active here
+///
+/// `idle_threshold_secs` is how long without input counts as idle
+/// (default 5 minutes).
+#[tauri::command]
+pub(crate) fn set_notification_mirroring(
+    mode: String,
+    idle_threshold_secs: Option<u64>,
+) -> Result<(), String> {
+    let config = MirroringConfig {
+        mode,
+        idle_threshold_secs: idle_threshold_secs.unwrap_or(DEFAULT_IDLE_THRESHOLD_SECS),
+    };
+    apply_mirroring(&config)?;
+    store::save(MIRRORING_STORE_DOC, &config)
+}
@@ -0,0 +1,777 @@
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::compat::{self, CompatPreference};
+use crate::{audit, gpu, os_dnd, scheduler};
+
+// ===========================================================================
+// First-Run Checks
+// ===========================================================================
+//
+// `run_first_time_checks()` looks at everything the onboarding wizard needs
+// to get right before the first voice call, in one pass:
+//
+//   microphone     a capture device exists and the OS lets Ripcord use it
+//                  (Windows privacy settings, macOS TCC; Linux has no gate)
+//   output         speakers or headphones exist
+//   udp            a STUN binding request gets an answer, i.e. voice can
+//                  use UDP instead of falling back to TCP
+//   gpu            the webview renders on a hardware GPU (see `compat`)
+//   notifications  toasts are allowed for Ripcord and DND is off
+//
+// Each result carries a status the wizard can colour and, where the user
+// can do something about it, a `FixAction`: a Ripcord command to invoke
+// with its arguments — usually `open_system_settings` on the right page.
+// The webview's shell scope can't open `ms-settings:` or
+// `x-apple.systempreferences:` URLs itself, so that command does it.
+//
+// The microphone check only reads the permission state; the macOS prompt
+// appears when the webview first asks for the mic.
+// ===========================================================================
+
+/// Answers binding requests from anywhere; overridable per call.
+const DEFAULT_STUN_SERVER: &str = "stun.l.google.com:19302";
+const STUN_ATTEMPTS: u32 = 3;
+const STUN_TIMEOUT: Duration = Duration::from_millis(800);
+const STUN_MAGIC: u32 = 0x2112_A442;
+const STUN_XOR_MAPPED_ADDRESS: u16 = 0x0020;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum CheckStatus {
+    Passed,
+    /// Works, but not as well as it could.
+    Warning,
+    Failed,
+    /// Couldn't be determined on this platform.
+    Unknown,
+}
+
+/// Something the wizard can offer as a one-click fix.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FixAction {
+    /// Button text.
+    pub label: String,
+    /// Ripcord command to invoke.
+    pub command: &'static str,
+    pub args: Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FirstRunCheck {
+    /// "microphone", "output", "udp", "gpu" or "notifications".
+    pub id: &'static str,
+    pub status: CheckStatus,
+    /// One sentence for the user.
+    pub detail: String,
+    pub fix: Option<FixAction>,
+}
+
+impl FirstRunCheck {
+    fn new(id: &'static str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            id,
+            status,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn with_fix(mut self, fix: Option<FixAction>) -> Self {
+        self.fix = fix;
+        self
+    }
+}
+
+/// A system settings page `open_system_settings` can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum SettingsPage {
+    Microphone,
+    Sound,
+    Notifications,
+}
+
+/// What the OS says about microphone access. Each platform only reports
+/// some of these.
+#[allow(dead_code)]
+enum MicPermission {
+    Granted,
+    /// Not asked yet; the OS prompts on first use.
+    NotAsked,
+    /// Blocked, and where.
+    Denied(&'static str),
+    /// The platform doesn't gate microphone access.
+    Ungated,
+}
+
+// ---------------------------------------------------------------------------
+// Windows
+// ---------------------------------------------------------------------------
+
+#[cfg(target_os = "windows")]
+mod win32 {
+    use std::ffi::c_void;
+
+    // Predefined keys are sign-extended 32-bit values.
+    pub const HKEY_CURRENT_USER: isize = 0x8000_0001_u32 as i32 as isize;
+    pub const HKEY_LOCAL_MACHINE: isize = 0x8000_0002_u32 as i32 as isize;
+    pub const RRF_RT_REG_SZ: u32 = 0x0000_0002;
+    pub const RRF_RT_REG_DWORD: u32 = 0x0000_0010;
+    pub const SW_SHOWNORMAL: i32 = 1;
+
+    #[link(name = "advapi32")]
+    extern "system" {
+        pub fn RegGetValueW(
+            key: isize,
+            sub_key: *const u16,
+            value: *const u16,
+            flags: u32,
+            value_type: *mut u32,
+            data: *mut c_void,
+            data_len: *mut u32,
+        ) -> i32;
+    }
+
+    #[link(name = "winmm")]
+    extern "system" {
+        pub fn waveInGetNumDevs() -> u32;
+        pub fn waveOutGetNumDevs() -> u32;
+    }
+
+    #[link(name = "shell32")]
+    extern "system" {
+        pub fn ShellExecuteW(
+            hwnd: isize,
+            operation: *const u16,
+            file: *const u16,
+            parameters: *const u16,
+            directory: *const u16,
+            show: i32,
+        ) -> isize;
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+#[cfg(target_os = "windows")]
+fn reg_string(root: isize, path: &str, value: &str) -> Option<String> {
+    let mut buf = [0u16; 256];
+    let mut len = std::mem::size_of_val(&buf) as u32;
+    let status = unsafe {
+        win32::RegGetValueW(
+            root,
+            wide(path).as_ptr(),
+            wide(value).as_ptr(),
+            win32::RRF_RT_REG_SZ,
+            std::ptr::null_mut(),
+            buf.as_mut_ptr().cast(),
+            &mut len,
+        )
+    };
+    if status != 0 {
+        return None;
+    }
+    let chars = &buf[..len as usize / 2];
+    let end = chars.iter().position(|&c| c == 0).unwrap_or(chars.len());
+    Some(String::from_utf16_lossy(&chars[..end]))
+}
+
+#[cfg(target_os = "windows")]
+fn reg_dword(root: isize, path: &str, value: &str) -> Option<u32> {
+    let mut data = 0u32;
+    let mut len = std::mem::size_of::<u32>() as u32;
+    let status = unsafe {
+        win32::RegGetValueW(
+            root,
+            wide(path).as_ptr(),
+            wide(value).as_ptr(),
+            win32::RRF_RT_REG_DWORD,
+            std::ptr::null_mut(),
+            (&mut data as *mut u32).cast(),
+            &mut len,
+        )
+    };
+    (status == 0).then_some(data)
+}
+
+#[cfg(target_os = "windows")]
+fn has_device(capture: bool) -> Option<bool> {
+    let count = unsafe {
+        if capture {
+            win32::waveInGetNumDevs()
+        } else {
+            win32::waveOutGetNumDevs()
+        }
+    };
+    Some(count > 0)
+}
+
+/// The Settings → Privacy → Microphone switches, machine-wide first.
+#[cfg(target_os = "windows")]
+fn mic_permission() -> MicPermission {
+    const CONSENT: &str = r"Software\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore\microphone";
+    let denied = |root, path: &str| reg_string(root, path, "Value").as_deref() == Some("Deny");
+
+    if denied(win32::HKEY_LOCAL_MACHINE, CONSENT) {
+        MicPermission::Denied("microphone access is turned off for this device")
+    } else if denied(win32::HKEY_CURRENT_USER, CONSENT) {
+        MicPermission::Denied("microphone access is turned off")
+    } else if denied(win32::HKEY_CURRENT_USER, &format!(r"{CONSENT}\NonPackaged")) {
+        MicPermission::Denied("desktop apps aren't allowed to use the microphone")
+    } else {
+        MicPermission::Granted
+    }
+}
+
+/// Why toasts are off, if they are.
+#[cfg(target_os = "windows")]
+fn notifications_blocked() -> Option<&'static str> {
+    let all_off = reg_dword(
+        win32::HKEY_CURRENT_USER,
+        r"Software\Microsoft\Windows\CurrentVersion\PushNotifications",
+        "ToastEnabled",
+    ) == Some(0);
+    if all_off {
+        return Some("Notifications are turned off for all apps.");
+    }
+    let identifier = crate::APP_HANDLE.get()?.config().identifier.clone();
+    let app_off = reg_dword(
+        win32::HKEY_CURRENT_USER,
+        &format!(r"Software\Microsoft\Windows\CurrentVersion\Notifications\Settings\{identifier}"),
+        "Enabled",
+    ) == Some(0);
+    app_off.then_some("Notifications are turned off for Ripcord.")
+}
+
+// ---------------------------------------------------------------------------
+// macOS
+// ---------------------------------------------------------------------------
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use std::ffi::{c_char, c_void};
+
+    /// `kAudioObjectSystemObject`.
+    pub const SYSTEM_OBJECT: u32 = 1;
+    /// `kAudioHardwarePropertyDefaultInputDevice` ('dIn ').
+    pub const DEFAULT_INPUT_DEVICE: u32 = 0x6449_6E20;
+    /// `kAudioHardwarePropertyDefaultOutputDevice` ('dOut').
+    pub const DEFAULT_OUTPUT_DEVICE: u32 = 0x644F_7574;
+    /// `kAudioObjectPropertyScopeGlobal` ('glob').
+    pub const SCOPE_GLOBAL: u32 = 0x676C_6F62;
+
+    #[repr(C)]
+    pub struct AudioObjectPropertyAddress {
+        pub selector: u32,
+        pub scope: u32,
+        pub element: u32,
+    }
+
+    #[link(name = "CoreAudio", kind = "framework")]
+    extern "C" {
+        pub fn AudioObjectGetPropertyData(
+            object: u32,
+            address: *const AudioObjectPropertyAddress,
+            qualifier_size: u32,
+            qualifier: *const c_void,
+            data_size: *mut u32,
+            data: *mut c_void,
+        ) -> i32;
+    }
+
+    #[link(name = "AVFoundation", kind = "framework")]
+    extern "C" {
+        pub static AVMediaTypeAudio: *const c_void;
+    }
+
+    #[link(name = "objc")]
+    extern "C" {
+        pub fn objc_getClass(name: *const c_char) -> *const c_void;
+        pub fn sel_registerName(name: *const c_char) -> *const c_void;
+        pub fn objc_msgSend();
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn has_device(capture: bool) -> Option<bool> {
+    let address = macos::AudioObjectPropertyAddress {
+        selector: if capture {
+            macos::DEFAULT_INPUT_DEVICE
+        } else {
+            macos::DEFAULT_OUTPUT_DEVICE
+        },
+        scope: macos::SCOPE_GLOBAL,
+        element: 0,
+    };
+    let mut device = 0u32;
+    let mut size = std::mem::size_of::<u32>() as u32;
+    let status = unsafe {
+        macos::AudioObjectGetPropertyData(
+            macos::SYSTEM_OBJECT,
+            &address,
+            0,
+            std::ptr::null(),
+            &mut size,
+            (&mut device as *mut u32).cast(),
+        )
+    };
+    // `kAudioObjectUnknown` (0) when there is no default device.
+    (status == 0).then_some(device != 0)
+}
+
+/// `[AVCaptureDevice authorizationStatusForMediaType:AVMediaTypeAudio]`.
+#[cfg(target_os = "macos")]
+fn mic_permission() -> MicPermission {
+    use std::ffi::c_void;
+
+    type StatusFn = unsafe extern "C" fn(*const c_void, *const c_void, *const c_void) -> isize;
+
+    let status = unsafe {
+        let class = macos::objc_getClass(c"AVCaptureDevice".as_ptr());
+        if class.is_null() {
+            return MicPermission::Granted;
+        }
+        let selector = macos::sel_registerName(c"authorizationStatusForMediaType:".as_ptr());
+        let send: StatusFn = std::mem::transmute(macos::objc_msgSend as unsafe extern "C" fn());
+        send(class, selector, macos::AVMediaTypeAudio)
+    };
+    match status {
+        0 => MicPermission::NotAsked,
+        1 => MicPermission::Denied("microphone access is restricted on this Mac"),
+        2 => MicPermission::Denied("Ripcord isn't allowed to use the microphone"),
+        _ => MicPermission::Granted,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Linux
+// ---------------------------------------------------------------------------
+
+/// Whether ALSA lists a PCM with a playback/capture stream.
+#[cfg(target_os = "linux")]
+fn has_device(capture: bool) -> Option<bool> {
+    let pcms = std::fs::read_to_string("/proc/asound/pcm").ok()?;
+    let stream = if capture { "capture" } else { "playback" };
+    Some(pcms.lines().any(|line| line.contains(stream)))
+}
+
+#[cfg(target_os = "linux")]
+fn mic_permission() -> MicPermission {
+    MicPermission::Ungated
+}
+
+/// The desktop's notification service is running.
+#[cfg(target_os = "linux")]
+fn notification_service_running() -> bool {
+    os_dnd::command_output(
+        "gdbus",
+        &[
+            "call",
+            "--session",
+            "--timeout",
+            "1",
+            "--dest",
+            "org.freedesktop.Notifications",
+            "--object-path",
+            "/org/freedesktop/Notifications",
+            "--method",
+            "org.freedesktop.Notifications.GetServerInformation",
+        ],
+    )
+    .is_some()
+}
+
+#[cfg(target_os = "linux")]
+fn is_gnome() -> bool {
+    std::env::var("XDG_CURRENT_DESKTOP").is_ok_and(|desktop| {
+        desktop
+            .split(':')
+            .any(|name| name.eq_ignore_ascii_case("gnome"))
+    })
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn has_device(_capture: bool) -> Option<bool> {
+    None
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn mic_permission() -> MicPermission {
+    MicPermission::Ungated
+}
+
+// ---------------------------------------------------------------------------
+// Settings pages
+// ---------------------------------------------------------------------------
+
+/// The platform's URL (or GNOME panel) for `page`, if there is one.
+fn settings_target(page: SettingsPage) -> Option<&'static str> {
+    #[cfg(target_os = "windows")]
+    {
+        Some(match page {
+            SettingsPage::Microphone => "ms-settings:privacy-microphone",
+            SettingsPage::Sound => "ms-settings:sound",
+            SettingsPage::Notifications => "ms-settings:notifications",
+        })
+    }
+    #[cfg(target_os = "macos")]
+    {
+        Some(match page {
+            SettingsPage::Microphone => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_Microphone"
+            }
+            SettingsPage::Sound => "x-apple.systempreferences:com.apple.preference.sound",
+            SettingsPage::Notifications => {
+                "x-apple.systempreferences:com.apple.preference.notifications"
+            }
+        })
+    }
+    #[cfg(target_os = "linux")]
+    {
+        match page {
+            SettingsPage::Sound if is_gnome() => Some("sound"),
+            SettingsPage::Notifications if is_gnome() => Some("notifications"),
+            _ => None,
+        }
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        let _ = page;
+        None
+    }
+}
+
+/// A fix that opens `page`, where this platform has one.
+fn open_settings_fix(page: SettingsPage, label: &str) -> Option<FixAction> {
+    settings_target(page)?;
+    Some(FixAction {
+        label: label.to_string(),
+        command: "open_system_settings",
+        args: json!({ "page": page }),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Checks
+// ---------------------------------------------------------------------------
+
+fn check_microphone() -> FirstRunCheck {
+    const ID: &str = "microphone";
+    let present = has_device(true);
+    if present == Some(false) {
+        return FirstRunCheck::new(ID, CheckStatus::Failed, "No microphone was found.").with_fix(
+            open_settings_fix(SettingsPage::Sound, "Open sound settings"),
+        );
+    }
+    match mic_permission() {
+        MicPermission::Denied(why) => FirstRunCheck::new(
+            ID,
+            CheckStatus::Failed,
+            format!("The microphone is blocked: {why}."),
+        )
+        .with_fix(open_settings_fix(
+            SettingsPage::Microphone,
+            "Open privacy settings",
+        )),
+        MicPermission::NotAsked => FirstRunCheck::new(
+            ID,
+            CheckStatus::Warning,
+            "You'll be asked for microphone access when you first join voice.",
+        ),
+        _ if present.is_none() => {
+            FirstRunCheck::new(ID, CheckStatus::Unknown, "Couldn't list audio devices.")
+        }
+        MicPermission::Granted | MicPermission::Ungated => {
+            FirstRunCheck::new(ID, CheckStatus::Passed, "Microphone is ready.")
+        }
+    }
+}
+
+fn check_output() -> FirstRunCheck {
+    const ID: &str = "output";
+    match has_device(false) {
+        Some(true) => FirstRunCheck::new(ID, CheckStatus::Passed, "Speakers or headphones found."),
+        Some(false) => FirstRunCheck::new(
+            ID,
+            CheckStatus::Failed,
+            "No speakers or headphones were found.",
+        )
+        .with_fix(open_settings_fix(
+            SettingsPage::Sound,
+            "Open sound settings",
+        )),
+        None => FirstRunCheck::new(ID, CheckStatus::Unknown, "Couldn't list audio devices."),
+    }
+}
+
+/// Random-enough STUN transaction id. Only needs to match our own reply.
+fn transaction_id() -> [u8; 12] {
+    use std::collections::hash_map::RandomState;
+    use std::hash::BuildHasher;
+
+    let mut id = [0u8; 12];
+    let random = RandomState::new().hash_one(scheduler::now_ms());
+    id[..8].copy_from_slice(&random.to_le_bytes());
+    id[8..].copy_from_slice(&std::process::id().to_le_bytes());
+    id
+}
+
+/// The XOR-MAPPED-ADDRESS in a binding response's attributes.
+fn mapped_address(mut attrs: &[u8], id: &[u8; 12]) -> Option<SocketAddr> {
+    while attrs.len() >= 4 {
+        let kind = u16::from_be_bytes([attrs[0], attrs[1]]);
+        let len = usize::from(u16::from_be_bytes([attrs[2], attrs[3]]));
+        let value = attrs.get(4..4 + len)?;
+        if kind == STUN_XOR_MAPPED_ADDRESS && len >= 8 {
+            let port = u16::from_be_bytes([value[2], value[3]]) ^ (STUN_MAGIC >> 16) as u16;
+            let mut key = [0u8; 16];
+            key[..4].copy_from_slice(&STUN_MAGIC.to_be_bytes());
+            key[4..].copy_from_slice(id);
+            let ip = match value[1] {
+                0x01 => {
+                    let mut octets = [0u8; 4];
+                    for (i, octet) in octets.iter_mut().enumerate() {
+                        *octet = value[4 + i] ^ key[i];
+                    }
+                    IpAddr::from(octets)
+                }
+                0x02 if len >= 20 => {
+                    let mut octets = [0u8; 16];
+                    for (i, octet) in octets.iter_mut().enumerate() {
+                        *octet = value[4 + i] ^ key[i];
+                    }
+                    IpAddr::from(octets)
+                }
+                _ => return None,
+            };
+            return Some(SocketAddr::new(ip, port));
+        }
+        // Attribute values are padded to a multiple of 4 bytes.
+        attrs = attrs.get(4 + len.div_ceil(4) * 4..)?;
+    }
+    None
+}
+
+/// Send a STUN binding request; returns our public address if the reply
+/// includes it.
+fn stun_binding(server: &str) -> Result<Option<SocketAddr>, String> {
+    let target = server
+        .to_socket_addrs()
+        .map_err(|e| format!("couldn't resolve {server}: {e}"))?
+        .next()
+        .ok_or_else(|| format!("couldn't resolve {server}"))?;
+    let local = if target.is_ipv4() {
+        "0.0.0.0:0"
+    } else {
+        "[::]:0"
+    };
+    let socket = UdpSocket::bind(local).map_err(|e| e.to_string())?;
+    socket.connect(target).map_err(|e| e.to_string())?;
+    socket
+        .set_read_timeout(Some(STUN_TIMEOUT))
+        .map_err(|e| e.to_string())?;
+
+    let id = transaction_id();
+    let mut request = [0u8; 20];
+    request[..2].copy_from_slice(&0x0001_u16.to_be_bytes()); // Binding request
+    request[4..8].copy_from_slice(&STUN_MAGIC.to_be_bytes());
+    request[8..].copy_from_slice(&id);
+
+    let mut reply = [0u8; 576];
+    for _ in 0..STUN_ATTEMPTS {
+        socket.send(&request).map_err(|e| e.to_string())?;
+        match socket.recv(&mut reply) {
+            // Binding success response for our transaction.
+            Ok(len) if len >= 20 && reply[..2] == [0x01, 0x01] && reply[8..20] == id => {
+                return Ok(mapped_address(&reply[20..len], &id));
+            }
+            Ok(_) => {}
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) => {}
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+    Err("no reply".to_string())
+}
+
+fn check_udp(server: &str) -> FirstRunCheck {
+    const ID: &str = "udp";
+    audit::record("networkRequest", "run_first_time_checks", server);
+    match stun_binding(server) {
+        Ok(Some(address)) => FirstRunCheck::new(
+            ID,
+            CheckStatus::Passed,
+            format!("Voice can use UDP (public address {}).", address.ip()),
+        ),
+        Ok(None) => FirstRunCheck::new(ID, CheckStatus::Passed, "Voice can use UDP."),
+        Err(e) => FirstRunCheck::new(
+            ID,
+            CheckStatus::Warning,
+            format!(
+                "UDP looks blocked ({e}). Voice will fall back to TCP, which adds delay; \
+                 check your firewall or VPN."
+            ),
+        ),
+    }
+}
+
+fn check_gpu() -> FirstRunCheck {
+    const ID: &str = "gpu";
+    if let Ok(mode) = compat::get_compat_mode() {
+        if mode.software_rendering {
+            let why = match mode.reason.as_deref() {
+                Some("remoteDesktop") => "this is a Remote Desktop session",
+                Some("virtualMachine") => "this looks like a virtual machine",
+                _ => "compatibility mode is on",
+            };
+            let fix = (mode.preference == CompatPreference::On).then(|| FixAction {
+                label: "Turn off compatibility mode".to_string(),
+                command: "set_compat_mode",
+                args: json!({ "preference": CompatPreference::Auto }),
+            });
+            return FirstRunCheck::new(
+                ID,
+                CheckStatus::Warning,
+                format!("Ripcord is drawing without the GPU because {why}."),
+            )
+            .with_fix(fix);
+        }
+    }
+
+    let adapters = gpu::list_adapters();
+    let hardware = adapters
+        .iter()
+        .find(|adapter| adapter.webview && !adapter.software)
+        .or_else(|| adapters.iter().find(|adapter| !adapter.software));
+    match hardware {
+        Some(adapter) => FirstRunCheck::new(
+            ID,
+            CheckStatus::Passed,
+            format!("Hardware acceleration is available ({}).", adapter.name),
+        ),
+        None if adapters.is_empty() => {
+            FirstRunCheck::new(ID, CheckStatus::Unknown, "Couldn't list graphics adapters.")
+        }
+        None => FirstRunCheck::new(
+            ID,
+            CheckStatus::Warning,
+            "No hardware graphics adapter was found; video will be slower.",
+        ),
+    }
+}
+
+fn check_notifications() -> FirstRunCheck {
+    const ID: &str = "notifications";
+    let fix = || open_settings_fix(SettingsPage::Notifications, "Open notification settings");
+
+    #[cfg(target_os = "windows")]
+    if let Some(why) = notifications_blocked() {
+        return FirstRunCheck::new(ID, CheckStatus::Failed, why).with_fix(fix());
+    }
+    #[cfg(target_os = "linux")]
+    if !notification_service_running() {
+        return FirstRunCheck::new(
+            ID,
+            CheckStatus::Failed,
+            "No notification service is running on this desktop.",
+        );
+    }
+
+    let dnd = os_dnd::get_os_dnd_state();
+    if dnd.active {
+        return FirstRunCheck::new(
+            ID,
+            CheckStatus::Warning,
+            "Do Not Disturb is on, so notifications are held until it ends.",
+        )
+        .with_fix(fix());
+    }
+    if cfg!(target_os = "macos") {
+        // Only asked (and only readable) once the first notification is sent.
+        return FirstRunCheck::new(
+            ID,
+            CheckStatus::Unknown,
+            "macOS will ask to allow notifications the first time one arrives.",
+        )
+        .with_fix(fix());
+    }
+    FirstRunCheck::new(ID, CheckStatus::Passed, "Notifications are on.")
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Check mic, speakers, UDP, GPU and notifications for the onboarding
+/// wizard. `stun_server` ("host:port") is used for the UDP check.
+#[tauri::command]
+pub(crate) async fn run_first_time_checks(
+    stun_server: Option<String>,
+) -> Result<Vec<FirstRunCheck>, String> {
+    let server = stun_server.unwrap_or_else(|| DEFAULT_STUN_SERVER.to_string());
+    tauri::async_runtime::spawn_blocking(move || {
+        // The network round trip overlaps the local checks.
+        std::thread::scope(|scope| {
+            let udp = scope.spawn(|| check_udp(&server));
+            let mut checks = vec![check_microphone(), check_output()];
+            checks.push(udp.join().unwrap_or_else(|_| {
+                FirstRunCheck::new("udp", CheckStatus::Unknown, "The UDP check crashed.")
+            }));
+            checks.push(check_gpu());
+            checks.push(check_notifications());
+            checks
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Open a system settings page (a `FixAction` target).
+#[tauri::command]
+pub(crate) fn open_system_settings(page: SettingsPage) -> Result<(), String> {
+    let target = settings_target(page).ok_or("no settings page for this on this system")?;
+
+    #[cfg(target_os = "windows")]
+    {
+        let result = unsafe {
+            win32::ShellExecuteW(
+                0,
+                wide("open").as_ptr(),
+                wide(target).as_ptr(),
+                std::ptr::null(),
+                std::ptr::null(),
+                win32::SW_SHOWNORMAL,
+            )
+        };
+        // Values up to 32 are error codes.
+        if result <= 32 {
+            return Err(format!("couldn't open {target} ({result})"));
+        }
+        Ok(())
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let program = if cfg!(target_os = "macos") {
+            "open"
+        } else {
+            "gnome-control-center"
+        };
+        std::process::Command::new(program)
+            .arg(target)
+            .spawn()
+            .map(|_| ())
+            .map_err(|e| format!("couldn't open {target}: {e}"))
+    }
+}
@@ -0,0 +1,376 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+use tauri_plugin_notification::NotificationExt;
+
+use crate::notifications::{held_count, hold, summarize_channels, DigestItem};
+use crate::{scheduler, store, APP_HANDLE};
+
+// ===========================================================================
+// OS Do Not Disturb
+// ===========================================================================
+//
+// Reads the system's own "don't interrupt me" state so Ripcord's
+// notification engine can defer to it instead of needing its own snooze:
+//
+//   Windows: Focus Assist, from the shell's WNF quiet-hours state (there is
+//            no documented API), plus `SHQueryUserNotificationState` for
+//            presentation mode and fullscreen apps
+//   macOS:   Focus, from the assertions file in `~/Library/DoNotDisturb`.
+//            macOS only lets apps with Full Disk Access read it; without
+//            that the state is reported as unsupported
+//   Linux:   GNOME's `show-banners` setting, else the notification server's
+//            `Inhibited` property (KDE, and others that implement it)
+//
+// The state is re-read every few seconds on the scheduler and
+// `os-dnd-changed` is emitted with the new `OsDndState` when it flips.
+//
+// While DND is on (and the user hasn't turned `respect` off),
+// `queue_digest_notification` holds every message here. When DND ends, one
+// summary toast covers what was held, like the end of a snooze, and
+// `os-dnd-released` carries the items.
+// ===========================================================================
+
+const STORE_DOC: &str = "os-dnd";
+const CHECK_KEY: &str = "os-dnd-check";
+const CHECK_INTERVAL_MS: u64 = 5_000;
+
+/// The OS's DND state, as sent with `os-dnd-changed`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OsDndState {
+    /// Whether the state could be read on this system.
+    pub supported: bool,
+    pub active: bool,
+    /// Which mode, where the OS says: "priorityOnly", "alarmsOnly",
+    /// "presentation", "fullscreen", "quietTime" (Windows), or the Focus
+    /// name (macOS).
+    pub mode: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct DndConfig {
+    /// Hold notifications while OS DND is on.
+    respect: bool,
+    /// Messages held during the current DND period.
+    held: Vec<DigestItem>,
+    /// Messages held past the cap, by channel name (see `notifications`).
+    held_overflow: BTreeMap<String, usize>,
+}
+
+impl Default for DndConfig {
+    fn default() -> Self {
+        Self {
+            respect: true,
+            held: Vec::new(),
+            held_overflow: BTreeMap::new(),
+        }
+    }
+}
+
+static STATE: Mutex<Option<OsDndState>> = Mutex::new(None);
+static CONFIG: Mutex<Option<DndConfig>> = Mutex::new(None);
+
+fn with_config<R>(f: impl FnOnce(&mut DndConfig) -> R) -> R {
+    let mut guard = CONFIG.lock().unwrap();
+    f(guard.get_or_insert_with(|| store::load(STORE_DOC)))
+}
+
+// ---------------------------------------------------------------------------
+// Windows
+// ---------------------------------------------------------------------------
+
+#[cfg(target_os = "windows")]
+mod win32 {
+    use std::ffi::c_void;
+
+    /// `WNF_SHEL_QUIETHOURS_ACTIVE_PROFILE_CHANGED`: 0 off, 1 priority
+    /// only, 2 alarms only.
+    pub const WNF_SHEL_QUIETHOURS_ACTIVE_PROFILE_CHANGED: u64 = 0x0D83_063E_A3BF_1C75;
+
+    pub const QUNS_BUSY: i32 = 2;
+    pub const QUNS_RUNNING_D3D_FULL_SCREEN: i32 = 3;
+    pub const QUNS_PRESENTATION_MODE: i32 = 4;
+    pub const QUNS_QUIET_TIME: i32 = 6;
+
+    #[link(name = "ntdll")]
+    extern "system" {
+        pub fn NtQueryWnfStateData(
+            state_name: *const u64,
+            type_id: *const c_void,
+            explicit_scope: *const c_void,
+            change_stamp: *mut u32,
+            buffer: *mut c_void,
+            buffer_size: *mut u32,
+        ) -> i32;
+    }
+
+    #[link(name = "shell32")]
+    extern "system" {
+        pub fn SHQueryUserNotificationState(state: *mut i32) -> i32;
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn read_state() -> OsDndState {
+    let mut profile: u32 = 0;
+    let mut stamp: u32 = 0;
+    let mut size = std::mem::size_of::<u32>() as u32;
+    let focus_assist = unsafe {
+        win32::NtQueryWnfStateData(
+            &win32::WNF_SHEL_QUIETHOURS_ACTIVE_PROFILE_CHANGED,
+            std::ptr::null(),
+            std::ptr::null(),
+            &mut stamp,
+            (&mut profile as *mut u32).cast(),
+            &mut size,
+        )
+    } >= 0;
+
+    let mut quns: i32 = 0;
+    let shell = unsafe { win32::SHQueryUserNotificationState(&mut quns) } >= 0;
+
+    let mode = match (focus_assist, profile, quns) {
+        (true, 1, _) => Some("priorityOnly"),
+        (true, 2, _) => Some("alarmsOnly"),
+        (_, _, win32::QUNS_PRESENTATION_MODE) => Some("presentation"),
+        (_, _, win32::QUNS_BUSY | win32::QUNS_RUNNING_D3D_FULL_SCREEN) => Some("fullscreen"),
+        // Quiet time is the first hour after a new user's first sign-in.
+        (_, _, win32::QUNS_QUIET_TIME) => Some("quietTime"),
+        _ => None,
+    };
+    OsDndState {
+        supported: focus_assist || shell,
+        active: mode.is_some(),
+        mode: mode.map(str::to_string),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// macOS
+// ---------------------------------------------------------------------------
+
+#[cfg(target_os = "macos")]
+fn read_state() -> OsDndState {
+    use serde_json::Value;
+
+    let Some(home) = std::env::var_os("HOME") else {
+        return OsDndState::default();
+    };
+    let db = std::path::Path::new(&home).join("Library/DoNotDisturb/DB");
+    let read = |name: &str| {
+        std::fs::read(db.join(name))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Value>(&bytes).ok())
+    };
+    // Unreadable without Full Disk Access.
+    let Some(assertions) = read("Assertions.json") else {
+        return OsDndState::default();
+    };
+
+    let mode_id = assertions["data"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .flat_map(|entry| {
+            entry["storeAssertionRecords"]
+                .as_array()
+                .into_iter()
+                .flatten()
+        })
+        .find_map(|record| {
+            record["assertionDetails"]["assertionDetailsModeIdentifier"]
+                .as_str()
+                .map(str::to_string)
+        });
+    let Some(mode_id) = mode_id else {
+        return OsDndState {
+            supported: true,
+            ..OsDndState::default()
+        };
+    };
+
+    let name = read("ModeConfigurations.json").and_then(|configs| {
+        configs["data"]
+            .as_array()?
+            .iter()
+            .find_map(|entry| entry["modeConfigurations"][&mode_id]["mode"]["name"].as_str())
+            .map(str::to_string)
+    });
+    OsDndState {
+        supported: true,
+        active: true,
+        mode: Some(name.unwrap_or(mode_id)),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Linux
+// ---------------------------------------------------------------------------
+
+/// Trimmed stdout of a command that succeeded.
+#[cfg(target_os = "linux")]
+pub(crate) fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn read_state() -> OsDndState {
+    let gnome = std::env::var("XDG_CURRENT_DESKTOP").is_ok_and(|desktop| {
+        desktop
+            .split(':')
+            .any(|name| name.eq_ignore_ascii_case("gnome"))
+    });
+    if gnome {
+        if let Some(banners) = command_output(
+            "gsettings",
+            &["get", "org.gnome.desktop.notifications", "show-banners"],
+        ) {
+            return OsDndState {
+                supported: true,
+                active: banners == "false",
+                mode: None,
+            };
+        }
+    }
+
+    // Replies "(<true>,)" / "(<false>,)".
+    match command_output(
+        "gdbus",
+        &[
+            "call",
+            "--session",
+            "--timeout",
+            "1",
+            "--dest",
+            "org.freedesktop.Notifications",
+            "--object-path",
+            "/org/freedesktop/Notifications",
+            "--method",
+            "org.freedesktop.DBus.Properties.Get",
+            "org.freedesktop.Notifications",
+            "Inhibited",
+        ],
+    ) {
+        Some(reply) => OsDndState {
+            supported: true,
+            active: reply.contains("true"),
+            mode: None,
+        },
+        None => OsDndState::default(),
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn read_state() -> OsDndState {
+    OsDndState::default()
+}
+
+// ---------------------------------------------------------------------------
+// Deferral
+// ---------------------------------------------------------------------------
+
+/// Hold `item` if OS DND is on and respected. Returns whether it was held.
+pub(crate) fn defer(item: &DigestItem) -> Result<bool, String> {
+    let active = STATE
+        .lock()
+        .unwrap()
+        .as_ref()
+        .is_some_and(|state| state.active);
+    if !active {
+        return Ok(false);
+    }
+    with_config(|config| {
+        if !config.respect {
+            return Ok(false);
+        }
+        hold(&mut config.held, &mut config.held_overflow, item.clone());
+        store::save(STORE_DOC, config).map(|_| true)
+    })
+}
+
+/// DND ended: summarize what was held.
+fn release() {
+    let (held, overflow) = with_config(|config| {
+        let held = std::mem::take(&mut config.held);
+        let overflow = std::mem::take(&mut config.held_overflow);
+        if !held.is_empty() {
+            let _ = store::save(STORE_DOC, config);
+        }
+        (held, overflow)
+    });
+    if held.is_empty() {
+        return;
+    }
+    if let Some(handle) = APP_HANDLE.get() {
+        let _ = handle
+            .notification()
+            .builder()
+            .title("While Do Not Disturb was on")
+            .body(format!(
+                "{} messages: {}",
+                held_count(&held, &overflow),
+                summarize_channels(&held, &overflow)
+            ))
+            .show();
+        let _ = handle.emit("os-dnd-released", &held);
+    }
+}
+
+/// Re-read the OS state, announce a change, and re-arm.
+fn check(_key: &str) {
+    let state = read_state();
+    let previous = STATE.lock().unwrap().replace(state.clone());
+    if previous.as_ref() != Some(&state) {
+        if let Some(handle) = APP_HANDLE.get() {
+            let _ = handle.emit("os-dnd-changed", &state);
+        }
+        // Also covers messages still held from a DND period that ended
+        // while the app was closed.
+        if !state.active {
+            release();
+        }
+    }
+    scheduler::schedule(CHECK_KEY, scheduler::now_ms() + CHECK_INTERVAL_MS, check);
+}
+
+/// Start watching the OS state. The first read runs on the scheduler
+/// thread, off the startup path.
+pub(crate) fn init() {
+    scheduler::schedule(CHECK_KEY, scheduler::now_ms(), check);
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// The OS's current Do Not Disturb / Focus state.
+#[tauri::command]
+pub(crate) fn get_os_dnd_state() -> OsDndState {
+    STATE.lock().unwrap().clone().unwrap_or_else(read_state)
+}
+
+/// Whether notifications are held while OS DND is on (default on).
+/// Turning it off releases anything already held.
+#[tauri::command]
+pub(crate) fn set_respect_os_dnd(enabled: bool) -> Result<(), String> {
+    with_config(|config| {
+        config.respect = enabled;
+        store::save(STORE_DOC, config)
+    })?;
+    if !enabled {
+        release();
+    }
+    Ok(())
+}
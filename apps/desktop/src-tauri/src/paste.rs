@@ -0,0 +1,589 @@
+// ===========================================================================
+// Rich Paste
+// ===========================================================================
+//
+// `html_to_markdown(html)` turns the `text/html` flavour of a paste (Google
+// Docs, web pages, IDEs) into the chat's Markdown dialect instead of letting
+// the composer fall back to flattened plain text. The webview's paste
+// handler passes `clipboardData.getData("text/html")` here and inserts the
+// result.
+//
+// Preserved: links, bold/italic/underline/strikethrough, inline code and
+// code blocks (with a `language-*` class as the fence tag), headings 1–3,
+// ordered and unordered lists (nested), block quotes and line breaks.
+// Everything else is reduced to its text. Markdown metacharacters in plain
+// text are escaped so pasted prose can't accidentally format itself.
+// ===========================================================================
+
+/// Tag names whose contents are never text.
+const SKIPPED: &[&str] = &["head", "script", "style", "title", "noscript", "template"];
+
+struct Tag<'a> {
+    name: String,
+    closing: bool,
+    attrs: &'a str,
+}
+
+impl Tag<'_> {
+    /// Value of attribute `key`, if present.
+    fn attr(&self, key: &str) -> Option<String> {
+        let bytes = self.attrs.as_bytes();
+        let mut i = 0;
+        while i < bytes.len() {
+            while i < bytes.len() && (bytes[i].is_ascii_whitespace() || bytes[i] == b'/') {
+                i += 1;
+            }
+            let name_start = i;
+            while i < bytes.len() && !bytes[i].is_ascii_whitespace() && bytes[i] != b'=' {
+                i += 1;
+            }
+            let name = &self.attrs[name_start..i];
+            let mut value = "";
+            if bytes.get(i) == Some(&b'=') {
+                i += 1;
+                match bytes.get(i) {
+                    Some(&q @ (b'"' | b'\'')) => {
+                        let start = i + 1;
+                        let end = self.attrs[start..]
+                            .find(q as char)
+                            .map_or(bytes.len(), |e| start + e);
+                        value = &self.attrs[start..end];
+                        i = end + 1;
+                    }
+                    _ => {
+                        let start = i;
+                        while i < bytes.len() && !bytes[i].is_ascii_whitespace() {
+                            i += 1;
+                        }
+                        value = &self.attrs[start..i];
+                    }
+                }
+            }
+            if name.eq_ignore_ascii_case(key) {
+                return Some(decode_entities(value));
+            }
+            if name.is_empty() {
+                i += 1;
+            }
+        }
+        None
+    }
+}
+
+enum Token<'a> {
+    Tag(Tag<'a>),
+    Text(&'a str),
+}
+
+/// Split HTML into tags and text runs. Comments, doctypes and processing
+/// instructions are dropped; a `<` that doesn't start a tag is text.
+fn tokenize(html: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = html;
+    while !rest.is_empty() {
+        let Some(lt) = rest.find('<') else {
+            tokens.push(Token::Text(rest));
+            break;
+        };
+        if lt > 0 {
+            tokens.push(Token::Text(&rest[..lt]));
+        }
+        rest = &rest[lt..];
+
+        if let Some(after) = rest.strip_prefix("<!--") {
+            rest = after.find("-->").map_or("", |end| &after[end + 3..]);
+            continue;
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+            continue;
+        }
+
+        let (closing, body) = match rest[1..].strip_prefix('/') {
+            Some(body) => (true, body),
+            None => (false, &rest[1..]),
+        };
+        if !body.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            tokens.push(Token::Text("<"));
+            rest = &rest[1..];
+            continue;
+        }
+        let Some(end) = tag_end(body) else {
+            tokens.push(Token::Text(rest));
+            break;
+        };
+        let inner = &body[..end];
+        let name_len = inner
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(inner.len());
+        tokens.push(Token::Tag(Tag {
+            name: inner[..name_len].to_ascii_lowercase(),
+            closing,
+            attrs: &inner[name_len..],
+        }));
+        rest = &body[end + 1..];
+    }
+    tokens
+}
+
+/// Index of the `>` closing a tag, skipping quoted attribute values.
+fn tag_end(body: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in body.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 10)
+            .and_then(|end| {
+                let entity = &rest[1..=end];
+                let c = match entity {
+                    "amp" => Some('&'),
+                    "lt" => Some('<'),
+                    "gt" => Some('>'),
+                    "quot" => Some('"'),
+                    "apos" => Some('\''),
+                    "nbsp" => Some(' '),
+                    _ => entity
+                        .strip_prefix("#x")
+                        .or_else(|| entity.strip_prefix("#X"))
+                        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                        .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                        .and_then(char::from_u32),
+                };
+                c.map(|c| (c, end + 2))
+            });
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[derive(Default)]
+struct Converter {
+    out: String,
+    line_start: bool,
+    space_pending: bool,
+    quote_depth: usize,
+    /// `None` for bullet lists, `Some(next number)` for ordered lists.
+    lists: Vec<Option<u32>>,
+    skip_depth: usize,
+    in_pre: bool,
+    /// Opening fence not yet written (waiting for a `<code class>`).
+    fence_pending: bool,
+    /// Open inline formatting: (tag, marker, output length after opening).
+    inline: Vec<(String, &'static str, usize)>,
+    /// Open links: (href, output length after `[`).
+    links: Vec<(Option<String>, usize)>,
+}
+
+impl Converter {
+    /// Append markup, inserting the quote prefix at the start of each line.
+    fn push_raw(&mut self, s: &str) {
+        for c in s.chars() {
+            if self.line_start && c != '\n' {
+                for _ in 0..self.quote_depth {
+                    self.out.push_str("> ");
+                }
+                self.line_start = false;
+            }
+            self.out.push(c);
+            if c == '\n' {
+                self.line_start = true;
+            }
+        }
+    }
+
+    fn flush_space(&mut self) {
+        if self.space_pending && !self.line_start {
+            self.out.push(' ');
+        }
+        self.space_pending = false;
+    }
+
+    fn ensure_newline(&mut self) {
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.push_raw("\n");
+        }
+        self.space_pending = false;
+    }
+
+    /// Paragraph break. Inside quotes and lists a single newline is used so
+    /// the construct isn't terminated.
+    fn blank_line(&mut self) {
+        self.ensure_newline();
+        if self.quote_depth == 0
+            && self.lists.is_empty()
+            && !self.out.is_empty()
+            && !self.out.ends_with("\n\n")
+        {
+            self.push_raw("\n");
+        }
+    }
+
+    fn text(&mut self, raw: &str) {
+        if self.skip_depth > 0 {
+            return;
+        }
+        let text = decode_entities(raw);
+        if self.in_pre {
+            if self.fence_pending {
+                self.open_fence("");
+            }
+            self.push_raw(&text);
+            return;
+        }
+        let in_code = self.inline.iter().any(|(_, m, _)| *m == "`");
+        for c in text.chars() {
+            if c.is_whitespace() {
+                self.space_pending = true;
+                continue;
+            }
+            self.flush_space();
+            let at_line_start = self.line_start;
+            let mut buf = [0; 4];
+            if !in_code
+                && (matches!(c, '\\' | '*' | '_' | '~' | '`' | '|')
+                    || (at_line_start && matches!(c, '#' | '>' | '-')))
+            {
+                self.push_raw("\\");
+            }
+            self.push_raw(c.encode_utf8(&mut buf));
+        }
+    }
+
+    fn open_fence(&mut self, lang: &str) {
+        self.fence_pending = false;
+        self.push_raw("```");
+        self.push_raw(lang);
+        self.push_raw("\n");
+    }
+
+    fn open_inline(&mut self, tag: &str, marker: &'static str) {
+        self.flush_space();
+        self.push_raw(marker);
+        self.inline.push((tag.to_string(), marker, self.out.len()));
+    }
+
+    fn close_inline(&mut self, tag: &str) {
+        let Some(i) = self.inline.iter().rposition(|(t, _, _)| t == tag) else {
+            return;
+        };
+        let (_, marker, opened_at) = self.inline.remove(i);
+        if self.out.len() == opened_at {
+            // Nothing inside — drop the opening marker instead.
+            self.out.truncate(opened_at - marker.len());
+        } else {
+            self.push_raw(marker);
+        }
+    }
+
+    fn tag(&mut self, tag: &Tag) {
+        let name = tag.name.as_str();
+        if SKIPPED.contains(&name) {
+            if tag.closing {
+                self.skip_depth = self.skip_depth.saturating_sub(1);
+            } else {
+                self.skip_depth += 1;
+            }
+            return;
+        }
+        if self.skip_depth > 0 {
+            return;
+        }
+        if self.in_pre && !matches!(name, "pre" | "code" | "br") {
+            return;
+        }
+
+        match (name, tag.closing) {
+            ("br", _) => {
+                self.push_raw("\n");
+                self.space_pending = false;
+            }
+            ("p" | "h4" | "h5" | "h6" | "table", _) => self.blank_line(),
+            ("div" | "section" | "article" | "header" | "footer" | "tr" | "dd" | "dt", _) => {
+                self.ensure_newline()
+            }
+            ("h1" | "h2" | "h3", false) => {
+                self.blank_line();
+                self.push_raw(&"#".repeat(usize::from(name.as_bytes()[1] - b'0')));
+                self.push_raw(" ");
+            }
+            ("h1" | "h2" | "h3", true) => self.blank_line(),
+            ("hr", _) => self.blank_line(),
+            ("td" | "th", false) => self.space_pending = true,
+
+            ("b" | "strong", false) => {
+                // Google Docs wraps whole documents in <b style="font-weight:normal">.
+                let normal = tag
+                    .attr("style")
+                    .is_some_and(|s| s.replace(' ', "").contains("font-weight:normal"));
+                self.open_inline(name, if normal { "" } else { "**" });
+            }
+            ("i" | "em", false) => self.open_inline(name, "*"),
+            ("u" | "ins", false) => self.open_inline(name, "__"),
+            ("s" | "del" | "strike", false) => self.open_inline(name, "~~"),
+            ("b" | "strong" | "i" | "em" | "u" | "ins" | "s" | "del" | "strike", true) => {
+                self.close_inline(name)
+            }
+
+            ("code", false) if self.in_pre && self.fence_pending => {
+                let lang = tag
+                    .attr("class")
+                    .and_then(|c| {
+                        c.split_whitespace()
+                            .find_map(|c| {
+                                c.strip_prefix("language-")
+                                    .or_else(|| c.strip_prefix("lang-"))
+                            })
+                            .map(str::to_string)
+                    })
+                    .unwrap_or_default();
+                self.open_fence(&lang);
+            }
+            ("code", _) if self.in_pre => {}
+            ("code" | "kbd" | "samp", false) => self.open_inline(name, "`"),
+            ("code" | "kbd" | "samp", true) => self.close_inline(name),
+
+            ("pre", false) => {
+                self.blank_line();
+                self.in_pre = true;
+                self.fence_pending = true;
+            }
+            ("pre", true) => {
+                self.in_pre = false;
+                if !self.fence_pending {
+                    self.ensure_newline();
+                    self.push_raw("```");
+                }
+                self.fence_pending = false;
+                self.blank_line();
+            }
+
+            ("a", false) => {
+                let href = tag.attr("href").filter(|h| {
+                    h.starts_with("http://")
+                        || h.starts_with("https://")
+                        || h.starts_with("mailto:")
+                });
+                self.flush_space();
+                if href.is_some() {
+                    self.push_raw("[");
+                }
+                self.links.push((href, self.out.len()));
+            }
+            ("a", true) => {
+                if let Some((Some(href), start)) = self.links.pop() {
+                    let label = self.out[start..].trim().to_string();
+                    if label.is_empty() || label.replace('\\', "") == href {
+                        // Bare URL: let autolinking handle it.
+                        self.out.truncate(start - 1);
+                        self.push_raw(&href);
+                    } else {
+                        self.push_raw("](");
+                        self.push_raw(&href);
+                        self.push_raw(")");
+                    }
+                }
+            }
+
+            ("img", false) => {
+                if let Some(alt) = tag.attr("alt").filter(|a| !a.trim().is_empty()) {
+                    self.text(&alt);
+                }
+            }
+
+            ("blockquote", false) => {
+                self.blank_line();
+                self.quote_depth += 1;
+            }
+            ("blockquote", true) => {
+                self.ensure_newline();
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+                self.blank_line();
+            }
+
+            ("ul" | "ol", false) => {
+                if self.lists.is_empty() {
+                    self.blank_line();
+                } else {
+                    self.ensure_newline();
+                }
+                let start = tag.attr("start").and_then(|s| s.parse().ok()).unwrap_or(1);
+                self.lists.push((name == "ol").then_some(start));
+            }
+            ("ul" | "ol", true) => {
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.blank_line();
+                }
+            }
+            ("li", false) => {
+                self.ensure_newline();
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                let marker = match self.lists.last_mut() {
+                    Some(Some(n)) => {
+                        *n += 1;
+                        format!("{}. ", *n - 1)
+                    }
+                    _ => "- ".to_string(),
+                };
+                self.push_raw(&indent);
+                self.push_raw(&marker);
+            }
+            _ => {}
+        }
+    }
+
+    fn finish(self) -> String {
+        let mut out = String::with_capacity(self.out.len());
+        let mut blank_run = 0;
+        for line in self.out.lines() {
+            let line = line.trim_end();
+            if line.is_empty() {
+                blank_run += 1;
+                if blank_run > 1 {
+                    continue;
+                }
+            } else {
+                blank_run = 0;
+            }
+            out.push_str(line);
+            out.push('\n');
+        }
+        out.trim().to_string()
+    }
+}
+
+/// Convert an HTML fragment to chat Markdown.
+pub(crate) fn convert(html: &str) -> String {
+    let mut converter = Converter {
+        line_start: true,
+        ..Converter::default()
+    };
+    for token in tokenize(html) {
+        match token {
+            Token::Text(text) => converter.text(text),
+            Token::Tag(tag) => converter.tag(&tag),
+        }
+    }
+    converter.finish()
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Convert pasted HTML into Markdown for the composer.
+#[tauri::command]
+pub(crate) fn html_to_markdown(html: String) -> String {
+    convert(&html)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inline_formatting_and_links() {
+        assert_eq!(
+            convert("<p>Hello <b>bold</b>, <em>it</em>, <u>u</u> and <s>gone</s></p>"),
+            "Hello **bold**, *it*, __u__ and ~~gone~~"
+        );
+        assert_eq!(
+            convert(r#"see <a href="https://example.com/a?b=1&amp;c=2">the docs</a>"#),
+            "see [the docs](https://example.com/a?b=1&c=2)"
+        );
+        // Bare URLs and unsafe schemes are left to autolinking / dropped.
+        assert_eq!(
+            convert(r#"<a href="https://example.com">https://example.com</a>"#),
+            "https://example.com"
+        );
+        assert_eq!(
+            convert(r#"<a href="javascript:alert(1)">click</a>"#),
+            "click"
+        );
+        assert_eq!(convert("<b></b>x<i> </i>"), "x");
+    }
+
+    #[test]
+    fn google_docs_wrapper_is_not_bold() {
+        assert_eq!(
+            convert(r#"<b style="font-weight: normal;" id="docs-internal"><span>plain</span></b>"#),
+            "plain"
+        );
+    }
+
+    #[test]
+    fn blocks_headings_and_code() {
+        assert_eq!(
+            convert("<h1>Title</h1><p>one</p><p>two<br>three</p><h3>Sub</h3>"),
+            "# Title\n\none\n\ntwo\nthree\n\n### Sub"
+        );
+        assert_eq!(
+            convert(
+                r#"<pre><code class="hljs language-rust">fn main() {
+    let x = a * b;
+}</code></pre>"#
+            ),
+            "```rust\nfn main() {\n    let x = a * b;\n}\n```"
+        );
+        assert_eq!(convert("run <code>a_b*c</code> now"), "run `a_b*c` now");
+    }
+
+    #[test]
+    fn nested_lists_and_quotes() {
+        assert_eq!(
+            convert("<ul><li>a<ul><li>b</li></ul></li><li>c</li></ul>"),
+            "- a\n  - b\n- c"
+        );
+        assert_eq!(
+            convert(r#"<ol start="3"><li>x</li><li>y</li></ol>"#),
+            "3. x\n4. y"
+        );
+        assert_eq!(
+            convert("<blockquote><p>quoted</p><p>more</p></blockquote><p>after</p>"),
+            "> quoted\n> more\n\nafter"
+        );
+    }
+
+    #[test]
+    fn escapes_and_skips() {
+        assert_eq!(
+            convert("<p># not a heading</p><p>2 * 3 = 6_ish | ~x~</p>"),
+            "\\# not a heading\n\n2 \\* 3 = 6\\_ish \\| \\~x\\~"
+        );
+        assert_eq!(
+            convert("<head><title>t</title><style>p{}</style></head><!-- c --><p>a &lt;b&gt; &#x41;&#66;&bogus;</p><script>x</script>"),
+            "a <b> AB&bogus;"
+        );
+        assert_eq!(convert("1 < 2 and <3"), "1 < 2 and <3");
+        assert_eq!(convert(r#"<img alt="smile"> <img src="x">"#), "smile");
+    }
+}
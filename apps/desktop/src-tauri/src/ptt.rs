@@ -0,0 +1,1741 @@
+use std::sync::{
+    atomic::{AtomicBool, AtomicI32, AtomicU32, AtomicU64, AtomicU8, Ordering},
+    mpsc, Condvar, Mutex, Once, OnceLock,
+};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::Emitter;
+
+use crate::{audit, scheduler, APP_HANDLE};
+
+#[cfg(target_os = "linux")]
+mod evdev;
+#[cfg(target_os = "macos")]
+mod event_tap;
+#[cfg(target_os = "windows")]
+mod foreground;
+#[cfg(target_os = "windows")]
+mod gamepad;
+mod keybinds;
+mod keybinds_file;
+mod latency;
+#[cfg(target_os = "windows")]
+mod raw_input;
+
+// ===========================================================================
+// PTT Low-Level Keyboard Hook (Windows)
+// ===========================================================================
+//
+// Uses `SetWindowsHookEx(WH_KEYBOARD_LL)` to capture key press and release
+// events system-wide, even when the Ripcord window is backgrounded. This is
+// the same mechanism Discord uses for push-to-talk.
+//
+// Architecture:
+//   1. `start_ptt_hook(keyCode)` spawns a dedicated thread that installs the
+//      hook and runs a `GetMessage` pump (required by Windows for LL hooks).
+//   2. The hook callback checks every keystroke against the configured PTT
+//      virtual-key code. On match it emits Tauri events (`ptt-hook-down` /
+//      `ptt-hook-up`) to the frontend via the stored `AppHandle`.
+//   3. `stop_ptt_hook()` posts `WM_QUIT` to the hook thread, which tears
+//      down the hook and exits.
+//
+// Key properties:
+//   - Event-driven (zero latency vs. the polling approach)
+//   - Does not consume the key (other apps still receive it) unless
+//     `suppress` is set, in which case the bound key is swallowed while the
+//     chord is held — Discord's "suppress keybind". Windows only: the macOS
+//     tap is listen-only, and evdev could only suppress by grabbing the
+//     whole device. Not applied with a device filter, since raw input
+//     cannot block keys
+//   - Handles both WM_KEYDOWN and WM_KEYUP (unlike RegisterHotKey)
+//   - Suppresses key-repeat via an AtomicBool guard
+//   - Hold or toggle (latch) activation, resolved natively so toggle mode
+//     does not depend on the webview receiving both events. Toggle presses
+//     closer together than the debounce window are ignored, so a bouncing
+//     switch or a double-fired key doesn't flip transmit straight back
+//   - Release delay: in hold mode `ptt-hook-up` can trail the key-up by
+//     `release_delay_ms`, timed natively so a busy webview can't clip the
+//     end of a sentence. Re-pressing within the delay keeps transmit on
+//     without emitting anything
+//   - Optional per-keyboard binding: with a `device_filter`, PTT follows
+//     only the named device — via Raw Input on Windows (see `raw_input`),
+//     by reading only that device's event node on Linux (see `evdev`)
+//   - Hot-mic safety timeout: a key reported down for longer than the
+//     configured limit is force-released (`ptt-safety-release`), covering
+//     keyboards/KVMs that drop the key-up message
+//   - Chords: `modifiers` (e.g. `["ctrl", "shift"]`) must be held with the
+//     key. Down fires once the whole chord is held, in any order; up fires
+//     as soon as any part of it is released
+//   - Foreground restriction: with `foreground_process` (e.g. "game.exe"),
+//     a press only starts transmit while that program has focus, so typing
+//     the key in a browser or chat window doesn't open the mic. Presses
+//     that stop or extend transmit always go through (see `foreground`).
+//     Windows only
+//   - Scan-code bindings: given the `scan_code` from key capture, PTT and
+//     keybinds match the physical key rather than the virtual key, which
+//     moves with the layout (Q on QWERTY is A on AZERTY). Windows scan
+//     codes carry the 0xE0 prefix for extended keys (0xE01D = Right Ctrl)
+//   - Other global keybinds (toggle mute, push-to-mute/deafen, priority
+//     speaker, …) share the hook thread via the registry in `keybinds`; the
+//     thread stays up while PTT or any keybind is bound. They can also be
+//     kept in a `keybinds.json` that is applied live as it changes (see
+//     `keybinds_file`)
+//   - Media keys: play/pause, next/previous, stop and the volume keys bind
+//     like any other key (`VK_MEDIA_*` / `VK_VOLUME_*`), on Windows and
+//     Linux; a keybind's `consume` keeps them from also reaching the media
+//     player and OS overlay (Windows). Linux also reports the laptop
+//     mic-mute key (`KEY_MICMUTE`), which has no Windows virtual key, as
+//     0xE8; on Windows that key is handled by the vendor driver and never
+//     reaches the hook. The macOS tap doesn't see media keys — they arrive
+//     as system-defined events, not key events
+//   - Key capture: `capture_next_key()` resolves with the next key the hook
+//     sees, so settings bind the exact codes the hook will match
+//   - Event timing: `ptt-hook-down` / `ptt-hook-up` carry the OS event time,
+//     a monotonic receipt time and the hook-to-emit latency, and
+//     `get_ptt_latency_stats()` summarizes recent delays (see `latency`)
+//   - Errors: `start_ptt_hook` fails with a `PttError` whose `code` says
+//     why (hook refused, missing permission, no input devices, …). A hook
+//     that dies after starting — its thread panicked, or the watchdog
+//     couldn't reinstall it — emits `ptt-hook-error` with the same shape
+//   - Hook watchdog: Windows silently removes an LL hook whose callback
+//     overruns `LowLevelHooksTimeout`. Once a second the hook thread compares
+//     the bound key's `GetAsyncKeyState` with what the hook last saw; if they
+//     disagree twice running, it reinstalls the hooks, resyncs the key and
+//     emits `ptt-hook-recovered`. (The macOS tap emits the same event when it
+//     has to re-enable itself after a timeout.)
+//   - Mouse buttons: binding `VK_MBUTTON`/`VK_XBUTTON1`/`VK_XBUTTON2`
+//     installs a `WH_MOUSE_LL` hook on the same thread. It is only present
+//     while a mouse button is bound, so keyboard PTT doesn't pay for a hook
+//     on every mouse move
+//   - Gamepad buttons: `VK_GAMEPAD_*` codes are served by an XInput poll
+//     thread (see `gamepad`); on Linux controllers come through evdev
+//
+// On macOS the same command surface is backed by a listen-only Quartz event
+// tap (see `event_tap`), which needs the user's Input Monitoring grant. On
+// Linux it reads `/dev/input` directly (see `evdev`), which works under X11
+// and Wayland but needs membership of the `input` group.
+// ===========================================================================
+
+/// Virtual-key code of the current PTT key. 0 = disabled.
+static PTT_VK: AtomicI32 = AtomicI32::new(0);
+
+/// Scan code PTT matches instead of `PTT_VK`, so the binding stays on the
+/// same physical key across keyboard layouts. 0 = match the virtual key.
+static PTT_SCAN: AtomicU32 = AtomicU32::new(0);
+
+/// Modifiers that must be held with `PTT_VK` (`MOD_*` group bits). 0 = the
+/// key alone.
+static PTT_MODIFIERS: AtomicU8 = AtomicU8::new(0);
+
+/// Modifier keys currently down, one bit per side (`MOD_*`).
+static HELD_MODIFIERS: AtomicU8 = AtomicU8::new(0);
+
+/// Whether the main PTT key itself is physically down, regardless of
+/// modifiers.
+static MAIN_KEY_HELD: AtomicBool = AtomicBool::new(false);
+
+/// Set when the safety watchdog force-released a key the OS still reports
+/// down; cleared by the next real release. The hook watchdog stands down
+/// meanwhile, or it would read the stuck key and transmit again.
+static SAFETY_LATCHED: AtomicBool = AtomicBool::new(false);
+
+/// Left/right bits for each modifier. A required modifier is satisfied by
+/// either side.
+const MOD_CTRL: u8 = 0b0000_0011;
+const MOD_SHIFT: u8 = 0b0000_1100;
+const MOD_ALT: u8 = 0b0011_0000;
+const MOD_META: u8 = 0b1100_0000;
+
+/// Whether the full chord is currently held (prevents duplicate "down"
+/// events from key-repeat messages).
+static PTT_PRESSED: AtomicBool = AtomicBool::new(false);
+
+/// Activation mode: `MODE_HOLD` or `MODE_TOGGLE`.
+static PTT_MODE: AtomicU8 = AtomicU8::new(MODE_HOLD);
+
+/// Whether transmit is currently on, as last reported to the frontend.
+/// Mirrors `PTT_PRESSED` in hold mode; latched across presses in toggle mode.
+static PTT_TRANSMITTING: AtomicBool = AtomicBool::new(false);
+
+const MODE_HOLD: u8 = 0;
+const MODE_TOGGLE: u8 = 1;
+
+/// Minimum time between two toggle-mode flips, in ms. 0 = no debounce.
+static PTT_TOGGLE_DEBOUNCE_MS: AtomicU64 = AtomicU64::new(DEFAULT_TOGGLE_DEBOUNCE_MS);
+
+/// When transmit was last flipped in toggle mode, in milliseconds since
+/// `EPOCH`. `u64::MAX` = never.
+static LAST_TOGGLE_AT_MS: AtomicU64 = AtomicU64::new(u64::MAX);
+
+const DEFAULT_TOGGLE_DEBOUNCE_MS: u64 = 50;
+const MAX_TOGGLE_DEBOUNCE_MS: u64 = 1000;
+
+/// Maximum time the key may be held before it is force-released, in
+/// seconds. 0 = disabled.
+static PTT_SAFETY_TIMEOUT_SECS: AtomicU64 = AtomicU64::new(DEFAULT_SAFETY_TIMEOUT_SECS);
+
+/// When the current press started, in milliseconds since `EPOCH`.
+static PTT_PRESSED_AT_MS: AtomicU64 = AtomicU64::new(0);
+
+/// Reference point for `PTT_PRESSED_AT_MS` (monotonic clock).
+static EPOCH: OnceLock<Instant> = OnceLock::new();
+
+/// Guards the one-time spawn of the safety watchdog thread.
+static SAFETY_WATCHDOG: Once = Once::new();
+
+const DEFAULT_SAFETY_TIMEOUT_SECS: u64 = 10 * 60;
+const SAFETY_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Delay between releasing the key and `ptt-hook-up` in hold mode, in ms.
+/// 0 = release immediately.
+static PTT_RELEASE_DELAY_MS: AtomicU64 = AtomicU64::new(0);
+
+/// When the pending delayed release is due, if there is one.
+static PENDING_RELEASE: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Wakes the release timer when `PENDING_RELEASE` changes.
+static RELEASE_WAKE: Condvar = Condvar::new();
+
+/// Guards the one-time spawn of the release timer thread.
+static RELEASE_TIMER: Once = Once::new();
+
+const MAX_RELEASE_DELAY_MS: u64 = 2000;
+
+/// Device path PTT is restricted to (`None` = any keyboard).
+static PTT_DEVICE_FILTER: Mutex<Option<String>> = Mutex::new(None);
+
+/// Fast-path mirror of `PTT_DEVICE_FILTER.is_some()` for the hook callback,
+/// which must not take a lock on every keystroke.
+static PTT_DEVICE_FILTER_SET: AtomicBool = AtomicBool::new(false);
+
+/// Executable (file name or full path) that must be in the foreground for
+/// a press to start transmit (`None` = any).
+static PTT_FOREGROUND: Mutex<Option<String>> = Mutex::new(None);
+
+/// Fast-path mirror of `PTT_FOREGROUND.is_some()` for the hook callback.
+static PTT_FOREGROUND_SET: AtomicBool = AtomicBool::new(false);
+
+/// Whether matching PTT key events are swallowed instead of passed on.
+static PTT_SUPPRESS: AtomicBool = AtomicBool::new(false);
+
+/// Whether the last main-key press was swallowed, so its release is too.
+#[cfg(target_os = "windows")]
+static SUPPRESSED_DOWN: AtomicBool = AtomicBool::new(false);
+
+/// Whether the hook thread's raw-input sink is registered. Device filtering
+/// is only honoured when it is; otherwise the LL hook keeps handling PTT.
+#[cfg(target_os = "windows")]
+static RAW_INPUT_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Receiver side of an in-progress `capture_next_key`, tagged with its id
+/// so a finished capture doesn't clear a newer one. The hook sends only
+/// (virtual key, scan code, `MOD_*` bits); `capture_next_key` names it.
+static CAPTURE: Mutex<Option<(u64, mpsc::Sender<(i32, u32, u8)>)>> = Mutex::new(None);
+
+/// Fast-path mirror of `CAPTURE.is_some()` for the hook callbacks.
+static CAPTURING: AtomicBool = AtomicBool::new(false);
+
+/// Source of capture ids.
+static CAPTURE_SEQ: AtomicU64 = AtomicU64::new(0);
+
+const DEFAULT_CAPTURE_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Whether the hook thread is running.
+static HOOK_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Thread ID of the hook thread (needed to post WM_QUIT for clean shutdown).
+static HOOK_THREAD_ID: AtomicU32 = AtomicU32::new(0);
+
+/// How often the hook thread cross-checks the hook against the OS key state.
+#[cfg(target_os = "windows")]
+const WATCHDOG_INTERVAL_MS: u32 = 1000;
+
+/// Consecutive disagreeing checks before the hook is presumed dead.
+#[cfg(target_os = "windows")]
+const WATCHDOG_STRIKES: u32 = 2;
+
+// ---------------------------------------------------------------------------
+// Win32 FFI (Windows only)
+// ---------------------------------------------------------------------------
+
+#[cfg(target_os = "windows")]
+mod win32 {
+    pub const WH_KEYBOARD_LL: i32 = 13;
+    pub const WH_MOUSE_LL: i32 = 14;
+    pub const WM_KEYDOWN: usize = 0x0100;
+    pub const WM_KEYUP: usize = 0x0101;
+    pub const WM_SYSKEYDOWN: usize = 0x0104;
+    pub const WM_SYSKEYUP: usize = 0x0105;
+    pub const LLKHF_EXTENDED: u32 = 0x01;
+    pub const WM_QUIT: u32 = 0x0012;
+    pub const WM_TIMER: u32 = 0x0113;
+    pub const WM_MBUTTONDOWN: usize = 0x0207;
+    pub const WM_MBUTTONUP: usize = 0x0208;
+    pub const WM_XBUTTONDOWN: usize = 0x020B;
+    pub const WM_XBUTTONUP: usize = 0x020C;
+    /// Private thread message: re-evaluate which hooks are needed.
+    pub const WM_APP_SYNC_HOOKS: u32 = 0x8000 + 1;
+
+    pub const VK_MBUTTON: u32 = 0x04;
+    pub const VK_XBUTTON1: u32 = 0x05;
+    pub const VK_XBUTTON2: u32 = 0x06;
+
+    pub const MOD_ALT: u32 = 0x0001;
+    pub const MOD_CONTROL: u32 = 0x0002;
+    pub const MOD_SHIFT: u32 = 0x0004;
+    pub const MOD_WIN: u32 = 0x0008;
+    pub const MOD_NOREPEAT: u32 = 0x4000;
+    pub const ERROR_HOTKEY_ALREADY_REGISTERED: u32 = 1409;
+
+    #[repr(C)]
+    pub struct KBDLLHOOKSTRUCT {
+        pub vk_code: u32,
+        pub scan_code: u32,
+        pub flags: u32,
+        pub time: u32,
+        pub extra_info: usize,
+    }
+
+    #[repr(C)]
+    pub struct MSLLHOOKSTRUCT {
+        pub pt_x: i32,
+        pub pt_y: i32,
+        /// For X buttons, the high word is 1 (XBUTTON1) or 2 (XBUTTON2).
+        pub mouse_data: u32,
+        pub flags: u32,
+        pub time: u32,
+        pub extra_info: usize,
+    }
+
+    #[repr(C)]
+    pub struct MSG {
+        pub hwnd: isize,
+        pub message: u32,
+        pub w_param: usize,
+        pub l_param: isize,
+        pub time: u32,
+        pub pt_x: i32,
+        pub pt_y: i32,
+    }
+
+    extern "system" {
+        pub fn SetWindowsHookExW(
+            id_hook: i32,
+            lpfn: unsafe extern "system" fn(i32, usize, isize) -> isize,
+            hmod: isize,
+            dw_thread_id: u32,
+        ) -> isize;
+        pub fn UnhookWindowsHookEx(hhk: isize) -> i32;
+        pub fn CallNextHookEx(
+            hhk: isize,
+            n_code: i32,
+            w_param: usize,
+            l_param: isize,
+        ) -> isize;
+        pub fn GetLastError() -> u32;
+        pub fn RegisterHotKey(hwnd: isize, id: i32, modifiers: u32, vk: u32) -> i32;
+        pub fn UnregisterHotKey(hwnd: isize, id: i32) -> i32;
+        pub fn GetMessageW(
+            msg: *mut MSG,
+            hwnd: isize,
+            w_msg_filter_min: u32,
+            w_msg_filter_max: u32,
+        ) -> i32;
+        pub fn PostThreadMessageW(
+            id_thread: u32,
+            msg: u32,
+            w_param: usize,
+            l_param: isize,
+        ) -> i32;
+        pub fn DispatchMessageW(msg: *const MSG) -> isize;
+        pub fn GetCurrentThreadId() -> u32;
+        pub fn GetTickCount() -> u32;
+        pub fn GetAsyncKeyState(v_key: i32) -> i16;
+        pub fn SetTimer(hwnd: isize, id_event: usize, elapse: u32, timer_func: isize) -> usize;
+        pub fn KillTimer(hwnd: isize, id_event: usize) -> i32;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Activation state machine
+// ---------------------------------------------------------------------------
+
+/// Physical transition of the bound key, as seen by a backend.
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+fn on_main_key(down: bool) {
+    on_main_key_at(down, None);
+}
+
+/// `on_main_key` for backends that know when the OS stamped the event.
+#[cfg_attr(
+    not(any(target_os = "windows", target_os = "macos", target_os = "linux")),
+    allow(dead_code)
+)]
+fn on_main_key_at(down: bool, os: Option<latency::OsEventTime>) {
+    latency::mark(os);
+    if !down {
+        SAFETY_LATCHED.store(false, Ordering::Relaxed);
+    }
+    MAIN_KEY_HELD.store(down, Ordering::Relaxed);
+    update_chord();
+    latency::clear();
+}
+
+/// Physical transition of a modifier key (`bit` is one side's `MOD_*` bit).
+#[cfg_attr(not(any(target_os = "windows", target_os = "linux")), allow(dead_code))]
+fn on_modifier(bit: u8, down: bool) {
+    if down {
+        HELD_MODIFIERS.fetch_or(bit, Ordering::Relaxed);
+    } else {
+        HELD_MODIFIERS.fetch_and(!bit, Ordering::Relaxed);
+    }
+    update_chord();
+    keybinds::refresh();
+}
+
+/// Replace the whole held-modifier set, for backends that report modifier
+/// state as flags rather than per-key transitions.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn set_held_modifiers(bits: u8) {
+    HELD_MODIFIERS.store(bits, Ordering::Relaxed);
+    update_chord();
+    keybinds::refresh();
+}
+
+/// Translate chord state into press/release of the PTT "key".
+fn update_chord() {
+    let held = HELD_MODIFIERS.load(Ordering::Relaxed);
+    let required = PTT_MODIFIERS.load(Ordering::Relaxed);
+    let modifiers_held = [MOD_CTRL, MOD_SHIFT, MOD_ALT, MOD_META]
+        .iter()
+        .all(|&group| required & group == 0 || held & group != 0);
+
+    if MAIN_KEY_HELD.load(Ordering::Relaxed) && modifiers_held {
+        on_key_down();
+    } else {
+        on_key_up();
+    }
+}
+
+/// Side bit for a Windows modifier virtual-key code. The generic codes map
+/// to the left side.
+#[cfg_attr(
+    not(any(target_os = "windows", target_os = "macos", target_os = "linux")),
+    allow(dead_code)
+)]
+fn modifier_bit(vk: u32) -> Option<u8> {
+    match vk {
+        0x11 | 0xA2 => Some(0b0000_0001),
+        0xA3 => Some(0b0000_0010),
+        0x10 | 0xA0 => Some(0b0000_0100),
+        0xA1 => Some(0b0000_1000),
+        0x12 | 0xA4 => Some(0b0001_0000),
+        0xA5 => Some(0b0010_0000),
+        0x5B => Some(0b0100_0000),
+        0x5C => Some(0b1000_0000),
+        _ => None,
+    }
+}
+
+/// Parse the `modifiers` argument of `start_ptt_hook` into group bits.
+fn parse_modifiers(names: &[String]) -> Option<u8> {
+    names.iter().try_fold(0, |mask, name| {
+        let group = match name.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => MOD_CTRL,
+            "shift" => MOD_SHIFT,
+            "alt" | "option" => MOD_ALT,
+            "meta" | "win" | "super" | "cmd" | "command" => MOD_META,
+            _ => return None,
+        };
+        Some(mask | group)
+    })
+}
+
+/// Modifier names, as accepted by `parse_modifiers`, for a set of held bits.
+fn modifier_names(bits: u8) -> Vec<String> {
+    [
+        (MOD_CTRL, "ctrl"),
+        (MOD_SHIFT, "shift"),
+        (MOD_ALT, "alt"),
+        (MOD_META, "meta"),
+    ]
+    .iter()
+    .filter(|&&(group, _)| bits & group != 0)
+    .map(|&(_, name)| name.to_string())
+    .collect()
+}
+
+/// Whether a press may start transmit given the focused program. Checked
+/// before the press registers, so a refused press is also not suppressed
+/// and reaches the app it was typed into.
+fn foreground_allows_press() -> bool {
+    if PTT_TRANSMITTING.load(Ordering::Relaxed) {
+        return true;
+    }
+    #[cfg(target_os = "windows")]
+    if PTT_FOREGROUND_SET.load(Ordering::Relaxed) {
+        return foreground::matches();
+    }
+    true
+}
+
+/// The chord is complete: PTT pressed, as far as activation is concerned.
+fn on_key_down() {
+    if !PTT_PRESSED.load(Ordering::Relaxed) && !foreground_allows_press() {
+        return;
+    }
+    // Guard against key-repeat — only act on the initial press
+    if PTT_PRESSED.swap(true, Ordering::Relaxed) {
+        return;
+    }
+    cancel_release();
+    let now = now_ms();
+    PTT_PRESSED_AT_MS.store(now, Ordering::Relaxed);
+    SAFETY_WATCHDOG.call_once(|| {
+        std::thread::spawn(safety_watchdog);
+    });
+
+    match PTT_MODE.load(Ordering::Relaxed) {
+        MODE_TOGGLE => {
+            let last = LAST_TOGGLE_AT_MS.load(Ordering::Relaxed);
+            let debounce = PTT_TOGGLE_DEBOUNCE_MS.load(Ordering::Relaxed);
+            if last != u64::MAX && now.saturating_sub(last) < debounce {
+                return;
+            }
+            LAST_TOGGLE_AT_MS.store(now, Ordering::Relaxed);
+            let was_on = PTT_TRANSMITTING.load(Ordering::Relaxed);
+            set_transmitting(!was_on);
+        }
+        _ => set_transmitting(true),
+    }
+}
+
+/// Part of the chord was released.
+fn on_key_up() {
+    if !PTT_PRESSED.swap(false, Ordering::Relaxed) {
+        return;
+    }
+
+    // Toggle mode latches on press; release is ignored.
+    if PTT_MODE.load(Ordering::Relaxed) != MODE_HOLD {
+        return;
+    }
+    match PTT_RELEASE_DELAY_MS.load(Ordering::Relaxed) {
+        0 => set_transmitting(false),
+        delay => schedule_release(Duration::from_millis(delay)),
+    }
+}
+
+/// Arm (or re-arm) the delayed release.
+fn schedule_release(delay: Duration) {
+    RELEASE_TIMER.call_once(|| {
+        std::thread::spawn(release_timer);
+    });
+    *PENDING_RELEASE.lock().unwrap() = Some(Instant::now() + delay);
+    RELEASE_WAKE.notify_one();
+}
+
+/// Drop any pending delayed release.
+fn cancel_release() {
+    *PENDING_RELEASE.lock().unwrap() = None;
+}
+
+/// Background loop that performs delayed releases.
+///
+/// The release is decided under `PENDING_RELEASE` but emitted after the
+/// guard is dropped, so a slow emit can't stall `cancel_release` on the
+/// hook thread. A press landing in between finds transmit still on and
+/// leaves it; the re-check after releasing turns it back on, and the
+/// `PTT_TRANSMITTING` swap keeps the down/up events paired.
+fn release_timer() {
+    let mut pending = PENDING_RELEASE.lock().unwrap();
+    loop {
+        match *pending {
+            None => pending = RELEASE_WAKE.wait(pending).unwrap(),
+            Some(due) => {
+                let now = Instant::now();
+                if now < due {
+                    pending = RELEASE_WAKE.wait_timeout(pending, due - now).unwrap().0;
+                    continue;
+                }
+                *pending = None;
+                if PTT_PRESSED.load(Ordering::Relaxed) {
+                    continue;
+                }
+                drop(pending);
+                set_transmitting(false);
+                if PTT_PRESSED.load(Ordering::Relaxed) {
+                    set_transmitting(true);
+                }
+                pending = PENDING_RELEASE.lock().unwrap();
+            }
+        }
+    }
+}
+
+/// Update the transmit state and emit `ptt-hook-down` / `ptt-hook-up` if it
+/// changed.
+fn set_transmitting(on: bool) {
+    if PTT_TRANSMITTING.swap(on, Ordering::Relaxed) == on {
+        return;
+    }
+    let payload = latency::event_payload();
+    if let Some(handle) = APP_HANDLE.get() {
+        let event = if on { "ptt-hook-down" } else { "ptt-hook-up" };
+        let _ = handle.emit(event, payload);
+    }
+}
+
+/// Milliseconds elapsed on the monotonic clock since `EPOCH`.
+fn now_ms() -> u64 {
+    EPOCH.get_or_init(Instant::now).elapsed().as_millis() as u64
+}
+
+/// Background loop that force-releases a key held past the safety timeout.
+///
+/// Spawned on the first press and never exits; it only reads atomics, so an
+/// idle iteration costs nothing measurable.
+fn safety_watchdog() {
+    loop {
+        std::thread::sleep(SAFETY_POLL_INTERVAL);
+
+        let limit_secs = PTT_SAFETY_TIMEOUT_SECS.load(Ordering::Relaxed);
+        if limit_secs == 0 || !PTT_PRESSED.load(Ordering::Relaxed) {
+            continue;
+        }
+
+        let held_ms = now_ms().saturating_sub(PTT_PRESSED_AT_MS.load(Ordering::Relaxed));
+        if held_ms < limit_secs * 1000 {
+            continue;
+        }
+
+        // Only the thread that flips PTT_PRESSED off gets to release — a
+        // genuine key-up racing with us wins and we do nothing.
+        if PTT_PRESSED.swap(false, Ordering::Relaxed) {
+            // Forget the stuck key too, or the next modifier event would
+            // complete the chord again.
+            SAFETY_LATCHED.store(true, Ordering::Relaxed);
+            MAIN_KEY_HELD.store(false, Ordering::Relaxed);
+            if let Some(handle) = APP_HANDLE.get() {
+                let _ = handle.emit("ptt-safety-release", held_ms);
+            }
+            set_transmitting(false);
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Key capture
+// ---------------------------------------------------------------------------
+
+/// The platform key code (macOS `kVK_*`, Linux evdev) the hook matches for
+/// a virtual-key code.
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+pub(crate) fn platform_keycode(vk: i32) -> Option<u32> {
+    #[cfg(target_os = "macos")]
+    {
+        event_tap::mac_keycode(vk).map(|code| code as u32)
+    }
+    #[cfg(target_os = "linux")]
+    {
+        evdev::linux_keycode(vk).map(u32::from)
+    }
+}
+
+/// Every physical key or button transition a backend sees, reported before
+/// it updates modifier state. Feeds key capture and the keybind registry;
+/// PTT itself is driven through `on_main_key`. Returns whether a `consume`
+/// keybind claims the transition (only the Windows hooks can act on that).
+#[cfg_attr(
+    not(any(target_os = "windows", target_os = "macos", target_os = "linux")),
+    allow(dead_code)
+)]
+fn observe_key(vk: i32, scan_code: u32, down: bool) -> bool {
+    offer_capture(vk, scan_code, down);
+    keybinds::on_key(vk, scan_code, down)
+}
+
+/// Offer a key transition to an in-progress `capture_next_key`.
+///
+/// A non-modifier press is captured with the modifiers held at the time. A
+/// modifier is only captured on release, and only if nothing else was
+/// pressed meanwhile — otherwise Ctrl+` could never be captured, because
+/// Ctrl goes down first.
+#[cfg_attr(
+    not(any(target_os = "windows", target_os = "macos", target_os = "linux")),
+    allow(dead_code)
+)]
+fn offer_capture(vk: i32, scan_code: u32, down: bool) {
+    if !CAPTURING.load(Ordering::Relaxed) {
+        return;
+    }
+    let held = HELD_MODIFIERS.load(Ordering::Relaxed);
+    let modifiers = match (modifier_bit(vk as u32), down) {
+        (None, true) => held,
+        (Some(bit), false) => held & !bit,
+        _ => return,
+    };
+
+    let Some((_, tx)) = CAPTURE.lock().unwrap().take() else {
+        return;
+    };
+    CAPTURING.store(false, Ordering::Relaxed);
+    let _ = tx.send((vk, scan_code, modifiers));
+}
+
+// ---------------------------------------------------------------------------
+// Hook callback
+// ---------------------------------------------------------------------------
+
+#[cfg(target_os = "windows")]
+unsafe extern "system" fn ll_keyboard_proc(
+    code: i32,
+    w_param: usize,
+    l_param: isize,
+) -> isize {
+    if code >= 0 {
+        let kb = unsafe { &*(l_param as *const win32::KBDLLHOOKSTRUCT) };
+
+        // With a device filter, raw input drives PTT instead (the LL hook
+        // can't tell keyboards apart).
+        let filtered =
+            PTT_DEVICE_FILTER_SET.load(Ordering::Relaxed) && RAW_INPUT_ACTIVE.load(Ordering::Relaxed);
+
+        let down = match w_param {
+            win32::WM_KEYDOWN | win32::WM_SYSKEYDOWN => Some(true),
+            win32::WM_KEYUP | win32::WM_SYSKEYUP => Some(false),
+            _ => None,
+        };
+
+        let scan = scan_with_prefix(kb.scan_code, kb.flags & win32::LLKHF_EXTENDED != 0);
+        let consumed = down.is_some_and(|down| observe_key(kb.vk_code as i32, scan, down));
+
+        // Modifiers count from any keyboard, even with a device filter.
+        if let (Some(bit), Some(down)) = (modifier_bit(kb.vk_code), down) {
+            on_modifier(bit, down);
+        }
+
+        if is_ptt_key(kb.vk_code as i32, scan) && !filtered {
+            if let Some(down) = down {
+                on_main_key_at(down, Some(os_event_time(kb.time)));
+                if should_suppress(down) {
+                    return 1;
+                }
+            }
+        }
+        if consumed {
+            return 1;
+        }
+    }
+    // Pass the event to the next hook — unless suppressed, we only observe.
+    unsafe { win32::CallNextHookEx(0, code, w_param, l_param) }
+}
+
+/// A Windows scan code in the `MAPVK_VK_TO_VSC_EX` form: extended keys
+/// (right-hand Ctrl/Alt, arrows, the navigation block, ...) get the 0xE0
+/// prefix so they don't collide with their left-hand or keypad twins.
+#[cfg(target_os = "windows")]
+fn scan_with_prefix(scan: u32, extended: bool) -> u32 {
+    if extended {
+        0xE000 | (scan & 0xFF)
+    } else {
+        scan
+    }
+}
+
+/// Whether a key event is the PTT key: by scan code if PTT was bound with
+/// one, otherwise by virtual key.
+#[cfg(target_os = "windows")]
+fn is_ptt_key(vk: i32, scan: u32) -> bool {
+    match PTT_SCAN.load(Ordering::Relaxed) {
+        0 => {
+            let bound = PTT_VK.load(Ordering::Relaxed);
+            bound > 0 && (vk == bound || sided_modifiers(bound).contains(&vk))
+        }
+        bound => scan == bound,
+    }
+}
+
+/// The left/right virtual keys for a generic modifier (VK_SHIFT, VK_CONTROL,
+/// VK_MENU), which the frontend binds but the LL hook never reports.
+#[cfg(target_os = "windows")]
+fn sided_modifiers(vk: i32) -> &'static [i32] {
+    match vk {
+        0x10 => &[0xA0, 0xA1],
+        0x11 => &[0xA2, 0xA3],
+        0x12 => &[0xA4, 0xA5],
+        _ => &[],
+    }
+}
+
+/// Timing of a hook event stamped `time` (`GetTickCount` ms) by the OS.
+#[cfg(target_os = "windows")]
+fn os_event_time(time: u32) -> latency::OsEventTime {
+    let now = unsafe { win32::GetTickCount() };
+    latency::OsEventTime {
+        time: u64::from(time),
+        lag_us: Some(u64::from(now.wrapping_sub(time)) * 1000),
+    }
+}
+
+/// Whether to swallow a main-key event. A press is swallowed only while the
+/// full chord is held (so an unmodified key still types), and a release
+/// only if its press was, so the focused app never sees an unpaired key-up.
+#[cfg(target_os = "windows")]
+fn should_suppress(down: bool) -> bool {
+    if !PTT_SUPPRESS.load(Ordering::Relaxed) {
+        return false;
+    }
+    if down {
+        let active = PTT_PRESSED.load(Ordering::Relaxed);
+        SUPPRESSED_DOWN.store(active, Ordering::Relaxed);
+        active
+    } else {
+        SUPPRESSED_DOWN.swap(false, Ordering::Relaxed)
+    }
+}
+
+/// Whether a virtual-key code is a mouse button PTT can bind.
+#[cfg(target_os = "windows")]
+fn is_mouse_vk(vk: i32) -> bool {
+    matches!(vk, 0x04..=0x06)
+}
+
+#[cfg(target_os = "windows")]
+unsafe extern "system" fn ll_mouse_proc(code: i32, w_param: usize, l_param: isize) -> isize {
+    if code >= 0 {
+        let ms = unsafe { &*(l_param as *const win32::MSLLHOOKSTRUCT) };
+        let button = match w_param {
+            win32::WM_MBUTTONDOWN | win32::WM_MBUTTONUP => Some(win32::VK_MBUTTON),
+            win32::WM_XBUTTONDOWN | win32::WM_XBUTTONUP => match ms.mouse_data >> 16 {
+                1 => Some(win32::VK_XBUTTON1),
+                2 => Some(win32::VK_XBUTTON2),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        let down = matches!(w_param, win32::WM_MBUTTONDOWN | win32::WM_XBUTTONDOWN);
+        let consumed = button.is_some_and(|button| observe_key(button as i32, 0, down));
+
+        if button.is_some_and(|b| b as i32 == PTT_VK.load(Ordering::Relaxed)) {
+            on_main_key_at(down, Some(os_event_time(ms.time)));
+            if should_suppress(down) {
+                return 1;
+            }
+        }
+        if consumed {
+            return 1;
+        }
+    }
+    unsafe { win32::CallNextHookEx(0, code, w_param, l_param) }
+}
+
+/// Install or remove the mouse hook to match the bound keys (or a pending
+/// key capture). Runs on the hook thread, which owns `mouse_hook`.
+#[cfg(target_os = "windows")]
+fn sync_mouse_hook(mouse_hook: &mut isize) {
+    let wanted = is_mouse_vk(PTT_VK.load(Ordering::Relaxed))
+        || CAPTURING.load(Ordering::Relaxed)
+        || keybinds::binds_any(is_mouse_vk);
+    if wanted && *mouse_hook == 0 {
+        *mouse_hook = unsafe { win32::SetWindowsHookExW(win32::WH_MOUSE_LL, ll_mouse_proc, 0, 0) };
+    } else if !wanted && *mouse_hook != 0 {
+        unsafe { win32::UnhookWindowsHookEx(*mouse_hook) };
+        *mouse_hook = 0;
+    }
+}
+
+/// Watchdog tick on the hook thread. A bound key whose physical state keeps
+/// disagreeing with what the hook last reported means the hook stopped
+/// receiving events, so reinstall it.
+///
+/// Skipped when the check can't be trusted: with a device filter the hook
+/// deliberately ignores the key, a suppressed key never reaches the async
+/// key state, gamepad buttons aren't seen by the hook at all, a scan-code
+/// binding's virtual key may belong to another key after a layout switch,
+/// and a generic modifier stays down while either side is (the hook tracks
+/// the last side to move). It also stands down after a safety release,
+/// while the stuck key is deliberately ignored until it comes up.
+#[cfg(target_os = "windows")]
+fn check_hook(hook: &mut isize, mouse_hook: &mut isize, strikes: &mut u32) {
+    let vk = PTT_VK.load(Ordering::Relaxed);
+    let filtered =
+        PTT_DEVICE_FILTER_SET.load(Ordering::Relaxed) && RAW_INPUT_ACTIVE.load(Ordering::Relaxed);
+    if vk <= 0
+        || filtered
+        || PTT_SUPPRESS.load(Ordering::Relaxed)
+        || PTT_SCAN.load(Ordering::Relaxed) != 0
+        || gamepad::is_gamepad_vk(vk)
+        || !sided_modifiers(vk).is_empty()
+        || SAFETY_LATCHED.load(Ordering::Relaxed)
+    {
+        *strikes = 0;
+        return;
+    }
+
+    let physical = unsafe { win32::GetAsyncKeyState(vk) } < 0;
+    if physical == MAIN_KEY_HELD.load(Ordering::Relaxed) {
+        *strikes = 0;
+        return;
+    }
+    *strikes += 1;
+    if *strikes < WATCHDOG_STRIKES {
+        return;
+    }
+    *strikes = 0;
+
+    let was_installed = *hook != 0;
+    // Unhooking a hook Windows already removed just fails.
+    unsafe { win32::UnhookWindowsHookEx(*hook) };
+    *hook = unsafe { win32::SetWindowsHookExW(win32::WH_KEYBOARD_LL, ll_keyboard_proc, 0, 0) };
+    let install_error = (*hook == 0).then(last_error);
+    if *mouse_hook != 0 {
+        unsafe { win32::UnhookWindowsHookEx(*mouse_hook) };
+        *mouse_hook = 0;
+    }
+    sync_mouse_hook(mouse_hook);
+    if let Some(error) = install_error {
+        // Retried on the next strikes; only the first failure is reported.
+        if was_installed {
+            emit_hook_error(&PttError::new(
+                "hookLost",
+                format!("the keyboard hook stopped and could not be reinstalled ({error})"),
+            ));
+        }
+        return;
+    }
+
+    on_main_key(physical);
+    emit_hook_recovered();
+}
+
+/// Tell the frontend the hook had stopped and has been restored.
+#[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+fn emit_hook_recovered() {
+    if let Some(handle) = APP_HANDLE.get() {
+        let _ = handle.emit("ptt-hook-recovered", ());
+    }
+}
+
+/// Tell the frontend the hook stopped working after it had started.
+fn emit_hook_error(error: &PttError) {
+    if let Some(handle) = APP_HANDLE.get() {
+        let _ = handle.emit("ptt-hook-error", error);
+    }
+}
+
+/// The calling thread's last Win32 error, for messages.
+#[cfg(target_os = "windows")]
+fn last_error() -> String {
+    format!("error {}", unsafe { win32::GetLastError() })
+}
+
+/// Handle a `WM_INPUT` message on the hook thread.
+#[cfg(target_os = "windows")]
+fn handle_raw_input(l_param: isize) {
+    if !PTT_DEVICE_FILTER_SET.load(Ordering::Relaxed) {
+        return;
+    }
+    let Some((path, vkey, scan, is_up)) = raw_input::read_keyboard(l_param) else {
+        return;
+    };
+    if !is_ptt_key(i32::from(vkey), scan) || !device_matches(&path) {
+        return;
+    }
+    on_main_key(!is_up);
+}
+
+/// Whether `path` matches the configured device filter (case-insensitive —
+/// Windows device paths are not case-stable across APIs).
+#[cfg(target_os = "windows")]
+fn device_matches(path: &str) -> bool {
+    match PTT_DEVICE_FILTER.lock().unwrap().as_deref() {
+        Some(filter) => path.eq_ignore_ascii_case(filter),
+        None => true,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Hook thread
+// ---------------------------------------------------------------------------
+
+/// Why the native hook couldn't start or stopped working. `start_ptt_hook`
+/// fails with it, and `ptt-hook-error` carries it.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct PttError {
+    /// `"invalidModifier"`, `"hookInstallFailed"`, `"permissionDenied"`,
+    /// `"noInputDevices"`, `"threadPanicked"`, `"hookLost"` or
+    /// `"unsupported"`.
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl PttError {
+    fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Lives on a hook thread's stack so that a panic unwinding it marks the
+/// hook stopped and tells the frontend, instead of leaving PTT silently
+/// dead with `HOOK_RUNNING` still set.
+#[cfg_attr(
+    not(any(target_os = "windows", target_os = "macos", target_os = "linux")),
+    allow(dead_code)
+)]
+struct PanicGuard;
+
+impl Drop for PanicGuard {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            HOOK_RUNNING.store(false, Ordering::Relaxed);
+            emit_hook_error(&PttError::new(
+                "threadPanicked",
+                "the input hook thread crashed",
+            ));
+        }
+    }
+}
+
+/// Ask the running hook thread to re-evaluate which hooks it needs.
+#[cfg(target_os = "windows")]
+fn request_hook_sync() {
+    let tid = HOOK_THREAD_ID.load(Ordering::Relaxed);
+    unsafe { win32::PostThreadMessageW(tid, win32::WM_APP_SYNC_HOOKS, 0, 0) };
+}
+
+/// Stop the platform hook thread, if running.
+fn stop_hook() {
+    HELD_MODIFIERS.store(0, Ordering::Relaxed);
+    if HOOK_RUNNING.load(Ordering::Relaxed) {
+        audit::record("inputHookStopped", "ptt", "input hook removed");
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        if HOOK_RUNNING.load(Ordering::Relaxed) {
+            let tid = HOOK_THREAD_ID.load(Ordering::Relaxed);
+            unsafe { win32::PostThreadMessageW(tid, win32::WM_QUIT, 0, 0) };
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    event_tap::stop();
+    #[cfg(target_os = "linux")]
+    evdev::stop();
+}
+
+/// Start the platform hook thread if it isn't running. Binding state is
+/// left alone, so this is safe to call with no PTT key bound.
+fn ensure_hook() -> Result<(), PttError> {
+    let was_running = HOOK_RUNNING.load(Ordering::Relaxed);
+    start_hook()?;
+    if !was_running {
+        audit::record("inputHookStarted", "ptt", "input hook installed");
+    }
+    Ok(())
+}
+
+fn start_hook() -> Result<(), PttError> {
+    #[cfg(target_os = "windows")]
+    {
+        gamepad::ensure_running();
+        if HOOK_RUNNING.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        std::thread::spawn(move || {
+            let _guard = PanicGuard;
+            let tid = unsafe { win32::GetCurrentThreadId() };
+            HOOK_THREAD_ID.store(tid, Ordering::Relaxed);
+
+            let mut hook = unsafe {
+                win32::SetWindowsHookExW(win32::WH_KEYBOARD_LL, ll_keyboard_proc, 0, 0)
+            };
+
+            if hook == 0 {
+                let _ = tx.send(Err(PttError::new(
+                    "hookInstallFailed",
+                    format!("SetWindowsHookEx was refused ({})", last_error()),
+                )));
+                return;
+            }
+
+            // Raw-input sink for per-device filtering. Failure is not
+            // fatal — PTT still works, just without device filtering.
+            let sink = raw_input::create_sink();
+            RAW_INPUT_ACTIVE.store(sink != 0, Ordering::Relaxed);
+
+            let mut mouse_hook = 0;
+            sync_mouse_hook(&mut mouse_hook);
+
+            // Thread timer for the watchdog; arrives as WM_TIMER below.
+            let timer = unsafe { win32::SetTimer(0, 0, WATCHDOG_INTERVAL_MS, 0) };
+            let mut strikes = 0;
+
+            HOOK_RUNNING.store(true, Ordering::Relaxed);
+            let _ = tx.send(Ok(()));
+
+            // Message pump — Windows requires an active message loop on the
+            // thread that installed the hook. This loop runs until WM_QUIT is
+            // posted by `stop_ptt_hook`.
+            let mut msg = win32::MSG {
+                hwnd: 0,
+                message: 0,
+                w_param: 0,
+                l_param: 0,
+                time: 0,
+                pt_x: 0,
+                pt_y: 0,
+            };
+            while unsafe { win32::GetMessageW(&mut msg, 0, 0, 0) } > 0 {
+                // The hook callback does most of the work; only raw input
+                // arrives as a message.
+                match msg.message {
+                    raw_input::WM_INPUT => handle_raw_input(msg.l_param),
+                    win32::WM_APP_SYNC_HOOKS => sync_mouse_hook(&mut mouse_hook),
+                    win32::WM_TIMER => check_hook(&mut hook, &mut mouse_hook, &mut strikes),
+                    _ => {}
+                }
+                // Dispatch so DefWindowProc can release the raw-input buffer.
+                unsafe { win32::DispatchMessageW(&msg) };
+            }
+
+            unsafe { win32::KillTimer(0, timer) };
+            RAW_INPUT_ACTIVE.store(false, Ordering::Relaxed);
+            raw_input::destroy_sink(sink);
+            if mouse_hook != 0 {
+                unsafe { win32::UnhookWindowsHookEx(mouse_hook) };
+            }
+            unsafe { win32::UnhookWindowsHookEx(hook) };
+            HOOK_RUNNING.store(false, Ordering::Relaxed);
+        });
+
+        rx.recv().unwrap_or_else(|_| {
+            Err(PttError::new(
+                "threadPanicked",
+                "the input hook thread crashed while starting",
+            ))
+        })
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        event_tap::start()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        evdev::start()
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        Err(PttError::new(
+            "unsupported",
+            "no native input hook on this platform",
+        ))
+    }
+}
+
+/// Apply `keybinds.json` and watch it for changes (see `keybinds_file`).
+pub(crate) fn init() {
+    keybinds_file::init();
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// A keyboard that PTT can be bound to.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct InputDevice {
+    /// Stable device path — pass this back as `device_filter`.
+    pub path: String,
+}
+
+/// Start the low-level keyboard hook for PTT.
+/// `key_code` may also be a mouse button (`VK_MBUTTON`, `VK_XBUTTON1`,
+/// `VK_XBUTTON2`) or a gamepad button (`VK_GAMEPAD_*`, Windows and Linux).
+/// If already running, just updates the key code (no restart needed).
+/// `device_filter` optionally restricts PTT to one keyboard (a path from
+/// `list_input_devices`); omit it to accept the key from any keyboard.
+/// `modifiers` lists keys that must be held with it — any of `"ctrl"`,
+/// `"shift"`, `"alt"`, `"meta"` — either side satisfies each.
+/// `suppress` swallows the bound key while PTT is held so it doesn't reach
+/// the focused app (Windows only, and not with a device filter).
+/// `release_delay_ms` holds transmit on for that long after the key is
+/// released in hold mode (capped at 2 s; default 0).
+/// `scan_code` (from `capture_next_key`) matches the physical key instead
+/// of `key_code`, so the binding survives a keyboard layout switch.
+/// `foreground_process` — an executable name like `"game.exe"` or a full
+/// path, case-insensitive — only lets a press start transmit while that
+/// program is focused (Windows only; ignored elsewhere).
+/// Succeeds if the hook is running (or already was); otherwise fails with a
+/// `PttError` saying why, e.g. `"invalidModifier"` for an unknown modifier
+/// name or `"permissionDenied"` when the OS withholds key events.
+#[tauri::command]
+pub(crate) fn start_ptt_hook(
+    key_code: i32,
+    device_filter: Option<String>,
+    modifiers: Option<Vec<String>>,
+    suppress: Option<bool>,
+    release_delay_ms: Option<u64>,
+    scan_code: Option<u32>,
+    foreground_process: Option<String>,
+) -> Result<(), PttError> {
+    let names = modifiers.unwrap_or_default();
+    let Some(modifiers) = parse_modifiers(&names) else {
+        return Err(PttError::new(
+            "invalidModifier",
+            format!("unknown modifier in {names:?}"),
+        ));
+    };
+    PTT_MODIFIERS.store(modifiers, Ordering::Relaxed);
+    PTT_SUPPRESS.store(suppress.unwrap_or(false), Ordering::Relaxed);
+    PTT_RELEASE_DELAY_MS.store(
+        release_delay_ms.unwrap_or(0).min(MAX_RELEASE_DELAY_MS),
+        Ordering::Relaxed,
+    );
+    cancel_release();
+    MAIN_KEY_HELD.store(false, Ordering::Relaxed);
+    SAFETY_LATCHED.store(false, Ordering::Relaxed);
+    PTT_DEVICE_FILTER_SET.store(device_filter.is_some(), Ordering::Relaxed);
+    *PTT_DEVICE_FILTER.lock().unwrap() = device_filter;
+    let foreground_process = foreground_process.filter(|name| !name.is_empty());
+    PTT_FOREGROUND_SET.store(foreground_process.is_some(), Ordering::Relaxed);
+    *PTT_FOREGROUND.lock().unwrap() = foreground_process;
+    #[cfg(target_os = "windows")]
+    if PTT_FOREGROUND_SET.load(Ordering::Relaxed) {
+        foreground::ensure_watching();
+        foreground::refresh();
+    }
+    PTT_VK.store(key_code, Ordering::Relaxed);
+    PTT_SCAN.store(scan_code.unwrap_or(0), Ordering::Relaxed);
+    PTT_PRESSED.store(false, Ordering::Relaxed);
+    PTT_TRANSMITTING.store(false, Ordering::Relaxed);
+
+    #[cfg(target_os = "windows")]
+    if HOOK_RUNNING.load(Ordering::Relaxed) {
+        // Already running — key code updated atomically; the thread only
+        // needs to add/remove the mouse hook if the binding kind changed.
+        request_hook_sync();
+        return Ok(());
+    }
+
+    ensure_hook()
+}
+
+/// A key reported by `capture_next_key`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CapturedKey {
+    /// Windows virtual-key code — what `start_ptt_hook` takes on every
+    /// platform.
+    pub key_code: i32,
+    /// Platform scan code (Windows scan code with the 0xE0 prefix for
+    /// extended keys, macOS `kVK_*`, Linux evdev code); 0 for mouse and
+    /// gamepad buttons. Pass it back as `scan_code` to bind the physical key.
+    pub scan_code: u32,
+    /// Modifiers held with it, in `start_ptt_hook`'s `modifiers` format.
+    pub modifiers: Vec<String>,
+    /// Label for the active keyboard layout.
+    pub name: String,
+}
+
+/// Wait for the next key or mouse button the user presses, as seen by the
+/// native hook, so bindings use the same codes the hook will match —
+/// browser key events don't report media or many OEM keys usefully.
+///
+/// Starts the hook thread if needed (it keeps running afterwards, idle until
+/// a key is bound). The key still reaches the focused window. A second call
+/// replaces a pending one, which then fails. Times out after `timeout_ms`
+/// (default 15 s).
+#[tauri::command]
+pub(crate) async fn capture_next_key(timeout_ms: Option<u64>) -> Result<CapturedKey, String> {
+    let id = CAPTURE_SEQ.fetch_add(1, Ordering::Relaxed);
+    let (tx, rx) = mpsc::channel();
+    *CAPTURE.lock().unwrap() = Some((id, tx));
+    CAPTURING.store(true, Ordering::Relaxed);
+
+    let finish = move || {
+        let mut capture = CAPTURE.lock().unwrap();
+        if capture.as_ref().is_some_and(|(current, _)| *current == id) {
+            *capture = None;
+            CAPTURING.store(false, Ordering::Relaxed);
+        }
+        drop(capture);
+        // Remove the mouse hook again if only the capture needed it.
+        #[cfg(target_os = "windows")]
+        if HOOK_RUNNING.load(Ordering::Relaxed) {
+            request_hook_sync();
+        }
+    };
+
+    if let Err(error) = ensure_hook() {
+        finish();
+        return Err(error.message);
+    }
+    #[cfg(target_os = "windows")]
+    request_hook_sync();
+
+    let timeout = timeout_ms.map_or(DEFAULT_CAPTURE_TIMEOUT, Duration::from_millis);
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        // Named here rather than in the hook: the lookup can block (a main
+        // thread round trip on macOS), which would stall input.
+        rx.recv_timeout(timeout)
+            .map(|(vk, scan_code, modifiers)| CapturedKey {
+                key_code: vk,
+                scan_code,
+                modifiers: modifier_names(modifiers),
+                name: crate::keymap::get_key_display_name(vk as u32, scan_code),
+            })
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    finish();
+
+    result.map_err(|e| match e {
+        mpsc::RecvTimeoutError::Timeout => "timed out waiting for a key".to_string(),
+        mpsc::RecvTimeoutError::Disconnected => "key capture was cancelled".to_string(),
+    })
+}
+
+/// Stop the low-level keyboard hook.
+#[tauri::command]
+pub(crate) fn stop_ptt_hook() {
+    PTT_VK.store(0, Ordering::Relaxed);
+    PTT_SCAN.store(0, Ordering::Relaxed);
+    PTT_MODIFIERS.store(0, Ordering::Relaxed);
+    PTT_SUPPRESS.store(false, Ordering::Relaxed);
+    PTT_RELEASE_DELAY_MS.store(0, Ordering::Relaxed);
+    cancel_release();
+    MAIN_KEY_HELD.store(false, Ordering::Relaxed);
+    SAFETY_LATCHED.store(false, Ordering::Relaxed);
+    PTT_DEVICE_FILTER_SET.store(false, Ordering::Relaxed);
+    *PTT_DEVICE_FILTER.lock().unwrap() = None;
+    PTT_FOREGROUND_SET.store(false, Ordering::Relaxed);
+    *PTT_FOREGROUND.lock().unwrap() = None;
+    PTT_PRESSED.store(false, Ordering::Relaxed);
+    PTT_TRANSMITTING.store(false, Ordering::Relaxed);
+
+    // Other keybinds still need the hook thread.
+    if keybinds::is_empty() {
+        stop_hook();
+    } else {
+        #[cfg(target_os = "windows")]
+        request_hook_sync();
+    }
+}
+
+/// Bind a global key (optionally a chord) to `action`, replacing any
+/// existing binding for it. Emits `keybind-down` / `keybind-up` with the
+/// action id. `key_code`, `modifiers` and `scan_code` take the same values
+/// as `start_ptt_hook`. With `gestures`, double taps and holds of the key
+/// also emit `keybind-gesture` (see `keybinds`). `consume` swallows the key
+/// while the binding is held (Windows only) — for media keys, so mapping
+/// play/pause to an action doesn't also control the music player.
+#[tauri::command]
+pub(crate) fn register_keybind(
+    action: String,
+    key_code: i32,
+    modifiers: Option<Vec<String>>,
+    scan_code: Option<u32>,
+    gestures: Option<bool>,
+    consume: Option<bool>,
+) -> Result<(), String> {
+    if action.is_empty() {
+        return Err("keybind action id must not be empty".into());
+    }
+    if key_code <= 0 {
+        return Err(format!("invalid key code {key_code}"));
+    }
+    let modifiers = parse_modifiers(&modifiers.unwrap_or_default())
+        .ok_or_else(|| "unknown modifier name".to_string())?;
+
+    bind_slot(
+        keybinds::Slot::Action,
+        action,
+        key_code,
+        scan_code.unwrap_or(0),
+        modifiers,
+        gestures.unwrap_or(false),
+        consume.unwrap_or(false),
+    )
+}
+
+/// Register a binding and make sure the hook thread is serving it.
+fn bind_slot(
+    slot: keybinds::Slot,
+    action: String,
+    key_code: i32,
+    scan_code: u32,
+    modifiers: u8,
+    gestures: bool,
+    consume: bool,
+) -> Result<(), String> {
+    keybinds::register(
+        slot,
+        action.clone(),
+        key_code,
+        scan_code,
+        modifiers,
+        gestures,
+        consume,
+    );
+    if let Err(error) = ensure_hook() {
+        keybinds::unregister(slot, &action);
+        return Err(error.message);
+    }
+    #[cfg(target_os = "windows")]
+    request_hook_sync();
+    Ok(())
+}
+
+/// Remove a binding, stopping the hook thread once nothing is bound.
+fn unbind_slot(slot: keybinds::Slot, action: &str) -> bool {
+    let removed = keybinds::unregister(slot, action);
+    if keybinds::is_empty() && PTT_VK.load(Ordering::Relaxed) == 0 {
+        stop_hook();
+    } else {
+        #[cfg(target_os = "windows")]
+        request_hook_sync();
+    }
+    removed
+}
+
+/// Bind (or with `key_code` 0, clear) a fixed slot such as push-to-mute.
+fn set_slot_key(
+    slot: keybinds::Slot,
+    key_code: i32,
+    modifiers: Option<Vec<String>>,
+    scan_code: Option<u32>,
+) -> Result<(), String> {
+    if key_code <= 0 {
+        unbind_slot(slot, "");
+        return Ok(());
+    }
+    let modifiers = parse_modifiers(&modifiers.unwrap_or_default())
+        .ok_or_else(|| "unknown modifier name".to_string())?;
+    bind_slot(
+        slot,
+        String::new(),
+        key_code,
+        scan_code.unwrap_or(0),
+        modifiers,
+        false,
+        false,
+    )
+}
+
+/// Remove the binding for `action`. Stops the hook thread once nothing is
+/// bound.
+#[tauri::command]
+pub(crate) fn unregister_keybind(action: String) -> bool {
+    unbind_slot(keybinds::Slot::Action, &action)
+}
+
+/// Bind push-to-mute: mutes while held, emitting `ptm-hook-down` /
+/// `ptm-hook-up`. Takes the same key arguments as `start_ptt_hook`;
+/// `key_code` 0 clears the binding.
+#[tauri::command]
+pub(crate) fn set_push_to_mute(
+    key_code: i32,
+    modifiers: Option<Vec<String>>,
+    scan_code: Option<u32>,
+) -> Result<(), String> {
+    set_slot_key(keybinds::Slot::PushToMute, key_code, modifiers, scan_code)
+}
+
+/// Bind push-to-deafen, emitting `ptd-hook-down` / `ptd-hook-up`. As
+/// `set_push_to_mute`.
+#[tauri::command]
+pub(crate) fn set_push_to_deafen(
+    key_code: i32,
+    modifiers: Option<Vec<String>>,
+    scan_code: Option<u32>,
+) -> Result<(), String> {
+    set_slot_key(keybinds::Slot::PushToDeafen, key_code, modifiers, scan_code)
+}
+
+/// Bind the priority-speaker key, emitting `ptt-priority-down` /
+/// `ptt-priority-up`. The voice code transmits (at a higher bitrate, asking
+/// the server to duck others) while it is held. As `set_push_to_mute`.
+#[tauri::command]
+pub(crate) fn set_priority_speaker_key(
+    key_code: i32,
+    modifiers: Option<Vec<String>>,
+    scan_code: Option<u32>,
+) -> Result<(), String> {
+    set_slot_key(
+        keybinds::Slot::PrioritySpeaker,
+        key_code,
+        modifiers,
+        scan_code,
+    )
+}
+
+/// Percentiles of recent PTT delays: hook receipt to emit, and (Windows,
+/// Linux) OS event time to hook receipt. `reset` starts a fresh window
+/// after reading.
+#[tauri::command]
+pub(crate) fn get_ptt_latency_stats(reset: Option<bool>) -> latency::PttLatencyStats {
+    latency::stats(reset.unwrap_or(false))
+}
+
+/// Result of `check_keybind_conflicts`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct KeybindConflicts {
+    /// Ripcord bindings that pressing the chord would also fire, or that
+    /// would fire it: action ids, `"ptt"`, `"pushToMute"`, `"pushToDeafen"`
+    /// or `"prioritySpeaker"`.
+    pub bindings: Vec<String>,
+    /// Whether another program (or the OS itself, e.g. Win+L) has claimed
+    /// the chord as a global hotkey. `None` where that can't be checked:
+    /// macOS, Linux, and mouse or gamepad buttons.
+    pub os_hotkey: Option<bool>,
+}
+
+/// Whether Windows already has `vk` + `modifiers` registered as a hotkey,
+/// found by trying to register it ourselves and undoing that on success.
+/// Hotkeys from the global-shortcut plugin count too — they are this
+/// process's, but they would take the key just the same.
+#[cfg(target_os = "windows")]
+fn os_hotkey_taken(vk: i32, modifiers: u8) -> Option<bool> {
+    if is_mouse_vk(vk) || gamepad::is_gamepad_vk(vk) {
+        return None;
+    }
+    let flags = [
+        (MOD_CTRL, win32::MOD_CONTROL),
+        (MOD_SHIFT, win32::MOD_SHIFT),
+        (MOD_ALT, win32::MOD_ALT),
+        (MOD_META, win32::MOD_WIN),
+    ]
+    .iter()
+    .filter(|&&(group, _)| modifiers & group != 0)
+    .fold(win32::MOD_NOREPEAT, |flags, &(_, flag)| flags | flag);
+
+    // Thread-level hotkey (no window); ids below 0xC000 are app-defined.
+    const PROBE_ID: i32 = 0xBFFF;
+    if unsafe { win32::RegisterHotKey(0, PROBE_ID, flags, vk as u32) } != 0 {
+        unsafe { win32::UnregisterHotKey(0, PROBE_ID) };
+        return Some(false);
+    }
+    match unsafe { win32::GetLastError() } {
+        win32::ERROR_HOTKEY_ALREADY_REGISTERED => Some(true),
+        _ => None,
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn os_hotkey_taken(_vk: i32, _modifiers: u8) -> Option<bool> {
+    None
+}
+
+/// Check a proposed binding before saving it: which Ripcord bindings it
+/// would collide with, and whether another program holds it as a global
+/// hotkey. `key_code` and `modifiers` are as for `start_ptt_hook`. Pass the
+/// action being rebound as `action` so it isn't reported against itself
+/// (`"ptt"` when rebinding PTT).
+#[tauri::command]
+pub(crate) fn check_keybind_conflicts(
+    key_code: i32,
+    modifiers: Option<Vec<String>>,
+    action: Option<String>,
+) -> Result<KeybindConflicts, String> {
+    if key_code <= 0 {
+        return Err(format!("invalid key code {key_code}"));
+    }
+    let modifiers = parse_modifiers(&modifiers.unwrap_or_default())
+        .ok_or_else(|| "unknown modifier name".to_string())?;
+    let action = action.as_deref();
+
+    let mut bindings = Vec::new();
+    let ptt = PTT_VK.load(Ordering::Relaxed);
+    let ptt_modifiers = PTT_MODIFIERS.load(Ordering::Relaxed);
+    if ptt > 0
+        && action != Some("ptt")
+        && keybinds::chords_overlap(ptt, ptt_modifiers, key_code, modifiers)
+    {
+        bindings.push("ptt".to_string());
+    }
+    bindings.extend(keybinds::conflicts(key_code, modifiers, action));
+
+    Ok(KeybindConflicts {
+        bindings,
+        os_hotkey: os_hotkey_taken(key_code, modifiers),
+    })
+}
+
+/// Registered global keybinds (PTT not included).
+#[tauri::command]
+pub(crate) fn list_keybinds() -> Vec<keybinds::KeybindInfo> {
+    keybinds::list()
+}
+
+/// Whether the OS lets the app observe keys for PTT, asking the user if not.
+///
+/// On macOS this shows the Input Monitoring prompt (Accessibility on older
+/// releases); a grant takes effect after the app restarts. On Linux there is
+/// no prompt — `false` means no input device is readable and the user needs
+/// to join the `input` group. Other platforms always return `true`.
+#[tauri::command]
+pub(crate) fn request_ptt_permission() -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        event_tap::is_trusted(true)
+    }
+    #[cfg(target_os = "linux")]
+    {
+        evdev::has_access()
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    {
+        true
+    }
+}
+
+/// List keyboards PTT can be restricted to via `start_ptt_hook`'s
+/// `device_filter`. Empty on platforms without per-device support.
+#[tauri::command]
+pub(crate) fn list_input_devices() -> Vec<InputDevice> {
+    #[cfg(target_os = "windows")]
+    {
+        raw_input::list_keyboards()
+    }
+    #[cfg(target_os = "linux")]
+    {
+        evdev::list_keyboards()
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        Vec::new()
+    }
+}
+
+/// Set how the PTT key activates transmit.
+///
+///   `"hold"`   — transmit while the key is held (default)
+///   `"toggle"` — each press flips transmit on/off
+///
+/// `debounce_ms` (toggle mode only, default 50, capped at 1000) ignores a
+/// press that follows the previous flip this closely.
+///
+/// Switching modes releases any latched transmit so the new mode starts from
+/// a known "off" state.
+#[tauri::command]
+pub(crate) fn set_ptt_activation(mode: String, debounce_ms: Option<u64>) -> Result<(), String> {
+    let mode = match mode.as_str() {
+        "hold" => MODE_HOLD,
+        "toggle" => MODE_TOGGLE,
+        other => return Err(format!("unknown PTT activation mode: {other}")),
+    };
+
+    let debounce = debounce_ms
+        .unwrap_or(DEFAULT_TOGGLE_DEBOUNCE_MS)
+        .min(MAX_TOGGLE_DEBOUNCE_MS);
+    PTT_TOGGLE_DEBOUNCE_MS.store(debounce, Ordering::Relaxed);
+
+    if PTT_MODE.swap(mode, Ordering::Relaxed) != mode {
+        cancel_release();
+        LAST_TOGGLE_AT_MS.store(u64::MAX, Ordering::Relaxed);
+        set_transmitting(false);
+    }
+    Ok(())
+}
+
+/// Set the hot-mic safety timeout in seconds (0 disables it).
+///
+/// If the PTT key is reported down continuously for longer than this, the
+/// hook emits `ptt-safety-release` (payload: held duration in ms) followed
+/// by `ptt-hook-up`.
+#[tauri::command]
+pub(crate) fn set_ptt_safety_timeout(seconds: u64) {
+    PTT_SAFETY_TIMEOUT_SECS.store(seconds, Ordering::Relaxed);
+}
+
+/// Check whether a key is currently held down (polling fallback).
+///
+/// Returns:
+///   `1`  — key is pressed (Windows)
+///   `0`  — key is not pressed (Windows)
+///   `-1` — not supported on this platform
+#[tauri::command]
+pub(crate) fn check_key_pressed(key_code: i32) -> i32 {
+    #[cfg(target_os = "windows")]
+    {
+        let state = unsafe { win32::GetAsyncKeyState(key_code) };
+        if state < 0 { 1 } else { 0 }
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = key_code;
+        -1
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Lifecycle
+// ---------------------------------------------------------------------------
+
+/// Scheduler key for the follow-up that runs once the old hook has exited.
+const RESET_KEY: &str = "ptt-reset";
+
+/// How often, and for how long, the follow-up waits for the hook thread.
+const RESET_POLL_MS: u64 = 10;
+const RESET_WAIT_MS: u64 = 500;
+
+/// When `reset` started, for the follow-up's give-up deadline.
+static RESET_AT_MS: AtomicU64 = AtomicU64::new(0);
+
+/// Tear down the hook thread and clear all PTT state.
+///
+/// Called by the lifecycle watchdog when the webview reloads: the old page's
+/// listeners are gone, so a still-running hook would emit into the void and
+/// a latched `PTT_PRESSED` would swallow the first press of the new page.
+/// Returns without waiting for the hook thread; `finish_reset` picks up once
+/// it has exited.
+pub(crate) fn reset() {
+    keybinds::clear();
+    stop_ptt_hook();
+    PTT_MODE.store(MODE_HOLD, Ordering::Relaxed);
+    PTT_TOGGLE_DEBOUNCE_MS.store(DEFAULT_TOGGLE_DEBOUNCE_MS, Ordering::Relaxed);
+    LAST_TOGGLE_AT_MS.store(u64::MAX, Ordering::Relaxed);
+    PTT_SAFETY_TIMEOUT_SECS.store(DEFAULT_SAFETY_TIMEOUT_SECS, Ordering::Relaxed);
+
+    let now = scheduler::now_ms();
+    RESET_AT_MS.store(now, Ordering::Relaxed);
+    scheduler::schedule(RESET_KEY, now, finish_reset);
+}
+
+/// Re-apply `keybinds.json` once the old hook thread is gone.
+///
+/// The new page may bind a key before then; `start_hook` sees the exiting
+/// thread as running and returns early, so the hook is started again here
+/// for whatever is bound by now.
+fn finish_reset(_key: &str) {
+    let now = scheduler::now_ms();
+    if HOOK_RUNNING.load(Ordering::Relaxed)
+        && now < RESET_AT_MS.load(Ordering::Relaxed) + RESET_WAIT_MS
+    {
+        scheduler::schedule(RESET_KEY, now + RESET_POLL_MS, finish_reset);
+        return;
+    }
+
+    if PTT_VK.load(Ordering::Relaxed) != 0 || !keybinds::is_empty() {
+        if let Err(error) = ensure_hook() {
+            emit_hook_error(&error);
+        }
+    }
+    keybinds_file::reset();
+}
+
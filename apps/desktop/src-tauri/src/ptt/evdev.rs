@@ -0,0 +1,386 @@
+// ===========================================================================
+// evdev backend (Linux)
+// ===========================================================================
+//
+// X11 key grabs (what the global-shortcut plugin uses) consume the key and
+// don't exist at all under Wayland. Reading the kernel's input devices
+// directly works under both: the hook thread opens every readable
+// `/dev/input/event*` node, `poll`s them, and feeds `EV_KEY` transitions for
+// the bound key into the shared activation state machine. Reading an event
+// node never takes it away from the compositor, so the key still reaches the
+// focused app.
+//
+// Event nodes are normally `root:input 0660`, so this needs the user to be
+// in the `input` group. With no readable keyboard `start_ptt_hook` fails
+// (`"permissionDenied"`, or `"noInputDevices"` if there are no event nodes at
+// all) and `request_ptt_permission` reports the problem, and the frontend
+// falls back to the global-shortcut path.
+//
+// Devices are rescanned every few seconds, so keyboards plugged in after
+// PTT started are picked up; nodes that disappear are dropped on read error.
+//
+// Every event arrives on its device's own node, so a `device_filter` needs
+// no extra machinery here: PTT only follows the node the filter resolves to.
+// Filters are `/dev/input/by-id/*-event-kbd` links (`list_keyboards`), which
+// survive replugging, unlike the `eventN` numbering.
+//
+// Key codes are Windows virtual-key codes, as on every other platform, and
+// `linux_keycode` translates them to `KEY_*` / `BTN_*` codes. Game
+// controllers are event nodes too, so `VK_GAMEPAD_*` buttons map to the
+// kernel's gamepad codes. Pads whose driver reports the triggers or d-pad
+// as axes rather than buttons can't bind those.
+// ===========================================================================
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use super::{
+    latency, observe_key, on_main_key_at, on_modifier, InputDevice, PanicGuard, PttError,
+    HOOK_RUNNING, PTT_DEVICE_FILTER, PTT_DEVICE_FILTER_SET, PTT_SCAN, PTT_VK,
+};
+
+mod ffi {
+    pub const POLLIN: i16 = 0x001;
+
+    #[repr(C)]
+    pub struct PollFd {
+        pub fd: i32,
+        pub events: i16,
+        pub revents: i16,
+    }
+
+    extern "C" {
+        pub fn poll(fds: *mut PollFd, nfds: std::ffi::c_ulong, timeout: i32) -> i32;
+    }
+}
+
+const EV_KEY: u16 = 0x01;
+const KEY_RELEASE: i32 = 0;
+const KEY_PRESS: i32 = 1;
+
+/// `struct input_event`: a `timeval` followed by type, code and value.
+const EVENT_SIZE: usize = 2 * std::mem::size_of::<usize>() + 8;
+
+/// How long `poll` blocks before the thread checks for a stop request.
+const POLL_TIMEOUT_MS: i32 = 100;
+const RESCAN_INTERVAL: Duration = Duration::from_secs(3);
+const BY_ID_DIR: &str = "/dev/input/by-id";
+
+/// Set by `stop`; the hook thread exits at its next poll timeout.
+static STOP: AtomicBool = AtomicBool::new(false);
+
+/// The event node `PTT_DEVICE_FILTER` resolves to, so matching a read is a
+/// path comparison. Re-resolved by `start` and on every rescan, since a
+/// replugged keyboard's by-id link moves to a new `eventN`.
+static FILTER_NODE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Windows virtual-key code -> Linux input event code.
+pub(super) fn linux_keycode(vk: i32) -> Option<u16> {
+    const LETTERS: [u16; 26] = [
+        30, 48, 46, 32, 18, 33, 34, 35, 23, 36, 37, 38, 50, 49, 24, 25, 16, 19, 31, 20, 22, 47, 17,
+        45, 21, 44,
+    ];
+    const KEYPAD: [u16; 10] = [82, 79, 80, 81, 75, 76, 77, 71, 72, 73];
+
+    Some(match vk {
+        0x41..=0x5A => LETTERS[(vk - 0x41) as usize],
+        0x30 => 11,                            // KEY_0
+        0x31..=0x39 => (vk - 0x31 + 2) as u16, // KEY_1..KEY_9
+        0x60..=0x69 => KEYPAD[(vk - 0x60) as usize],
+        0x70..=0x79 => (vk - 0x70 + 59) as u16,  // F1..F10
+        0x7A => 87,                              // F11
+        0x7B => 88,                              // F12
+        0x7C..=0x87 => (vk - 0x7C + 183) as u16, // F13..F24
+        0x04 => 0x112,                           // BTN_MIDDLE
+        0x05 => 0x113,                           // BTN_SIDE
+        0x06 => 0x114,                           // BTN_EXTRA
+        0xC3 => 0x130,                           // Gamepad A -> BTN_SOUTH
+        0xC4 => 0x131,                           // Gamepad B -> BTN_EAST
+        0xC5 => 0x133,                           // Gamepad X -> BTN_X
+        0xC6 => 0x134,                           // Gamepad Y -> BTN_Y
+        0xC7 => 0x137,                           // Right shoulder -> BTN_TR
+        0xC8 => 0x136,                           // Left shoulder -> BTN_TL
+        0xC9 => 0x138,                           // Left trigger -> BTN_TL2
+        0xCA => 0x139,                           // Right trigger -> BTN_TR2
+        0xCB => 0x220,                           // D-pad up
+        0xCC => 0x221,                           // D-pad down
+        0xCD => 0x222,                           // D-pad left
+        0xCE => 0x223,                           // D-pad right
+        0xCF => 0x13B,                           // Menu -> BTN_START
+        0xD0 => 0x13A,                           // View -> BTN_SELECT
+        0xD1 => 0x13D,                           // Left stick -> BTN_THUMBL
+        0xD2 => 0x13E,                           // Right stick -> BTN_THUMBR
+        0x08 => 14,                              // Backspace
+        0x09 => 15,                              // Tab
+        0x0D => 28,                              // Enter
+        0x10 | 0xA0 => 42,                       // Shift / LShift
+        0xA1 => 54,                              // RShift
+        0x11 | 0xA2 => 29,                       // Control / LControl
+        0xA3 => 97,                              // RControl
+        0x12 | 0xA4 => 56,                       // Alt / LAlt
+        0xA5 => 100,                             // RAlt
+        0x5B => 125,                             // LWin -> LeftMeta
+        0x5C => 126,                             // RWin -> RightMeta
+        0x5D => 127,                             // Apps -> Compose
+        0x13 => 119,                             // Pause
+        0x14 => 58,                              // Caps Lock
+        0x1B => 1,                               // Escape
+        0x20 => 57,                              // Space
+        0x21 => 104,                             // Page Up
+        0x22 => 109,                             // Page Down
+        0x23 => 107,                             // End
+        0x24 => 102,                             // Home
+        0x25 => 105,                             // Left
+        0x26 => 103,                             // Up
+        0x27 => 106,                             // Right
+        0x28 => 108,                             // Down
+        0x2C => 99,                              // Print Screen -> SysRq
+        0x2D => 110,                             // Insert
+        0x2E => 111,                             // Delete
+        0x6A => 55,                              // Keypad *
+        0x6B => 78,                              // Keypad +
+        0x6D => 74,                              // Keypad -
+        0x6E => 83,                              // Keypad .
+        0x6F => 98,                              // Keypad /
+        0x90 => 69,                              // Num Lock
+        0x91 => 70,                              // Scroll Lock
+        0xBA => 39,                              // ;
+        0xBB => 13,                              // =
+        0xBC => 51,                              // ,
+        0xBD => 12,                              // -
+        0xBE => 52,                              // .
+        0xBF => 53,                              // /
+        0xC0 => 41,                              // `
+        0xDB => 26,                              // [
+        0xDC => 43,                              // \
+        0xDD => 27,                              // ]
+        0xDE => 40,                              // '
+        0xAD => 113,                             // Volume Mute -> KEY_MUTE
+        0xAE => 114,                             // Volume Down
+        0xAF => 115,                             // Volume Up
+        0xB0 => 163,                             // Next Track -> KEY_NEXTSONG
+        0xB1 => 165,                             // Previous Track
+        0xB2 => 166,                             // Stop -> KEY_STOPCD
+        0xB3 => 164,                             // Play/Pause
+        0xE8 => 248,                             // Mic mute (Ripcord's code) -> KEY_MICMUTE
+        _ => return None,
+    })
+}
+
+/// Linux key code -> Windows virtual-key code, for key capture. Where
+/// several VKs map to one key, the side-specific one wins (Left Shift over
+/// Shift).
+fn windows_vk(code: u16) -> Option<i32> {
+    (1..=0xFE).rev().find(|&vk| linux_keycode(vk) == Some(code))
+}
+
+/// Open any event nodes not already in `devices`.
+fn scan(devices: &mut HashMap<PathBuf, File>) {
+    let Ok(entries) = std::fs::read_dir("/dev/input") else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_event_node = entry.file_name().to_string_lossy().starts_with("event");
+        if !is_event_node || devices.contains_key(&path) {
+            continue;
+        }
+        if let Ok(file) = File::open(&path) {
+            devices.insert(path, file);
+        }
+    }
+}
+
+/// Side bit of the chord state (`super::MOD_*`) for a modifier key code.
+fn modifier_bit(code: u16) -> Option<u8> {
+    match code {
+        29 => Some(0b0000_0001),  // KEY_LEFTCTRL
+        97 => Some(0b0000_0010),  // KEY_RIGHTCTRL
+        42 => Some(0b0000_0100),  // KEY_LEFTSHIFT
+        54 => Some(0b0000_1000),  // KEY_RIGHTSHIFT
+        56 => Some(0b0001_0000),  // KEY_LEFTALT
+        100 => Some(0b0010_0000), // KEY_RIGHTALT
+        125 => Some(0b0100_0000), // KEY_LEFTMETA
+        126 => Some(0b1000_0000), // KEY_RIGHTMETA
+        _ => None,
+    }
+}
+
+/// Resolve the device filter's link to its event node.
+fn resolve_device_filter() {
+    let filter = PTT_DEVICE_FILTER.lock().unwrap().clone();
+    *FILTER_NODE.lock().unwrap() = filter.and_then(|filter| std::fs::canonicalize(filter).ok());
+}
+
+/// Whether PTT follows the event node at `path` under the current device
+/// filter.
+fn device_matches(path: &Path) -> bool {
+    !PTT_DEVICE_FILTER_SET.load(Ordering::Relaxed)
+        || FILTER_NODE.lock().unwrap().as_deref() == Some(path)
+}
+
+/// Timing of an event from its `timeval` (`CLOCK_REALTIME` unless a client
+/// switched the device's clock).
+fn event_time(event: &[u8]) -> latency::OsEventTime {
+    let word = std::mem::size_of::<usize>();
+    let field = |at: usize| match word {
+        8 => u64::from_ne_bytes(event[at..at + 8].try_into().unwrap()),
+        _ => u64::from(u32::from_ne_bytes(event[at..at + 4].try_into().unwrap())),
+    };
+    let time = field(0) * 1_000_000 + field(word);
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_micros() as u64)
+        .unwrap_or(0);
+    latency::OsEventTime {
+        time,
+        // A device on another clock would show as nonsense; drop it.
+        lag_us: now.checked_sub(time).filter(|&lag| lag < 60_000_000),
+    }
+}
+
+/// Feed one read's worth of `input_event`s to the state machine.
+/// `ptt_device` is false for events from a keyboard the filter excludes;
+/// those still count for modifiers, keybinds and key capture.
+fn handle_events(buf: &[u8], ptt_device: bool) {
+    let code = match PTT_SCAN.load(Ordering::Relaxed) {
+        0 => linux_keycode(PTT_VK.load(Ordering::Relaxed)),
+        scan => u16::try_from(scan).ok(),
+    };
+    let offset = EVENT_SIZE - 8;
+    for event in buf.chunks_exact(EVENT_SIZE) {
+        let kind = u16::from_ne_bytes([event[offset], event[offset + 1]]);
+        let key = u16::from_ne_bytes([event[offset + 2], event[offset + 3]]);
+        let value = i32::from_ne_bytes(event[offset + 4..offset + 8].try_into().unwrap());
+        // Value 2 is autorepeat, which changes nothing.
+        let down = match value {
+            KEY_PRESS => true,
+            KEY_RELEASE => false,
+            _ => continue,
+        };
+        if kind != EV_KEY {
+            continue;
+        }
+        if let Some(vk) = windows_vk(key) {
+            observe_key(vk, u32::from(key), down);
+        }
+        if let Some(bit) = modifier_bit(key) {
+            on_modifier(bit, down);
+        }
+        if ptt_device && code == Some(key) {
+            on_main_key_at(down, Some(event_time(event)));
+        }
+    }
+}
+
+/// Keyboards with a stable by-id link, for `device_filter`.
+pub(super) fn list_keyboards() -> Vec<InputDevice> {
+    let Ok(entries) = std::fs::read_dir(BY_ID_DIR) else {
+        return Vec::new();
+    };
+    let mut devices: Vec<InputDevice> = entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().ends_with("-event-kbd"))
+        .map(|entry| InputDevice {
+            path: entry.path().to_string_lossy().into_owned(),
+        })
+        .collect();
+    devices.sort_by(|a, b| a.path.cmp(&b.path));
+    devices
+}
+
+/// Whether at least one input event node can be read.
+pub(super) fn has_access() -> bool {
+    let mut devices = HashMap::new();
+    scan(&mut devices);
+    !devices.is_empty()
+}
+
+/// Start the hook thread. Fails if no input device is readable.
+pub(super) fn start() -> Result<(), PttError> {
+    STOP.store(false, Ordering::Relaxed);
+    resolve_device_filter();
+    if HOOK_RUNNING.load(Ordering::Relaxed) {
+        // The thread reads PTT_VK on every event; nothing to restart.
+        return Ok(());
+    }
+
+    let mut devices = HashMap::new();
+    scan(&mut devices);
+    if devices.is_empty() {
+        let any_nodes = std::fs::read_dir("/dev/input").is_ok_and(|entries| {
+            entries
+                .flatten()
+                .any(|entry| entry.file_name().to_string_lossy().starts_with("event"))
+        });
+        return Err(if any_nodes {
+            PttError::new(
+                "permissionDenied",
+                "no readable input devices; add the user to the `input` group",
+            )
+        } else {
+            PttError::new("noInputDevices", "no input devices found in /dev/input")
+        });
+    }
+    HOOK_RUNNING.store(true, Ordering::Relaxed);
+
+    std::thread::spawn(move || {
+        let _guard = PanicGuard;
+        let mut buf = [0u8; EVENT_SIZE * 64];
+        let mut last_scan = Instant::now();
+
+        while !STOP.load(Ordering::Relaxed) {
+            if last_scan.elapsed() >= RESCAN_INTERVAL {
+                scan(&mut devices);
+                resolve_device_filter();
+                last_scan = Instant::now();
+            }
+
+            let paths: Vec<PathBuf> = devices.keys().cloned().collect();
+            let mut fds: Vec<ffi::PollFd> = paths
+                .iter()
+                .map(|path| ffi::PollFd {
+                    fd: devices[path].as_raw_fd(),
+                    events: ffi::POLLIN,
+                    revents: 0,
+                })
+                .collect();
+            let ready = unsafe { ffi::poll(fds.as_mut_ptr(), fds.len() as _, POLL_TIMEOUT_MS) };
+            if ready <= 0 {
+                continue;
+            }
+
+            for (path, fd) in paths.iter().zip(&fds) {
+                if fd.revents == 0 {
+                    continue;
+                }
+                // POLLERR/POLLHUP (device unplugged) also surface as a
+                // failed read.
+                let Some(file) = devices.get_mut(path) else {
+                    continue;
+                };
+                match file.read(&mut buf) {
+                    Ok(n) if n > 0 => handle_events(&buf[..n], device_matches(path)),
+                    _ => {
+                        devices.remove(path);
+                    }
+                }
+            }
+        }
+
+        HOOK_RUNNING.store(false, Ordering::Relaxed);
+    });
+
+    Ok(())
+}
+
+/// Ask the hook thread to exit; it closes its devices on the way out.
+pub(super) fn stop() {
+    STOP.store(true, Ordering::Relaxed);
+}
@@ -0,0 +1,466 @@
+// ===========================================================================
+// Quartz Event Tap backend (macOS)
+// ===========================================================================
+//
+// The global-shortcut plugin registers a Carbon hotkey, which consumes the
+// key and — with another app focused — frequently never reports the
+// release. A listen-only `CGEventTap` sees every key event in the session
+// without swallowing it, the same way the Windows LL hook does.
+//
+// The tap runs on a dedicated thread with its own `CFRunLoop`;
+// `stop_ptt_hook` stops that run loop and the thread releases the tap.
+//
+// Permissions: since 10.15 a listen-only keyboard tap needs Input Monitoring
+// (`CGRequestListenEventAccess`), and older releases need Accessibility
+// (`AXIsProcessTrustedWithOptions`). The Input Monitoring calls don't exist
+// before 10.15, so they're looked up with `dlsym` rather than linked, and
+// the Accessibility check stands in when they're missing. Without it `CGEventTapCreate` returns
+// NULL, `start_ptt_hook` fails with `"permissionDenied"`, and the frontend
+// should call `request_ptt_permission` — which shows the system prompt and
+// reports the current grant — then retry. macOS only applies a new grant
+// after the app restarts.
+//
+// Key codes: the frontend keeps binding Windows virtual-key codes on every
+// platform, so `start_ptt_hook(keyCode)` takes the same value here and
+// `mac_keycode` translates it to a macOS `kVK_*` code. Middle/side mouse
+// buttons map to Quartz "other mouse" button numbers.
+// ===========================================================================
+
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::{
+    emit_hook_recovered, observe_key, on_main_key, set_held_modifiers, PanicGuard, PttError,
+    HOOK_RUNNING, PTT_SCAN, PTT_VK,
+};
+
+#[allow(non_upper_case_globals)]
+mod ffi {
+    use std::ffi::{c_char, c_void, CStr};
+
+    pub const kCGSessionEventTap: u32 = 1;
+    pub const kCGHeadInsertEventTap: u32 = 0;
+    pub const kCGEventTapOptionListenOnly: u32 = 1;
+
+    pub const kCGEventKeyDown: u32 = 10;
+    pub const kCGEventKeyUp: u32 = 11;
+    pub const kCGEventFlagsChanged: u32 = 12;
+    pub const kCGEventOtherMouseDown: u32 = 25;
+    pub const kCGEventOtherMouseUp: u32 = 26;
+    pub const kCGEventTapDisabledByTimeout: u32 = 0xFFFF_FFFE;
+    pub const kCGEventTapDisabledByUserInput: u32 = 0xFFFF_FFFF;
+
+    pub const kCGMouseEventButtonNumber: u32 = 3;
+    pub const kCGKeyboardEventAutorepeat: u32 = 8;
+    pub const kCGKeyboardEventKeycode: u32 = 9;
+
+    pub type CGEventTapCallBack =
+        unsafe extern "C" fn(*mut c_void, u32, *mut c_void, *mut c_void) -> *mut c_void;
+
+    #[repr(C)]
+    pub struct CFDictionaryKeyCallBacks {
+        _opaque: [usize; 6],
+    }
+
+    #[repr(C)]
+    pub struct CFDictionaryValueCallBacks {
+        _opaque: [usize; 5],
+    }
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        pub fn CGEventTapCreate(
+            tap: u32,
+            place: u32,
+            options: u32,
+            events_of_interest: u64,
+            callback: CGEventTapCallBack,
+            user_info: *mut c_void,
+        ) -> *mut c_void;
+        pub fn CGEventTapEnable(tap: *mut c_void, enable: bool);
+        pub fn CGEventGetIntegerValueField(event: *mut c_void, field: u32) -> i64;
+        pub fn CGEventGetFlags(event: *mut c_void) -> u64;
+    }
+
+    /// `CGPreflightListenEventAccess` / `CGRequestListenEventAccess` (10.15+).
+    pub type ListenEventAccess = unsafe extern "C" fn() -> bool;
+
+    /// Search every image already loaded into the process.
+    const RTLD_DEFAULT: *mut c_void = -2isize as *mut c_void;
+
+    extern "C" {
+        fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+    }
+
+    /// Look up a CoreGraphics function that may be missing on this release.
+    pub unsafe fn symbol<T>(name: &CStr) -> Option<T> {
+        let ptr = unsafe { dlsym(RTLD_DEFAULT, name.as_ptr()) };
+        (!ptr.is_null()).then(|| unsafe { std::mem::transmute_copy(&ptr) })
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        pub static kCFRunLoopCommonModes: *const c_void;
+        pub static kCFBooleanTrue: *const c_void;
+        pub static kCFTypeDictionaryKeyCallBacks: CFDictionaryKeyCallBacks;
+        pub static kCFTypeDictionaryValueCallBacks: CFDictionaryValueCallBacks;
+
+        pub fn CFMachPortCreateRunLoopSource(
+            allocator: *const c_void,
+            port: *mut c_void,
+            order: isize,
+        ) -> *mut c_void;
+        pub fn CFMachPortInvalidate(port: *mut c_void);
+        pub fn CFRunLoopGetCurrent() -> *mut c_void;
+        pub fn CFRunLoopAddSource(rl: *mut c_void, source: *mut c_void, mode: *const c_void);
+        pub fn CFRunLoopRun();
+        pub fn CFRunLoopStop(rl: *mut c_void);
+        pub fn CFDictionaryCreate(
+            allocator: *const c_void,
+            keys: *const *const c_void,
+            values: *const *const c_void,
+            count: isize,
+            key_callbacks: *const CFDictionaryKeyCallBacks,
+            value_callbacks: *const CFDictionaryValueCallBacks,
+        ) -> *const c_void;
+        pub fn CFRelease(cf: *const c_void);
+    }
+
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        pub static kAXTrustedCheckOptionPrompt: *const c_void;
+        pub fn AXIsProcessTrusted() -> bool;
+        pub fn AXIsProcessTrustedWithOptions(options: *const c_void) -> bool;
+    }
+}
+
+/// `CFMachPortRef` of the live tap (0 = none). The callback needs it to
+/// re-enable the tap after macOS disables it.
+static TAP: AtomicUsize = AtomicUsize::new(0);
+
+/// `CFRunLoopRef` of the tap thread, for `stop`.
+static RUN_LOOP: AtomicUsize = AtomicUsize::new(0);
+
+/// Device-dependent modifier bits (`NX_DEVICE*KEYMASK`), which unlike the
+/// `kCGEventFlagMask*` bits tell left from right, with their key codes and
+/// the matching side bit of the chord state (`super::MOD_*`).
+const MODIFIERS: [(i64, u64, u8); 8] = [
+    (0x3B, 0x0000_0001, 0b0000_0001), // left control
+    (0x3E, 0x0000_2000, 0b0000_0010), // right control
+    (0x38, 0x0000_0002, 0b0000_0100), // left shift
+    (0x3C, 0x0000_0004, 0b0000_1000), // right shift
+    (0x3A, 0x0000_0020, 0b0001_0000), // left option
+    (0x3D, 0x0000_0040, 0b0010_0000), // right option
+    (0x37, 0x0000_0008, 0b0100_0000), // left command
+    (0x36, 0x0000_0010, 0b1000_0000), // right command
+];
+
+/// `kCGEventFlagMaskAlphaShift` — caps lock has no device-dependent bit.
+const CAPS_LOCK: (i64, u64) = (0x39, 0x0001_0000);
+
+/// Flag bit reporting whether the modifier with `keycode` is down.
+fn modifier_mask(keycode: i64) -> Option<u64> {
+    MODIFIERS
+        .iter()
+        .map(|&(code, mask, _)| (code, mask))
+        .chain([CAPS_LOCK])
+        .find(|&(code, _)| code == keycode)
+        .map(|(_, mask)| mask)
+}
+
+/// Windows virtual-key code -> macOS `kVK_*` key code.
+pub(super) fn mac_keycode(vk: i32) -> Option<i64> {
+    const LETTERS: [i64; 26] = [
+        0x00, 0x0B, 0x08, 0x02, 0x0E, 0x03, 0x05, 0x04, 0x22, 0x26, 0x28, 0x25, 0x2E, 0x2D, 0x1F,
+        0x23, 0x0C, 0x0F, 0x01, 0x11, 0x20, 0x09, 0x0D, 0x07, 0x10, 0x06,
+    ];
+    const DIGITS: [i64; 10] = [0x1D, 0x12, 0x13, 0x14, 0x15, 0x17, 0x16, 0x1A, 0x1C, 0x19];
+    const KEYPAD: [i64; 10] = [0x52, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5B, 0x5C];
+    const FUNCTION: [i64; 20] = [
+        0x7A, 0x78, 0x63, 0x76, 0x60, 0x61, 0x62, 0x64, 0x65, 0x6D, 0x67, 0x6F, 0x69, 0x6B, 0x71,
+        0x6A, 0x40, 0x4F, 0x50, 0x5A,
+    ];
+
+    Some(match vk {
+        0x41..=0x5A => LETTERS[(vk - 0x41) as usize],
+        0x30..=0x39 => DIGITS[(vk - 0x30) as usize],
+        0x60..=0x69 => KEYPAD[(vk - 0x60) as usize],
+        0x70..=0x83 => FUNCTION[(vk - 0x70) as usize],
+        0x08 => 0x33,        // Backspace -> Delete
+        0x09 => 0x30,        // Tab
+        0x0D => 0x24,        // Enter -> Return
+        0x10 | 0xA0 => 0x38, // Shift / LShift
+        0xA1 => 0x3C,        // RShift
+        0x11 | 0xA2 => 0x3B, // Control / LControl
+        0xA3 => 0x3E,        // RControl
+        0x12 | 0xA4 => 0x3A, // Alt / LAlt -> Option
+        0xA5 => 0x3D,        // RAlt -> Right Option
+        0x5B => 0x37,        // LWin -> Command
+        0x5C => 0x36,        // RWin -> Right Command
+        0x14 => 0x39,        // Caps Lock
+        0x1B => 0x35,        // Escape
+        0x20 => 0x31,        // Space
+        0x21 => 0x74,        // Page Up
+        0x22 => 0x79,        // Page Down
+        0x23 => 0x77,        // End
+        0x24 => 0x73,        // Home
+        0x25 => 0x7B,        // Left
+        0x26 => 0x7E,        // Up
+        0x27 => 0x7C,        // Right
+        0x28 => 0x7D,        // Down
+        0x2D => 0x72,        // Insert -> Help
+        0x2E => 0x75,        // Delete -> Forward Delete
+        0x6A => 0x43,        // Keypad *
+        0x6B => 0x45,        // Keypad +
+        0x6D => 0x4E,        // Keypad -
+        0x6E => 0x41,        // Keypad .
+        0x6F => 0x4B,        // Keypad /
+        0xBA => 0x29,        // ;
+        0xBB => 0x18,        // =
+        0xBC => 0x2B,        // ,
+        0xBD => 0x1B,        // -
+        0xBE => 0x2F,        // .
+        0xBF => 0x2C,        // /
+        0xC0 => 0x32,        // `
+        0xDB => 0x21,        // [
+        0xDC => 0x2A,        // \
+        0xDD => 0x1E,        // ]
+        0xDE => 0x27,        // '
+        _ => return None,
+    })
+}
+
+/// macOS key code -> Windows virtual-key code, for key capture. Where
+/// several VKs map to one key, the side-specific one wins (Left Shift over
+/// Shift).
+fn windows_vk(keycode: i64) -> Option<i32> {
+    (1..=0xFE)
+        .rev()
+        .find(|&vk| mac_keycode(vk) == Some(keycode))
+}
+
+/// Windows mouse VK -> Quartz button number (0 left, 1 right, 2 middle, …).
+fn mouse_button(vk: i32) -> Option<i64> {
+    match vk {
+        0x04 => Some(2),
+        0x05 => Some(3),
+        0x06 => Some(4),
+        _ => None,
+    }
+}
+
+unsafe extern "C" fn tap_callback(
+    _proxy: *mut c_void,
+    event_type: u32,
+    event: *mut c_void,
+    _user_info: *mut c_void,
+) -> *mut c_void {
+    let vk = PTT_VK.load(Ordering::Relaxed);
+    // Key code PTT matches: the bound scan code (a `kVK_*`) if any.
+    let ptt_keycode = match PTT_SCAN.load(Ordering::Relaxed) {
+        0 if vk > 0 => mac_keycode(vk),
+        0 => None,
+        scan => Some(i64::from(scan)),
+    };
+
+    match event_type {
+        // macOS disables a tap whose callback stalls or when secure input
+        // toggles; turn it straight back on.
+        ffi::kCGEventTapDisabledByTimeout | ffi::kCGEventTapDisabledByUserInput => {
+            let tap = TAP.load(Ordering::Relaxed);
+            if tap != 0 {
+                unsafe { ffi::CGEventTapEnable(tap as *mut c_void, true) };
+                // Key events may have been missed while it was off.
+                if event_type == ffi::kCGEventTapDisabledByTimeout {
+                    emit_hook_recovered();
+                }
+            }
+        }
+        ffi::kCGEventKeyDown | ffi::kCGEventKeyUp => {
+            let keycode =
+                unsafe { ffi::CGEventGetIntegerValueField(event, ffi::kCGKeyboardEventKeycode) };
+            let down = event_type == ffi::kCGEventKeyDown;
+            let repeat = down
+                && unsafe {
+                    ffi::CGEventGetIntegerValueField(event, ffi::kCGKeyboardEventAutorepeat)
+                } != 0;
+            if !repeat {
+                if let Some(key) = windows_vk(keycode) {
+                    observe_key(key, keycode as u32, down);
+                }
+                if ptt_keycode == Some(keycode) {
+                    on_main_key(down);
+                }
+            }
+        }
+        // Modifiers only produce flagsChanged; whether each one is down is
+        // read from its device-dependent flag bit.
+        ffi::kCGEventFlagsChanged => {
+            let keycode =
+                unsafe { ffi::CGEventGetIntegerValueField(event, ffi::kCGKeyboardEventKeycode) };
+            let flags = unsafe { ffi::CGEventGetFlags(event) };
+            if let Some(mask) = modifier_mask(keycode) {
+                let down = flags & mask != 0;
+                if let Some(key) = windows_vk(keycode) {
+                    observe_key(key, keycode as u32, down);
+                }
+                if ptt_keycode == Some(keycode) {
+                    on_main_key(down);
+                }
+            }
+            set_held_modifiers(
+                MODIFIERS
+                    .iter()
+                    .filter(|&&(_, mask, _)| flags & mask != 0)
+                    .fold(0, |bits, &(_, _, bit)| bits | bit),
+            );
+        }
+        ffi::kCGEventOtherMouseDown | ffi::kCGEventOtherMouseUp => {
+            let button =
+                unsafe { ffi::CGEventGetIntegerValueField(event, ffi::kCGMouseEventButtonNumber) };
+            let down = event_type == ffi::kCGEventOtherMouseDown;
+            if let Some(key) = (0x04..=0x06).find(|&key| mouse_button(key) == Some(button)) {
+                observe_key(key, 0, down);
+            }
+            if mouse_button(vk) == Some(button) {
+                on_main_key(down);
+            }
+        }
+        _ => {}
+    }
+    // Listen-only tap: the event is delivered regardless of what we return.
+    event
+}
+
+/// Start the tap thread. Fails if the tap could not be created — almost
+/// always a missing Input Monitoring / Accessibility grant.
+pub(super) fn start() -> Result<(), PttError> {
+    if HOOK_RUNNING.load(Ordering::Relaxed) {
+        // The callback reads PTT_VK on every event; nothing to restart.
+        return Ok(());
+    }
+
+    let (tx, rx) = std::sync::mpsc::channel();
+
+    std::thread::spawn(move || {
+        let _guard = PanicGuard;
+        let mask = [
+            ffi::kCGEventKeyDown,
+            ffi::kCGEventKeyUp,
+            ffi::kCGEventFlagsChanged,
+            ffi::kCGEventOtherMouseDown,
+            ffi::kCGEventOtherMouseUp,
+        ]
+        .iter()
+        .fold(0u64, |mask, t| mask | 1 << t);
+
+        let tap = unsafe {
+            ffi::CGEventTapCreate(
+                ffi::kCGSessionEventTap,
+                ffi::kCGHeadInsertEventTap,
+                ffi::kCGEventTapOptionListenOnly,
+                mask,
+                tap_callback,
+                std::ptr::null_mut(),
+            )
+        };
+        if tap.is_null() {
+            let error = if is_trusted(false) {
+                PttError::new("hookInstallFailed", "CGEventTapCreate failed")
+            } else {
+                PttError::new(
+                    "permissionDenied",
+                    "Input Monitoring access has not been granted",
+                )
+            };
+            let _ = tx.send(Err(error));
+            return;
+        }
+
+        let source = unsafe { ffi::CFMachPortCreateRunLoopSource(std::ptr::null(), tap, 0) };
+        if source.is_null() {
+            unsafe { ffi::CFRelease(tap) };
+            let _ = tx.send(Err(PttError::new(
+                "hookInstallFailed",
+                "could not add the event tap to a run loop",
+            )));
+            return;
+        }
+
+        let run_loop = unsafe { ffi::CFRunLoopGetCurrent() };
+        unsafe {
+            ffi::CFRunLoopAddSource(run_loop, source, ffi::kCFRunLoopCommonModes);
+            ffi::CGEventTapEnable(tap, true);
+        }
+        TAP.store(tap as usize, Ordering::Relaxed);
+        RUN_LOOP.store(run_loop as usize, Ordering::Relaxed);
+        HOOK_RUNNING.store(true, Ordering::Relaxed);
+        let _ = tx.send(Ok(()));
+
+        // Runs until `stop` calls CFRunLoopStop.
+        unsafe { ffi::CFRunLoopRun() };
+
+        TAP.store(0, Ordering::Relaxed);
+        RUN_LOOP.store(0, Ordering::Relaxed);
+        unsafe {
+            ffi::CGEventTapEnable(tap, false);
+            ffi::CFMachPortInvalidate(tap);
+            ffi::CFRelease(source);
+            ffi::CFRelease(tap);
+        }
+        HOOK_RUNNING.store(false, Ordering::Relaxed);
+    });
+
+    rx.recv().unwrap_or_else(|_| {
+        Err(PttError::new(
+            "threadPanicked",
+            "the event tap thread crashed while starting",
+        ))
+    })
+}
+
+/// Stop the tap thread's run loop; the thread cleans up after itself.
+pub(super) fn stop() {
+    let run_loop = RUN_LOOP.load(Ordering::Relaxed);
+    if run_loop != 0 {
+        unsafe { ffi::CFRunLoopStop(run_loop as *mut c_void) };
+    }
+}
+
+/// Whether the process may observe keyboard events. With `prompt`, shows the
+/// system permission dialog when it may not.
+pub(super) fn is_trusted(prompt: bool) -> bool {
+    if !prompt {
+        let preflight: Option<ffi::ListenEventAccess> =
+            unsafe { ffi::symbol(c"CGPreflightListenEventAccess") };
+        return match preflight {
+            Some(preflight) => unsafe { preflight() },
+            None => unsafe { ffi::AXIsProcessTrusted() },
+        };
+    }
+    let request: Option<ffi::ListenEventAccess> =
+        unsafe { ffi::symbol(c"CGRequestListenEventAccess") };
+    if request.is_some_and(|request| unsafe { request() }) {
+        return true;
+    }
+
+    // Pre-10.15 systems gate taps on Accessibility instead; asking for it
+    // also lists the app in that pane, which some users look for first.
+    unsafe {
+        let keys = [ffi::kAXTrustedCheckOptionPrompt];
+        let values = [ffi::kCFBooleanTrue];
+        let options = ffi::CFDictionaryCreate(
+            std::ptr::null(),
+            keys.as_ptr(),
+            values.as_ptr(),
+            1,
+            &ffi::kCFTypeDictionaryKeyCallBacks,
+            &ffi::kCFTypeDictionaryValueCallBacks,
+        );
+        let trusted = ffi::AXIsProcessTrustedWithOptions(options);
+        if !options.is_null() {
+            ffi::CFRelease(options);
+        }
+        trusted
+    }
+}
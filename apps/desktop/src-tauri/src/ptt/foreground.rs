@@ -0,0 +1,173 @@
+// ===========================================================================
+// Foreground process check (Windows)
+// ===========================================================================
+//
+// `start_ptt_hook`'s `foreground_process` restricts activation to presses
+// made while a given program (usually the game) is focused, so PTT doesn't
+// open the mic while the same key is typed into a browser or chat window.
+//
+// Only presses are checked — a release always goes through, so switching
+// away mid-sentence still stops transmit. Looking up the foreground process
+// (`GetForegroundWindow` → owning process id → image path) is too slow for
+// the hook callback, which Windows drops if it overruns
+// `LowLevelHooksTimeout`. A watcher thread does it instead, whenever the
+// foreground window changes (`EVENT_SYSTEM_FOREGROUND`) and when the filter
+// is set, and the callback only reads the result from `MATCHES`. The path
+// of the last process seen is cached by pid, since opening a process handle
+// is the expensive part.
+// ===========================================================================
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, Once};
+
+use super::{win32, PTT_FOREGROUND};
+
+#[allow(clippy::upper_case_acronyms)]
+mod ffi {
+    pub const PROCESS_QUERY_LIMITED_INFORMATION: u32 = 0x1000;
+    pub const EVENT_SYSTEM_FOREGROUND: u32 = 0x0003;
+    pub const WINEVENT_OUTOFCONTEXT: u32 = 0x0000;
+
+    pub type WinEventProc = unsafe extern "system" fn(isize, u32, isize, i32, i32, u32, u32);
+
+    extern "system" {
+        pub fn SetWinEventHook(
+            event_min: u32,
+            event_max: u32,
+            hmod: isize,
+            callback: WinEventProc,
+            process_id: u32,
+            thread_id: u32,
+            flags: u32,
+        ) -> isize;
+        pub fn GetForegroundWindow() -> isize;
+        pub fn GetWindowThreadProcessId(hwnd: isize, process_id: *mut u32) -> u32;
+        pub fn OpenProcess(desired_access: u32, inherit_handle: i32, process_id: u32) -> isize;
+        pub fn QueryFullProcessImageNameW(
+            process: isize,
+            flags: u32,
+            exe_name: *mut u16,
+            size: *mut u32,
+        ) -> i32;
+        pub fn CloseHandle(handle: isize) -> i32;
+    }
+}
+
+/// Last (pid, image path) looked up.
+static LAST: Mutex<Option<(u32, String)>> = Mutex::new(None);
+
+/// Whether the foreground process matches `PTT_FOREGROUND`, as of the last
+/// foreground change. Read by the hook callback.
+static MATCHES: AtomicBool = AtomicBool::new(false);
+
+static WATCHER: Once = Once::new();
+
+/// Image path of the process owning the foreground window.
+fn foreground_path() -> Option<String> {
+    let hwnd = unsafe { ffi::GetForegroundWindow() };
+    if hwnd == 0 {
+        return None;
+    }
+    let mut pid = 0u32;
+    unsafe { ffi::GetWindowThreadProcessId(hwnd, &mut pid) };
+    if pid == 0 {
+        return None;
+    }
+
+    let mut last = LAST.lock().unwrap();
+    if let Some((cached_pid, path)) = last.as_ref() {
+        if *cached_pid == pid {
+            return Some(path.clone());
+        }
+    }
+
+    let process = unsafe { ffi::OpenProcess(ffi::PROCESS_QUERY_LIMITED_INFORMATION, 0, pid) };
+    if process == 0 {
+        return None;
+    }
+    let mut buf = [0u16; 1024];
+    let mut len = buf.len() as u32;
+    let ok = unsafe { ffi::QueryFullProcessImageNameW(process, 0, buf.as_mut_ptr(), &mut len) };
+    unsafe { ffi::CloseHandle(process) };
+    if ok == 0 {
+        return None;
+    }
+    let path = String::from_utf16_lossy(&buf[..len as usize]);
+    *last = Some((pid, path.clone()));
+    Some(path)
+}
+
+/// Whether the foreground process is `wanted`: a full image path, or just
+/// the executable's file name (case-insensitive either way).
+fn is_foreground(wanted: &str) -> bool {
+    let Some(path) = foreground_path() else {
+        return false;
+    };
+    if wanted.contains(['\\', '/']) {
+        return path.eq_ignore_ascii_case(wanted);
+    }
+    path.rsplit('\\')
+        .next()
+        .is_some_and(|name| name.eq_ignore_ascii_case(wanted))
+}
+
+/// Re-evaluate `MATCHES` against the current foreground window.
+pub(super) fn refresh() {
+    let wanted = PTT_FOREGROUND.lock().unwrap().clone();
+    let matches = wanted.is_some_and(|wanted| is_foreground(&wanted));
+    MATCHES.store(matches, Ordering::Relaxed);
+}
+
+/// Whether the foreground process matched at the last change. Lock-free,
+/// for the hook callback.
+pub(super) fn matches() -> bool {
+    MATCHES.load(Ordering::Relaxed)
+}
+
+unsafe extern "system" fn on_foreground_changed(
+    _hook: isize,
+    _event: u32,
+    _hwnd: isize,
+    _object: i32,
+    _child: i32,
+    _thread: u32,
+    _time: u32,
+) {
+    refresh();
+}
+
+/// Start the thread that keeps `MATCHES` current, if it isn't running.
+pub(super) fn ensure_watching() {
+    WATCHER.call_once(|| {
+        std::thread::spawn(|| {
+            // Out-of-context events are delivered to this thread while it
+            // waits in `GetMessageW`.
+            let hook = unsafe {
+                ffi::SetWinEventHook(
+                    ffi::EVENT_SYSTEM_FOREGROUND,
+                    ffi::EVENT_SYSTEM_FOREGROUND,
+                    0,
+                    on_foreground_changed,
+                    0,
+                    0,
+                    ffi::WINEVENT_OUTOFCONTEXT,
+                )
+            };
+            if hook == 0 {
+                return;
+            }
+            let mut msg = win32::MSG {
+                hwnd: 0,
+                message: 0,
+                w_param: 0,
+                l_param: 0,
+                time: 0,
+                pt_x: 0,
+                pt_y: 0,
+            };
+            while unsafe { win32::GetMessageW(&mut msg, 0, 0, 0) } > 0 {
+                unsafe { win32::DispatchMessageW(&msg) };
+            }
+        });
+    });
+}
@@ -0,0 +1,185 @@
+// ===========================================================================
+// XInput gamepad backend (Windows)
+// ===========================================================================
+//
+// Controller buttons bind like keys: they use the Windows `VK_GAMEPAD_*`
+// virtual-key codes, so PTT, push-to-mute and every other keybind accept
+// them unchanged and key capture reports them. Transitions go through the
+// same `observe_key` / `on_main_key` entry points as the keyboard hook.
+//
+// XInput has no event API, so a thread polls `XInputGetState` for the four
+// controller slots. It only polls quickly while a gamepad button is bound
+// or a key capture is pending; otherwise it idles. Querying an empty slot
+// is comparatively slow, so disconnected slots are re-probed only every
+// couple of seconds.
+//
+// Triggers count as pressed past `XINPUT_GAMEPAD_TRIGGER_THRESHOLD`. The
+// thumbsticks' directional VKs are not mapped — stick deflection makes a
+// poor hold-to-talk key.
+//
+// On Linux gamepads are ordinary evdev devices and are handled by `evdev`.
+// macOS would need the GameController framework, which has no C API.
+// ===========================================================================
+
+use std::sync::atomic::Ordering;
+use std::sync::Once;
+use std::time::{Duration, Instant};
+
+use super::{keybinds, observe_key, on_main_key, CAPTURING, PTT_VK};
+
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+mod ffi {
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct XINPUT_GAMEPAD {
+        pub w_buttons: u16,
+        pub b_left_trigger: u8,
+        pub b_right_trigger: u8,
+        pub s_thumb_lx: i16,
+        pub s_thumb_ly: i16,
+        pub s_thumb_rx: i16,
+        pub s_thumb_ry: i16,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct XINPUT_STATE {
+        pub dw_packet_number: u32,
+        pub gamepad: XINPUT_GAMEPAD,
+    }
+
+    pub const ERROR_SUCCESS: u32 = 0;
+    pub const XUSER_MAX_COUNT: u32 = 4;
+    pub const XINPUT_GAMEPAD_TRIGGER_THRESHOLD: u8 = 30;
+
+    #[link(name = "xinput")]
+    extern "system" {
+        pub fn XInputGetState(user_index: u32, state: *mut XINPUT_STATE) -> u32;
+    }
+}
+
+/// `XINPUT_GAMEPAD_*` button bit -> `VK_GAMEPAD_*`.
+const BUTTONS: [(u16, i32); 14] = [
+    (0x0001, 0xCB), // D-pad up
+    (0x0002, 0xCC), // D-pad down
+    (0x0004, 0xCD), // D-pad left
+    (0x0008, 0xCE), // D-pad right
+    (0x0010, 0xCF), // Start -> Menu
+    (0x0020, 0xD0), // Back -> View
+    (0x0040, 0xD1), // Left stick press
+    (0x0080, 0xD2), // Right stick press
+    (0x0100, 0xC8), // Left shoulder
+    (0x0200, 0xC7), // Right shoulder
+    (0x1000, 0xC3), // A
+    (0x2000, 0xC4), // B
+    (0x4000, 0xC5), // X
+    (0x8000, 0xC6), // Y
+];
+
+/// Bits above the 16 button bits stand in for the analog triggers.
+const LEFT_TRIGGER: u32 = 1 << 16;
+const RIGHT_TRIGGER: u32 = 1 << 17;
+const VK_GAMEPAD_LEFT_TRIGGER: i32 = 0xC9;
+const VK_GAMEPAD_RIGHT_TRIGGER: i32 = 0xCA;
+
+const ACTIVE_POLL: Duration = Duration::from_millis(8);
+const IDLE_POLL: Duration = Duration::from_millis(250);
+const RESCAN_INTERVAL: Duration = Duration::from_secs(2);
+
+static POLLER: Once = Once::new();
+
+/// Whether a virtual-key code is a gamepad button.
+pub(super) fn is_gamepad_vk(vk: i32) -> bool {
+    matches!(vk, 0xC3..=0xDA)
+}
+
+/// Whether anything currently needs the controllers polled.
+fn wanted() -> bool {
+    is_gamepad_vk(PTT_VK.load(Ordering::Relaxed))
+        || CAPTURING.load(Ordering::Relaxed)
+        || keybinds::binds_any(is_gamepad_vk)
+}
+
+/// Held buttons of one controller as a bit set, or `None` if the slot is
+/// empty.
+fn read_slot(slot: u32) -> Option<u32> {
+    let mut state = ffi::XINPUT_STATE::default();
+    if unsafe { ffi::XInputGetState(slot, &mut state) } != ffi::ERROR_SUCCESS {
+        return None;
+    }
+    let pad = &state.gamepad;
+    let mut held = u32::from(pad.w_buttons);
+    if pad.b_left_trigger > ffi::XINPUT_GAMEPAD_TRIGGER_THRESHOLD {
+        held |= LEFT_TRIGGER;
+    }
+    if pad.b_right_trigger > ffi::XINPUT_GAMEPAD_TRIGGER_THRESHOLD {
+        held |= RIGHT_TRIGGER;
+    }
+    Some(held)
+}
+
+/// Feed the buttons that changed between `before` and `after`.
+fn emit_changes(before: u32, after: u32) {
+    let ptt = PTT_VK.load(Ordering::Relaxed);
+    let buttons = BUTTONS.iter().map(|&(bit, vk)| (u32::from(bit), vk));
+    let triggers = [
+        (LEFT_TRIGGER, VK_GAMEPAD_LEFT_TRIGGER),
+        (RIGHT_TRIGGER, VK_GAMEPAD_RIGHT_TRIGGER),
+    ];
+    for (bit, vk) in buttons.chain(triggers) {
+        if (before ^ after) & bit == 0 {
+            continue;
+        }
+        let down = after & bit != 0;
+        observe_key(vk, 0, down);
+        if vk == ptt {
+            on_main_key(down);
+        }
+    }
+}
+
+/// Poll loop. Runs for the life of the process once started.
+fn poll() {
+    // Per-slot held buttons; `None` = disconnected.
+    let mut slots: [Option<u32>; ffi::XUSER_MAX_COUNT as usize] = [None; 4];
+    // Union over all controllers, as last reported.
+    let mut held = 0u32;
+    let mut last_scan: Option<Instant> = None;
+
+    loop {
+        if !wanted() {
+            // Nothing bound: forget the state so a later binding doesn't
+            // see stale presses as releases.
+            slots = [None; 4];
+            held = 0;
+            last_scan = None;
+            std::thread::sleep(IDLE_POLL);
+            continue;
+        }
+
+        let rescan = last_scan.is_none_or(|at| at.elapsed() >= RESCAN_INTERVAL);
+        if rescan {
+            last_scan = Some(Instant::now());
+        }
+        for (index, slot) in slots.iter_mut().enumerate() {
+            if slot.is_some() || rescan {
+                *slot = read_slot(index as u32);
+            }
+        }
+
+        let now = slots.iter().flatten().fold(0, |all, buttons| all | buttons);
+        if now != held {
+            emit_changes(held, now);
+            held = now;
+        }
+        std::thread::sleep(ACTIVE_POLL);
+    }
+}
+
+/// Start the poll thread if it isn't running. It idles until a gamepad
+/// button is bound.
+pub(super) fn ensure_running() {
+    POLLER.call_once(|| {
+        std::thread::spawn(poll);
+    });
+}
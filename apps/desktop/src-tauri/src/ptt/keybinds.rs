@@ -0,0 +1,447 @@
+// ===========================================================================
+// Global keybind registry
+// ===========================================================================
+//
+// Bindings for actions other than PTT — toggle mute, toggle deafen, overlay,
+// answer/decline call — served by the same hook thread and backends. The
+// frontend registers `register_keybind(action, keyCode, modifiers,
+// scanCode, gestures, consume)` and listens for `keybind-down` /
+// `keybind-up`, whose payload is the action id.
+//
+// Chords behave like the PTT chord: down fires once the key and every
+// required modifier are held, in any order; up fires when any part is
+// released. Unlike PTT there is no activation mode or release delay —
+// actions are edge-triggered and the frontend decides what a press means.
+//
+// A binding registered with `consume` swallows its key while the chord is
+// held, the same way PTT's `suppress` does, so e.g. a play/pause key mapped
+// to mute doesn't also pause music or pop up the OS media overlay. Windows
+// only, like `suppress`.
+//
+// Gestures: an action registered with `gestures` also emits
+// `keybind-gesture` ({ action, kind }) when the hook sees
+//   - "doubleTap": a second press starting within 300 ms of the first
+//   - "hold":      a press still held after 400 ms (not the second press of
+//                  a double tap)
+//   - "holdEnd":   the release of a press reported as "hold"
+// so one key can, say, toggle deafen on double tap and transmit while held.
+// The timing is done here rather than in the webview, whose timers are
+// throttled while it's in the background. Plain `keybind-down/up` still
+// fire for every press.
+//
+// Push-to-mute, push-to-deafen and priority speaker live here too, as fixed
+// slots rather than named actions: they emit their own `ptm-hook-*`,
+// `ptd-hook-*` and `ptt-priority-*` events so the voice code can treat them
+// like PTT, and they don't show up in `list_keybinds`.
+//
+// PTT keeps its own dedicated state (`PTT_VK` and friends) because the hook
+// resolves its activation natively; a keybind may share PTT's key.
+// ===========================================================================
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::Emitter;
+
+use super::{modifier_names, now_ms, HELD_MODIFIERS, MOD_ALT, MOD_CTRL, MOD_META, MOD_SHIFT};
+use crate::{scheduler, APP_HANDLE};
+
+/// Longest gap between the starts of two presses that makes a double tap.
+const DOUBLE_TAP_MS: u64 = 300;
+
+/// How long a press must last to count as a hold.
+const HOLD_MS: u64 = 400;
+
+/// Scheduler key prefix for the hold check; the action id follows.
+const HOLD_KEY_PREFIX: &str = "keybind-hold:";
+
+/// What a binding drives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Slot {
+    /// A frontend-defined action; emits `keybind-down/up` with its id.
+    Action,
+    /// Mute while held.
+    PushToMute,
+    /// Deafen while held.
+    PushToDeafen,
+    /// Transmit as priority speaker while held.
+    PrioritySpeaker,
+}
+
+impl Slot {
+    /// Down/up event names.
+    fn events(self) -> (&'static str, &'static str) {
+        match self {
+            Slot::Action => ("keybind-down", "keybind-up"),
+            Slot::PushToMute => ("ptm-hook-down", "ptm-hook-up"),
+            Slot::PushToDeafen => ("ptd-hook-down", "ptd-hook-up"),
+            Slot::PrioritySpeaker => ("ptt-priority-down", "ptt-priority-up"),
+        }
+    }
+
+    /// Name reported by `check_keybind_conflicts` for a fixed slot.
+    fn name(self) -> &'static str {
+        match self {
+            Slot::Action => "",
+            Slot::PushToMute => "pushToMute",
+            Slot::PushToDeafen => "pushToDeafen",
+            Slot::PrioritySpeaker => "prioritySpeaker",
+        }
+    }
+
+    /// The fixed slot called `name`, as `keybinds.json` spells it.
+    pub(super) fn from_name(name: &str) -> Option<Slot> {
+        [Slot::PushToMute, Slot::PushToDeafen, Slot::PrioritySpeaker]
+            .into_iter()
+            .find(|slot| slot.name() == name)
+    }
+}
+
+struct Keybind {
+    slot: Slot,
+    /// Action id; empty for the fixed slots.
+    action: String,
+    vk: i32,
+    /// Scan code matched instead of `vk`; 0 = match the virtual key.
+    scan: u32,
+    /// Required modifier groups (`MOD_*`).
+    modifiers: u8,
+    /// Whether the bound key itself is down.
+    key_held: bool,
+    /// Whether the down event was emitted and the up event is still owed.
+    active: bool,
+    /// Whether `keybind-gesture` events are emitted for this binding.
+    gestures: bool,
+    /// Whether the bound key is swallowed while the chord is held.
+    consume: bool,
+    /// Whether the last press was swallowed, so its release is too.
+    swallowed: bool,
+    /// When the last press started (monotonic ms), while a second press
+    /// could still make it a double tap.
+    last_press_ms: Option<u64>,
+    /// Whether the current press can still become a hold.
+    hold_pending: bool,
+    /// Whether the current press was reported as a hold.
+    holding: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum GestureKind {
+    DoubleTap,
+    Hold,
+    HoldEnd,
+}
+
+/// `keybind-gesture` payload.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct KeybindGesture {
+    pub action: String,
+    pub kind: GestureKind,
+}
+
+/// A registered binding, as reported by `list_keybinds`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct KeybindInfo {
+    pub action: String,
+    pub key_code: i32,
+    pub scan_code: Option<u32>,
+    pub modifiers: Vec<String>,
+    pub gestures: bool,
+    pub consume: bool,
+}
+
+static KEYBINDS: Mutex<Vec<Keybind>> = Mutex::new(Vec::new());
+
+/// Fast-path mirror of `!KEYBINDS.is_empty()` for the hook callbacks.
+static ANY_KEYBINDS: AtomicBool = AtomicBool::new(false);
+
+/// Keys some binding may match, so the hook callbacks only lock `KEYBINDS`
+/// for those: bit `vk & 0xFF` for a virtual-key binding, `256 + (scan &
+/// 0xFF)` for a scan-code one. A collision just costs a lock.
+static BOUND_KEYS: [AtomicU64; 8] = [const { AtomicU64::new(0) }; 8];
+
+/// Whether some binding's key is down, i.e. a modifier change could
+/// complete or break a chord.
+static ANY_KEY_HELD: AtomicBool = AtomicBool::new(false);
+
+fn bound_key_bit(bind: &Keybind) -> usize {
+    match bind.scan {
+        0 => (bind.vk & 0xFF) as usize,
+        scan => 256 + (scan & 0xFF) as usize,
+    }
+}
+
+fn may_be_bound(vk: i32, scan: u32) -> bool {
+    let test = |bit: usize| BOUND_KEYS[bit / 64].load(Ordering::Relaxed) & (1 << (bit % 64)) != 0;
+    test((vk & 0xFF) as usize) || test(256 + (scan & 0xFF) as usize)
+}
+
+/// Republish `BOUND_KEYS` and the other mirrors after `binds` changed.
+fn publish(binds: &[Keybind]) {
+    let mut bits = [0u64; 8];
+    for bind in binds {
+        let bit = bound_key_bit(bind);
+        bits[bit / 64] |= 1 << (bit % 64);
+    }
+    for (word, bits) in BOUND_KEYS.iter().zip(bits) {
+        word.store(bits, Ordering::Relaxed);
+    }
+    ANY_KEYBINDS.store(!binds.is_empty(), Ordering::Relaxed);
+    ANY_KEY_HELD.store(binds.iter().any(|bind| bind.key_held), Ordering::Relaxed);
+}
+
+/// Whether pressing one chord would also fire the other: same key, and one
+/// chord's modifier groups are a subset of the other's (extra modifiers
+/// don't stop a binding from firing).
+pub(super) fn chords_overlap(vk_a: i32, mods_a: u8, vk_b: i32, mods_b: u8) -> bool {
+    vk_a == vk_b && (mods_a & !mods_b == 0 || mods_b & !mods_a == 0)
+}
+
+/// Whether `held` satisfies every modifier group in `required`.
+fn modifiers_held(required: u8, held: u8) -> bool {
+    [MOD_CTRL, MOD_SHIFT, MOD_ALT, MOD_META]
+        .iter()
+        .all(|&group| required & group == 0 || held & group != 0)
+}
+
+/// Recompute every binding against the current key and modifier state and
+/// emit the resulting transitions. `key` is the (vk, scan, down) transition
+/// that triggered the refresh, if any. Returns whether a `consume` binding
+/// claims that transition, i.e. the backend should swallow it.
+fn refresh_with(key: Option<(i32, u32, bool)>) -> bool {
+    // Runs on every keystroke, so it only locks when something can change:
+    // a transition of a bound key, or a modifier change while one is held.
+    let relevant = match key {
+        Some((vk, scan, _)) => may_be_bound(vk, scan),
+        None => ANY_KEY_HELD.load(Ordering::Relaxed),
+    };
+    if !relevant {
+        return false;
+    }
+    let held = HELD_MODIFIERS.load(Ordering::Relaxed);
+
+    let mut events = Vec::new();
+    let mut gestures = Vec::new();
+    let mut consumed = false;
+    let mut binds = KEYBINDS.lock().unwrap();
+    for bind in binds.iter_mut() {
+        let mut transition = None;
+        if let Some((vk, scan, down)) = key {
+            let matches = match bind.scan {
+                0 => bind.vk == vk,
+                bound => bound == scan,
+            };
+            if matches {
+                bind.key_held = down;
+                transition = Some(down);
+            }
+        }
+        let active = bind.key_held && modifiers_held(bind.modifiers, held);
+        // Like PTT's `suppress`: a press is swallowed only while the whole
+        // chord is held, and a release only if its press was.
+        if let (true, Some(down)) = (bind.consume, transition) {
+            let swallow = if down {
+                bind.swallowed = active;
+                active
+            } else {
+                std::mem::take(&mut bind.swallowed)
+            };
+            consumed |= swallow;
+        }
+        if active != bind.active {
+            bind.active = active;
+            let (down, up) = bind.slot.events();
+            events.push((
+                bind.slot,
+                if active { down } else { up },
+                bind.action.clone(),
+            ));
+            if bind.gestures {
+                gestures.extend(track_gesture(bind, active));
+            }
+        }
+    }
+    ANY_KEY_HELD.store(binds.iter().any(|bind| bind.key_held), Ordering::Relaxed);
+    drop(binds);
+
+    if let Some(handle) = APP_HANDLE.get() {
+        for (slot, event, action) in events {
+            let _ = match slot {
+                Slot::Action => handle.emit(event, action),
+                _ => handle.emit(event, ()),
+            };
+        }
+        for gesture in gestures {
+            let _ = handle.emit("keybind-gesture", gesture);
+        }
+    }
+    consumed
+}
+
+/// Advance a gesture-tracking binding's tap state on a press (`active`) or
+/// release, returning the gesture it completes, if any.
+fn track_gesture(bind: &mut Keybind, active: bool) -> Option<KeybindGesture> {
+    let gesture = |kind| {
+        Some(KeybindGesture {
+            action: bind.action.clone(),
+            kind,
+        })
+    };
+
+    if !active {
+        bind.hold_pending = false;
+        if !std::mem::take(&mut bind.holding) {
+            return None;
+        }
+        return gesture(GestureKind::HoldEnd);
+    }
+
+    let now = now_ms();
+    if bind
+        .last_press_ms
+        .is_some_and(|at| now.saturating_sub(at) <= DOUBLE_TAP_MS)
+    {
+        // A third quick press starts a new pair rather than another double.
+        bind.last_press_ms = None;
+        return gesture(GestureKind::DoubleTap);
+    }
+    bind.last_press_ms = Some(now);
+    bind.hold_pending = true;
+    scheduler::schedule(
+        format!("{HOLD_KEY_PREFIX}{}", bind.action),
+        scheduler::now_ms() + HOLD_MS,
+        check_hold,
+    );
+    None
+}
+
+/// Scheduler task: the press that armed it is still down, so it's a hold.
+/// A later press re-arms the task under the same key, and a release or
+/// double tap clears `hold_pending`, so a stale run finds nothing to do.
+fn check_hold(key: &str) {
+    let Some(action) = key.strip_prefix(HOLD_KEY_PREFIX) else {
+        return;
+    };
+    let held = KEYBINDS.lock().unwrap().iter_mut().any(|bind| {
+        let hold =
+            bind.slot == Slot::Action && bind.action == action && bind.active && bind.hold_pending;
+        if hold {
+            bind.hold_pending = false;
+            bind.holding = true;
+        }
+        hold
+    });
+    if let (true, Some(handle)) = (held, APP_HANDLE.get()) {
+        let _ = handle.emit(
+            "keybind-gesture",
+            KeybindGesture {
+                action: action.to_string(),
+                kind: GestureKind::Hold,
+            },
+        );
+    }
+}
+
+/// A physical key or button transition. Returns whether to swallow it.
+pub(super) fn on_key(vk: i32, scan: u32, down: bool) -> bool {
+    refresh_with(Some((vk, scan, down)))
+}
+
+/// The held modifier set changed.
+pub(super) fn refresh() {
+    refresh_with(None);
+}
+
+/// Whether any binding uses a key matching `is_kind` (e.g. a mouse or
+/// gamepad button), so the backend serving that kind is needed.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub(super) fn binds_any(is_kind: fn(i32) -> bool) -> bool {
+    ANY_KEYBINDS.load(Ordering::Relaxed)
+        && KEYBINDS.lock().unwrap().iter().any(|bind| is_kind(bind.vk))
+}
+
+pub(super) fn is_empty() -> bool {
+    !ANY_KEYBINDS.load(Ordering::Relaxed)
+}
+
+/// Add or replace the binding for `action` in `slot`.
+pub(super) fn register(
+    slot: Slot,
+    action: String,
+    vk: i32,
+    scan: u32,
+    modifiers: u8,
+    gestures: bool,
+    consume: bool,
+) {
+    let mut binds = KEYBINDS.lock().unwrap();
+    binds.retain(|bind| !(bind.slot == slot && bind.action == action));
+    binds.push(Keybind {
+        slot,
+        action,
+        vk,
+        scan,
+        modifiers,
+        key_held: false,
+        active: false,
+        gestures,
+        consume,
+        swallowed: false,
+        last_press_ms: None,
+        hold_pending: false,
+        holding: false,
+    });
+    publish(&binds);
+}
+
+/// Remove the binding for `action` in `slot`. Returns whether one existed.
+pub(super) fn unregister(slot: Slot, action: &str) -> bool {
+    let mut binds = KEYBINDS.lock().unwrap();
+    let before = binds.len();
+    binds.retain(|bind| !(bind.slot == slot && bind.action == action));
+    publish(&binds);
+    binds.len() != before
+}
+
+/// Bindings that overlap the chord `vk` + `modifiers`, as action ids or
+/// fixed-slot names, leaving out the action `except`.
+pub(super) fn conflicts(vk: i32, modifiers: u8, except: Option<&str>) -> Vec<String> {
+    KEYBINDS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|bind| chords_overlap(bind.vk, bind.modifiers, vk, modifiers))
+        .map(|bind| match bind.slot {
+            Slot::Action => bind.action.clone(),
+            slot => slot.name().to_string(),
+        })
+        .filter(|name| except != Some(name.as_str()))
+        .collect()
+}
+
+/// Remove every binding without emitting anything.
+pub(super) fn clear() {
+    let mut binds = KEYBINDS.lock().unwrap();
+    binds.clear();
+    publish(&binds);
+}
+
+pub(super) fn list() -> Vec<KeybindInfo> {
+    KEYBINDS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|bind| bind.slot == Slot::Action)
+        .map(|bind| KeybindInfo {
+            action: bind.action.clone(),
+            key_code: bind.vk,
+            scan_code: (bind.scan != 0).then_some(bind.scan),
+            modifiers: modifier_names(bind.modifiers),
+            gestures: bind.gestures,
+            consume: bind.consume,
+        })
+        .collect()
+}
@@ -0,0 +1,218 @@
+// ===========================================================================
+// keybinds.json hot reload
+// ===========================================================================
+//
+// Power users can keep global keybinds in `keybinds.json` in the app config
+// directory (`%APPDATA%\gg.ripcord.desktop` on Windows, `~/Library/
+// Application Support/gg.ripcord.desktop` on macOS,
+// `~/.config/gg.ripcord.desktop` on Linux) and manage it with their
+// dotfiles. The file is a list of bindings in the shape `list_keybinds`
+// reports, i.e. `register_keybind`'s arguments:
+//
+//   [
+//     { "action": "toggleMute", "keyCode": 77, "modifiers": ["ctrl", "shift"] },
+//     { "action": "toggleDeafen", "keyCode": 179, "consume": true },
+//     { "action": "pushToMute", "keyCode": 5 }
+//   ]
+//
+// `pushToMute`, `pushToDeafen` and `prioritySpeaker` bind those fixed slots;
+// any other action id is a frontend action. PTT is not configured here.
+//
+// The file is applied at startup and re-checked every couple of seconds on
+// the scheduler. A new version is applied over the registry: each entry
+// replaces the binding for its action, and actions the previous version
+// bound but this one doesn't are unbound. Bindings the frontend registered
+// for other actions are left alone, and a `register_keybind` for one of the
+// file's actions replaces it until the file next changes.
+//
+// Every reload emits `keybinds-reloaded` with the resulting `list_keybinds`
+// and an error per entry that couldn't be bound. A file that doesn't parse
+// (say, saved half-way through an edit) is reported the same way and
+// changes nothing.
+//
+// A webview reload clears the registry (`ptt::reset`); the file is then
+// applied again as if at startup.
+//
+// Guest sessions don't read the file — it belongs to the computer's owner.
+// ===========================================================================
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager};
+
+use super::keybinds::{self, KeybindInfo, Slot};
+use super::{bind_slot, parse_modifiers, unbind_slot};
+use crate::{guest, scheduler, APP_HANDLE};
+
+const FILE_NAME: &str = "keybinds.json";
+const CHECK_KEY: &str = "keybinds-file-check";
+const CHECK_INTERVAL_MS: u64 = 2_000;
+
+/// One binding in the file.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileKeybind {
+    action: String,
+    key_code: i32,
+    #[serde(default)]
+    modifiers: Vec<String>,
+    scan_code: Option<u32>,
+    #[serde(default)]
+    gestures: bool,
+    #[serde(default)]
+    consume: bool,
+}
+
+/// `keybinds-reloaded` payload.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct KeybindsReloaded {
+    pub keybinds: Vec<KeybindInfo>,
+    pub errors: Vec<String>,
+}
+
+/// Modification time and size, to notice a changed file cheaply.
+type Stamp = (Option<SystemTime>, u64);
+
+struct FileState {
+    /// The file as last seen; `None` when it didn't exist.
+    stamp: Option<Stamp>,
+    /// Bindings the file made, so a later version can remove them.
+    owned: Vec<(Slot, String)>,
+}
+
+static STATE: Mutex<Option<FileState>> = Mutex::new(None);
+
+fn file_path() -> Option<PathBuf> {
+    if guest::is_guest() {
+        return None;
+    }
+    let dir = APP_HANDLE.get()?.path().app_config_dir().ok()?;
+    Some(dir.join(FILE_NAME))
+}
+
+fn emit_reloaded(errors: Vec<String>) {
+    if let Some(handle) = APP_HANDLE.get() {
+        let _ = handle.emit(
+            "keybinds-reloaded",
+            KeybindsReloaded {
+                keybinds: keybinds::list(),
+                errors,
+            },
+        );
+    }
+}
+
+/// Bind one entry, returning the (slot, action) it now owns.
+fn apply(entry: &FileKeybind) -> Result<(Slot, String), String> {
+    if entry.action.is_empty() {
+        return Err("keybind action id must not be empty".into());
+    }
+    if entry.key_code <= 0 {
+        return Err(format!("invalid key code {}", entry.key_code));
+    }
+    let modifiers =
+        parse_modifiers(&entry.modifiers).ok_or_else(|| "unknown modifier name".to_string())?;
+    let scan_code = entry.scan_code.unwrap_or(0);
+
+    // Fixed slots take neither gestures nor consume, as with
+    // `set_push_to_mute`.
+    let (slot, action, gestures, consume) = match Slot::from_name(&entry.action) {
+        Some(slot) => (slot, String::new(), false, false),
+        None => (
+            Slot::Action,
+            entry.action.clone(),
+            entry.gestures,
+            entry.consume,
+        ),
+    };
+    bind_slot(
+        slot,
+        action.clone(),
+        entry.key_code,
+        scan_code,
+        modifiers,
+        gestures,
+        consume,
+    )?;
+    Ok((slot, action))
+}
+
+/// Apply the file's current contents over the bindings it owned.
+fn reload(path: &Path, owned: &mut Vec<(Slot, String)>) {
+    let entries = match std::fs::read(path) {
+        Ok(bytes) => match serde_json::from_slice::<Vec<FileKeybind>>(&bytes) {
+            Ok(entries) => entries,
+            Err(e) => {
+                emit_reloaded(vec![format!("{FILE_NAME}: {e}")]);
+                return;
+            }
+        },
+        // Deleted: everything it bound goes.
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => {
+            emit_reloaded(vec![format!("{FILE_NAME}: {e}")]);
+            return;
+        }
+    };
+    if entries.is_empty() && owned.is_empty() {
+        return;
+    }
+
+    let mut errors = Vec::new();
+    let mut now_owned = Vec::new();
+    for entry in &entries {
+        match apply(entry) {
+            Ok(binding) => now_owned.push(binding),
+            Err(e) => errors.push(format!("{}: {e}", entry.action)),
+        }
+    }
+    for (slot, action) in owned.drain(..) {
+        if !now_owned.contains(&(slot, action.clone())) {
+            unbind_slot(slot, &action);
+        }
+    }
+    *owned = now_owned;
+    emit_reloaded(errors);
+}
+
+/// Reload if the file changed since the last check, then re-arm.
+fn check(_key: &str) {
+    let Some(path) = file_path() else {
+        return;
+    };
+    let stamp = std::fs::metadata(&path)
+        .ok()
+        .map(|meta| (meta.modified().ok(), meta.len()));
+
+    {
+        let mut guard = STATE.lock().unwrap();
+        let state = guard.get_or_insert_with(|| FileState {
+            stamp: None,
+            owned: Vec::new(),
+        });
+        // Starts out as "no file", so a file present at startup is applied
+        // by the first check.
+        if state.stamp != stamp {
+            state.stamp = stamp;
+            reload(&path, &mut state.owned);
+        }
+    }
+
+    scheduler::schedule(CHECK_KEY, scheduler::now_ms() + CHECK_INTERVAL_MS, check);
+}
+
+/// Apply `keybinds.json` and start watching it.
+pub(super) fn init() {
+    scheduler::schedule(CHECK_KEY, scheduler::now_ms(), check);
+}
+
+/// Forget what the file bound (the registry was just cleared) and apply it
+/// again now.
+pub(super) fn reset() {
+    *STATE.lock().unwrap() = None;
+    scheduler::schedule(CHECK_KEY, scheduler::now_ms(), check);
+}
@@ -0,0 +1,165 @@
+// ===========================================================================
+// PTT event timestamps and latency statistics
+// ===========================================================================
+//
+// "PTT feels laggy" has two very different causes: the OS delivering key
+// events late (a starved hook thread, a busy system) or our own path from
+// the hook to `emit` being slow. Each bound-key transition is stamped when
+// the backend receives it, together with the OS's own event time where the
+// platform reports one:
+//
+//   Windows: `KBDLLHOOKSTRUCT.time` / `MSLLHOOKSTRUCT.time` (`GetTickCount`
+//            milliseconds)
+//   Linux:   the evdev `input_event` timestamp (Unix-epoch microseconds)
+//   macOS:   none; `CGEventTimestamp` units vary by machine
+//
+// When that transition causes `ptt-hook-down` / `ptt-hook-up`, the payload
+// carries the stamp and the time from receipt to emit, and both delays go
+// into a ring of recent samples that `get_ptt_latency_stats` summarizes.
+// Emits not caused by a key (delayed release, safety release, mode switch)
+// carry no OS time and aren't sampled.
+// ===========================================================================
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use serde::Serialize;
+
+use super::EPOCH;
+
+/// Samples kept for the statistics.
+const MAX_SAMPLES: usize = 512;
+
+/// OS timing of one input event, as captured by a backend.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct OsEventTime {
+    /// The platform's own event timestamp.
+    pub time: u64,
+    /// How long ago the OS stamped the event, when the backend received it.
+    pub lag_us: Option<u64>,
+}
+
+/// A bound-key transition waiting to (maybe) cause an emit.
+struct Stamp {
+    received: Instant,
+    os: Option<OsEventTime>,
+}
+
+/// `ptt-hook-down` / `ptt-hook-up` payload.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PttEvent {
+    /// The OS's timestamp for the key event (see the module notes for
+    /// units), if this emit was caused by one.
+    pub time: Option<u64>,
+    /// Monotonic microseconds (process-relative) at which the hook received
+    /// the event, or of the emit itself when no key caused it.
+    pub monotonic_us: u64,
+    /// Microseconds from the hook receiving the event to this emit.
+    pub latency_us: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    hook_to_emit_us: u64,
+    os_to_hook_us: Option<u64>,
+}
+
+/// Percentiles over the recent samples, in microseconds.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Percentiles {
+    pub p50_us: u64,
+    pub p90_us: u64,
+    pub p99_us: u64,
+    pub max_us: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PttLatencyStats {
+    pub samples: usize,
+    /// Hook receipt to `emit`.
+    pub hook_to_emit: Option<Percentiles>,
+    /// OS event timestamp to hook receipt, where the platform reports one.
+    pub os_to_hook: Option<Percentiles>,
+}
+
+static PENDING: Mutex<Option<Stamp>> = Mutex::new(None);
+static SAMPLES: Mutex<VecDeque<Sample>> = Mutex::new(VecDeque::new());
+
+fn micros_since_epoch(at: Instant) -> u64 {
+    at.saturating_duration_since(*EPOCH.get_or_init(Instant::now))
+        .as_micros() as u64
+}
+
+/// A backend received a bound-key transition.
+pub(super) fn mark(os: Option<OsEventTime>) {
+    *PENDING.lock().unwrap() = Some(Stamp {
+        received: Instant::now(),
+        os,
+    });
+}
+
+/// The transition has been processed; whatever it didn't emit is not
+/// attributed to it.
+pub(super) fn clear() {
+    *PENDING.lock().unwrap() = None;
+}
+
+/// Payload for an emit happening now, recording a sample if a key caused it.
+pub(super) fn event_payload() -> PttEvent {
+    let now = Instant::now();
+    let Some(stamp) = PENDING.lock().unwrap().take() else {
+        return PttEvent {
+            time: None,
+            monotonic_us: micros_since_epoch(now),
+            latency_us: None,
+        };
+    };
+
+    let latency = now.saturating_duration_since(stamp.received).as_micros() as u64;
+    let mut samples = SAMPLES.lock().unwrap();
+    if samples.len() == MAX_SAMPLES {
+        samples.pop_front();
+    }
+    samples.push_back(Sample {
+        hook_to_emit_us: latency,
+        os_to_hook_us: stamp.os.and_then(|os| os.lag_us),
+    });
+
+    PttEvent {
+        time: stamp.os.map(|os| os.time),
+        monotonic_us: micros_since_epoch(stamp.received),
+        latency_us: Some(latency),
+    }
+}
+
+fn percentiles(mut values: Vec<u64>) -> Option<Percentiles> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    let at = |p: usize| values[(values.len() - 1) * p / 100];
+    Some(Percentiles {
+        p50_us: at(50),
+        p90_us: at(90),
+        p99_us: at(99),
+        max_us: values[values.len() - 1],
+    })
+}
+
+/// Summarize the recent samples, optionally starting over afterwards.
+pub(super) fn stats(reset: bool) -> PttLatencyStats {
+    let mut samples = SAMPLES.lock().unwrap();
+    let stats = PttLatencyStats {
+        samples: samples.len(),
+        hook_to_emit: percentiles(samples.iter().map(|s| s.hook_to_emit_us).collect()),
+        os_to_hook: percentiles(samples.iter().filter_map(|s| s.os_to_hook_us).collect()),
+    };
+    if reset {
+        samples.clear();
+    }
+    stats
+}
@@ -0,0 +1,251 @@
+// ===========================================================================
+// Raw Input device identification (Windows)
+// ===========================================================================
+//
+// The low-level keyboard hook cannot tell which keyboard produced a key —
+// `KBDLLHOOKSTRUCT` has no device field. Raw Input (`WM_INPUT`) does: every
+// report carries the `hDevice` of the originating HID, which resolves to a
+// stable device path such as `\\?\HID#VID_1B1C&PID_1B3D&MI_00#...`.
+//
+// The hook thread also owns a message-only window registered as a keyboard
+// raw-input sink (`RIDEV_INPUTSINK`, so it receives input while
+// backgrounded). When a device filter is set, PTT transitions are driven
+// from `WM_INPUT` for the filtered device only, and the LL hook ignores the
+// PTT key. Like the hook, raw input only observes — the key still reaches the
+// focused application, so the same key on another keyboard keeps typing.
+// ===========================================================================
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use super::InputDevice;
+
+#[allow(clippy::upper_case_acronyms)]
+mod ffi {
+    pub const WM_INPUT: u32 = 0x00FF;
+    pub const HWND_MESSAGE: isize = -3;
+    pub const RIDEV_INPUTSINK: u32 = 0x0000_0100;
+    pub const RID_INPUT: u32 = 0x1000_0003;
+    pub const RIDI_DEVICENAME: u32 = 0x2000_0007;
+    pub const RIM_TYPEKEYBOARD: u32 = 1;
+    pub const RI_KEY_BREAK: u16 = 0x01;
+    pub const RI_KEY_E0: u16 = 0x02;
+    pub const HID_USAGE_PAGE_GENERIC: u16 = 0x01;
+    pub const HID_USAGE_GENERIC_KEYBOARD: u16 = 0x06;
+
+    #[repr(C)]
+    pub struct RAWINPUTDEVICE {
+        pub usage_page: u16,
+        pub usage: u16,
+        pub flags: u32,
+        pub hwnd_target: isize,
+    }
+
+    #[repr(C)]
+    pub struct RAWINPUTDEVICELIST {
+        pub h_device: isize,
+        pub dw_type: u32,
+    }
+
+    #[repr(C)]
+    pub struct RAWINPUTHEADER {
+        pub dw_type: u32,
+        pub dw_size: u32,
+        pub h_device: isize,
+        pub w_param: usize,
+    }
+
+    #[repr(C)]
+    pub struct RAWKEYBOARD {
+        pub make_code: u16,
+        pub flags: u16,
+        pub reserved: u16,
+        pub vkey: u16,
+        pub message: u32,
+        pub extra_information: u32,
+    }
+
+    #[repr(C)]
+    pub struct RAWINPUT_KEYBOARD {
+        pub header: RAWINPUTHEADER,
+        pub keyboard: RAWKEYBOARD,
+    }
+
+    extern "system" {
+        pub fn CreateWindowExW(
+            ex_style: u32,
+            class_name: *const u16,
+            window_name: *const u16,
+            style: u32,
+            x: i32,
+            y: i32,
+            width: i32,
+            height: i32,
+            parent: isize,
+            menu: isize,
+            instance: isize,
+            param: *const core::ffi::c_void,
+        ) -> isize;
+        pub fn DestroyWindow(hwnd: isize) -> i32;
+        pub fn RegisterRawInputDevices(
+            devices: *const RAWINPUTDEVICE,
+            num_devices: u32,
+            size: u32,
+        ) -> i32;
+        pub fn GetRawInputData(
+            raw_input: isize,
+            command: u32,
+            data: *mut core::ffi::c_void,
+            size: *mut u32,
+            header_size: u32,
+        ) -> u32;
+        pub fn GetRawInputDeviceInfoW(
+            device: isize,
+            command: u32,
+            data: *mut core::ffi::c_void,
+            size: *mut u32,
+        ) -> u32;
+        pub fn GetRawInputDeviceList(
+            list: *mut RAWINPUTDEVICELIST,
+            num_devices: *mut u32,
+            size: u32,
+        ) -> u32;
+    }
+}
+
+pub(super) use ffi::WM_INPUT;
+
+thread_local! {
+    /// `hDevice` → device path cache for the hook thread. Handles are stable
+    /// while the device stays plugged in; a replug yields a new handle.
+    static DEVICE_NAMES: RefCell<HashMap<isize, String>> = RefCell::new(HashMap::new());
+}
+
+/// Create a message-only raw-input sink window on the calling thread.
+/// Returns the window handle, or 0 if raw input is unavailable.
+pub(super) fn create_sink() -> isize {
+    let class: Vec<u16> = "STATIC\0".encode_utf16().collect();
+    let hwnd = unsafe {
+        ffi::CreateWindowExW(
+            0,
+            class.as_ptr(),
+            std::ptr::null(),
+            0,
+            0,
+            0,
+            0,
+            0,
+            ffi::HWND_MESSAGE,
+            0,
+            0,
+            std::ptr::null(),
+        )
+    };
+    if hwnd == 0 {
+        return 0;
+    }
+
+    let device = ffi::RAWINPUTDEVICE {
+        usage_page: ffi::HID_USAGE_PAGE_GENERIC,
+        usage: ffi::HID_USAGE_GENERIC_KEYBOARD,
+        flags: ffi::RIDEV_INPUTSINK,
+        hwnd_target: hwnd,
+    };
+    let ok = unsafe {
+        ffi::RegisterRawInputDevices(&device, 1, std::mem::size_of::<ffi::RAWINPUTDEVICE>() as u32)
+    };
+    if ok == 0 {
+        unsafe { ffi::DestroyWindow(hwnd) };
+        return 0;
+    }
+    hwnd
+}
+
+/// Destroy a sink created by `create_sink`.
+pub(super) fn destroy_sink(hwnd: isize) {
+    if hwnd != 0 {
+        unsafe { ffi::DestroyWindow(hwnd) };
+        DEVICE_NAMES.with(|names| names.borrow_mut().clear());
+    }
+}
+
+/// Decode a `WM_INPUT` message.
+/// Returns `(device_path, vkey, scan_code, is_key_up)` for keyboard
+/// reports, the scan code with the 0xE0 prefix for extended keys.
+pub(super) fn read_keyboard(l_param: isize) -> Option<(String, u16, u32, bool)> {
+    let mut raw = std::mem::MaybeUninit::<ffi::RAWINPUT_KEYBOARD>::uninit();
+    let mut size = std::mem::size_of::<ffi::RAWINPUT_KEYBOARD>() as u32;
+    let read = unsafe {
+        ffi::GetRawInputData(
+            l_param,
+            ffi::RID_INPUT,
+            raw.as_mut_ptr().cast(),
+            &mut size,
+            std::mem::size_of::<ffi::RAWINPUTHEADER>() as u32,
+        )
+    };
+    if read == u32::MAX || (read as usize) < std::mem::size_of::<ffi::RAWINPUT_KEYBOARD>() {
+        return None;
+    }
+    let raw = unsafe { raw.assume_init() };
+    if raw.header.dw_type != ffi::RIM_TYPEKEYBOARD {
+        return None;
+    }
+
+    let path = DEVICE_NAMES.with(|names| {
+        names
+            .borrow_mut()
+            .entry(raw.header.h_device)
+            .or_insert_with(|| device_path(raw.header.h_device).unwrap_or_default())
+            .clone()
+    });
+    let is_up = raw.keyboard.flags & ffi::RI_KEY_BREAK != 0;
+    let mut scan = u32::from(raw.keyboard.make_code);
+    if raw.keyboard.flags & ffi::RI_KEY_E0 != 0 {
+        scan |= 0xE000;
+    }
+    Some((path, raw.keyboard.vkey, scan, is_up))
+}
+
+/// Resolve a raw-input device handle to its device path.
+fn device_path(h_device: isize) -> Option<String> {
+    let mut len: u32 = 0;
+    unsafe {
+        ffi::GetRawInputDeviceInfoW(h_device, ffi::RIDI_DEVICENAME, std::ptr::null_mut(), &mut len)
+    };
+    if len == 0 {
+        return None;
+    }
+
+    let mut buf = vec![0u16; len as usize];
+    let written = unsafe {
+        ffi::GetRawInputDeviceInfoW(h_device, ffi::RIDI_DEVICENAME, buf.as_mut_ptr().cast(), &mut len)
+    };
+    if written == u32::MAX {
+        return None;
+    }
+    let end = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+    Some(String::from_utf16_lossy(&buf[..end]))
+}
+
+/// Enumerate all attached keyboards.
+pub(super) fn list_keyboards() -> Vec<InputDevice> {
+    let entry_size = std::mem::size_of::<ffi::RAWINPUTDEVICELIST>() as u32;
+    let mut count: u32 = 0;
+    if unsafe { ffi::GetRawInputDeviceList(std::ptr::null_mut(), &mut count, entry_size) } != 0 {
+        return Vec::new();
+    }
+
+    let mut list = Vec::with_capacity(count as usize);
+    let filled = unsafe { ffi::GetRawInputDeviceList(list.as_mut_ptr(), &mut count, entry_size) };
+    if filled == u32::MAX {
+        return Vec::new();
+    }
+    unsafe { list.set_len(filled as usize) };
+
+    list.iter()
+        .filter(|d| d.dw_type == ffi::RIM_TYPEKEYBOARD)
+        .filter_map(|d| device_path(d.h_device))
+        .map(|path| InputDevice { path })
+        .collect()
+}
@@ -0,0 +1,763 @@
+use tauri::ipc::Response;
+
+// ===========================================================================
+// QR Codes
+// ===========================================================================
+//
+// `generate_qr(data)` encodes text as a QR code (byte mode, smallest version
+// that fits, mask chosen by the standard penalty score) and returns it as a
+// PNG, delivered to the webview as an `ArrayBuffer`. Used for login-by-QR
+// and device-linking screens without shipping a JS QR library.
+//
+// The PNG writer is deliberately tiny: 1-bit grayscale with stored
+// (uncompressed) deflate blocks, which for a black-and-white bitmap is still
+// only a few kilobytes.
+//
+// Reading QR codes from screenshots (`scan_qr_from_image`) is not
+// implemented: it needs general image decoding plus a finder/perspective
+// detector, and there is no image codec in the dependency tree.
+// ===========================================================================
+
+const DEFAULT_SCALE: u32 = 8;
+const MAX_SCALE: u32 = 32;
+const BORDER: usize = 4;
+
+#[derive(Clone, Copy)]
+enum Ecc {
+    Low,
+    Medium,
+    Quartile,
+    High,
+}
+
+impl Ecc {
+    fn parse(level: &str) -> Option<Self> {
+        match level.to_ascii_uppercase().as_str() {
+            "L" => Some(Self::Low),
+            "M" => Some(Self::Medium),
+            "Q" => Some(Self::Quartile),
+            "H" => Some(Self::High),
+            _ => None,
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    /// The two format-information bits for this level.
+    fn format_bits(self) -> u32 {
+        match self {
+            Self::Low => 1,
+            Self::Medium => 0,
+            Self::Quartile => 3,
+            Self::High => 2,
+        }
+    }
+}
+
+/// Error-correction codewords per block, by level then version (index 0 unused).
+#[rustfmt::skip]
+const ECC_CODEWORDS_PER_BLOCK: [[u8; 41]; 4] = [
+    [0, 7, 10, 15, 20, 26, 18, 20, 24, 30, 18, 20, 24, 26, 30, 22, 24, 28, 30, 28, 28, 28, 28, 30, 30, 26, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30],
+    [0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26, 26, 26, 26, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28],
+    [0, 13, 22, 18, 26, 18, 24, 18, 22, 20, 24, 28, 26, 24, 20, 30, 24, 28, 28, 26, 30, 28, 30, 30, 30, 30, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30],
+    [0, 17, 28, 22, 16, 22, 28, 26, 26, 24, 28, 24, 28, 22, 24, 24, 30, 28, 28, 26, 28, 30, 24, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30],
+];
+
+/// Error-correction blocks, by level then version (index 0 unused).
+#[rustfmt::skip]
+const NUM_ECC_BLOCKS: [[u8; 41]; 4] = [
+    [0, 1, 1, 1, 1, 1, 2, 2, 2, 2, 4, 4, 4, 4, 4, 6, 6, 6, 6, 7, 8, 8, 9, 9, 10, 12, 12, 12, 13, 14, 15, 16, 17, 18, 19, 19, 20, 21, 22, 24, 25],
+    [0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17, 17, 18, 20, 21, 23, 25, 26, 28, 29, 31, 33, 35, 37, 38, 40, 43, 45, 47, 49],
+    [0, 1, 1, 2, 2, 4, 4, 6, 6, 8, 8, 8, 10, 12, 16, 12, 17, 16, 18, 21, 20, 23, 23, 25, 27, 29, 34, 34, 35, 38, 40, 43, 45, 48, 51, 53, 56, 59, 62, 65, 68],
+    [0, 1, 1, 2, 4, 4, 4, 5, 6, 8, 8, 11, 11, 16, 16, 18, 16, 19, 21, 25, 25, 25, 34, 30, 32, 35, 37, 40, 42, 45, 48, 51, 54, 57, 60, 63, 66, 70, 74, 77, 81],
+];
+
+/// Modules available for data + ECC in a version, after function patterns.
+fn raw_data_modules(ver: usize) -> usize {
+    let mut result = (16 * ver + 128) * ver + 64;
+    if ver >= 2 {
+        let num_align = ver / 7 + 2;
+        result -= (25 * num_align - 10) * num_align - 55;
+        if ver >= 7 {
+            result -= 36;
+        }
+    }
+    result
+}
+
+fn data_codewords(ver: usize, ecc: Ecc) -> usize {
+    raw_data_modules(ver) / 8
+        - usize::from(ECC_CODEWORDS_PER_BLOCK[ecc.index()][ver])
+            * usize::from(NUM_ECC_BLOCKS[ecc.index()][ver])
+}
+
+// ---------------------------------------------------------------------------
+// Reed-Solomon over GF(2^8), polynomial 0x11D
+// ---------------------------------------------------------------------------
+
+fn gf_mul(x: u8, y: u8) -> u8 {
+    let mut z: u32 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x11D);
+        z ^= u32::from((y >> i) & 1) * u32::from(x);
+    }
+    z as u8
+}
+
+fn rs_divisor(degree: usize) -> Vec<u8> {
+    let mut result = vec![0u8; degree];
+    result[degree - 1] = 1;
+    let mut root = 1u8;
+    for _ in 0..degree {
+        for j in 0..degree {
+            result[j] = gf_mul(result[j], root);
+            if j + 1 < degree {
+                result[j] ^= result[j + 1];
+            }
+        }
+        root = gf_mul(root, 0x02);
+    }
+    result
+}
+
+fn rs_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut result = vec![0u8; divisor.len()];
+    for &b in data {
+        let factor = b ^ result.remove(0);
+        result.push(0);
+        for (r, &d) in result.iter_mut().zip(divisor) {
+            *r ^= gf_mul(d, factor);
+        }
+    }
+    result
+}
+
+// ---------------------------------------------------------------------------
+// Encoding
+// ---------------------------------------------------------------------------
+
+struct BitBuffer(Vec<bool>);
+
+impl BitBuffer {
+    fn push(&mut self, value: u32, len: usize) {
+        for i in (0..len).rev() {
+            self.0.push((value >> i) & 1 != 0);
+        }
+    }
+}
+
+/// Data codewords for `data` at `ver`, padded to capacity.
+fn encode_data(data: &[u8], ver: usize, ecc: Ecc) -> Vec<u8> {
+    let count_bits = if ver <= 9 { 8 } else { 16 };
+    let capacity_bits = data_codewords(ver, ecc) * 8;
+
+    let mut bits = BitBuffer(Vec::with_capacity(capacity_bits));
+    bits.push(0b0100, 4); // byte mode
+    bits.push(data.len() as u32, count_bits);
+    for &b in data {
+        bits.push(u32::from(b), 8);
+    }
+    let terminator = (capacity_bits - bits.0.len()).min(4);
+    bits.push(0, terminator);
+    let align = (8 - bits.0.len() % 8) % 8;
+    bits.push(0, align);
+    let mut pad = [0xEC, 0x11].into_iter().cycle();
+    while bits.0.len() < capacity_bits {
+        bits.push(pad.next().unwrap_or(0), 8);
+    }
+
+    bits.0
+        .chunks(8)
+        .map(|byte| {
+            byte.iter()
+                .fold(0u8, |acc, &bit| (acc << 1) | u8::from(bit))
+        })
+        .collect()
+}
+
+/// Split into blocks, append ECC to each, and interleave.
+fn add_ecc_and_interleave(data: &[u8], ver: usize, ecc: Ecc) -> Vec<u8> {
+    let num_blocks = usize::from(NUM_ECC_BLOCKS[ecc.index()][ver]);
+    let block_ecc_len = usize::from(ECC_CODEWORDS_PER_BLOCK[ecc.index()][ver]);
+    let raw_codewords = raw_data_modules(ver) / 8;
+    let num_short_blocks = num_blocks - raw_codewords % num_blocks;
+    let short_block_len = raw_codewords / num_blocks;
+
+    let divisor = rs_divisor(block_ecc_len);
+    let mut blocks = Vec::with_capacity(num_blocks);
+    let mut k = 0;
+    for i in 0..num_blocks {
+        let dat_len = short_block_len - block_ecc_len + usize::from(i >= num_short_blocks);
+        let mut block = data[k..k + dat_len].to_vec();
+        k += dat_len;
+        let ecc_bytes = rs_remainder(&block, &divisor);
+        if i < num_short_blocks {
+            block.push(0);
+        }
+        block.extend(ecc_bytes);
+        blocks.push(block);
+    }
+
+    let mut result = Vec::with_capacity(raw_codewords);
+    for i in 0..blocks[0].len() {
+        for (j, block) in blocks.iter().enumerate() {
+            // Skip the padding byte of short blocks.
+            if i != short_block_len - block_ecc_len || j >= num_short_blocks {
+                result.push(block[i]);
+            }
+        }
+    }
+    result
+}
+
+// ---------------------------------------------------------------------------
+// Symbol layout
+// ---------------------------------------------------------------------------
+
+struct Symbol {
+    size: usize,
+    modules: Vec<bool>,
+    is_function: Vec<bool>,
+}
+
+impl Symbol {
+    fn get(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x]
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y * self.size + x] = dark;
+        self.is_function[y * self.size + x] = true;
+    }
+
+    fn alignment_positions(ver: usize) -> Vec<usize> {
+        if ver == 1 {
+            return Vec::new();
+        }
+        let num_align = ver / 7 + 2;
+        let step = if ver == 32 {
+            26
+        } else {
+            (ver * 4 + num_align * 2 + 1) / (num_align * 2 - 2) * 2
+        };
+        let size = ver * 4 + 17;
+        let mut result = vec![6];
+        let mut pos = size - 7;
+        for _ in 0..num_align - 1 {
+            result.insert(1, pos);
+            pos -= step;
+        }
+        result
+    }
+
+    fn draw_finder(&mut self, x: isize, y: isize) {
+        for dy in -4isize..=4 {
+            for dx in -4isize..=4 {
+                let (xx, yy) = (x + dx, y + dy);
+                let size = self.size as isize;
+                if (0..size).contains(&xx) && (0..size).contains(&yy) {
+                    let dist = dx.abs().max(dy.abs());
+                    self.set_function(xx as usize, yy as usize, dist != 2 && dist != 4);
+                }
+            }
+        }
+    }
+
+    fn draw_alignment(&mut self, x: usize, y: usize) {
+        for dy in -2isize..=2 {
+            for dx in -2isize..=2 {
+                let dark = dx.abs().max(dy.abs()) != 1;
+                self.set_function((x as isize + dx) as usize, (y as isize + dy) as usize, dark);
+            }
+        }
+    }
+
+    fn draw_format_bits(&mut self, ecc: Ecc, mask: u32) {
+        let data = (ecc.format_bits() << 3) | mask;
+        let mut rem = data;
+        for _ in 0..10 {
+            rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+        }
+        let bits = ((data << 10) | rem) ^ 0x5412;
+        let bit = |i: usize| (bits >> i) & 1 != 0;
+        let size = self.size;
+
+        for i in 0..=5 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i));
+        }
+        self.set_function(8, size - 8, true);
+    }
+
+    fn draw_version(&mut self, ver: usize) {
+        if ver < 7 {
+            return;
+        }
+        let mut rem = ver as u32;
+        for _ in 0..12 {
+            rem = (rem << 1) ^ ((rem >> 11) * 0x1F25);
+        }
+        let bits = ((ver as u32) << 12) | rem;
+        for i in 0..18 {
+            let dark = (bits >> i) & 1 != 0;
+            let a = self.size - 11 + i % 3;
+            let b = i / 3;
+            self.set_function(a, b, dark);
+            self.set_function(b, a, dark);
+        }
+    }
+
+    fn draw_function_patterns(&mut self, ver: usize, ecc: Ecc) {
+        for i in 0..self.size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+        let far = self.size as isize - 4;
+        self.draw_finder(3, 3);
+        self.draw_finder(far, 3);
+        self.draw_finder(3, far);
+
+        let positions = Self::alignment_positions(ver);
+        let n = positions.len();
+        for (i, &y) in positions.iter().enumerate() {
+            for (j, &x) in positions.iter().enumerate() {
+                let corner = (i == 0 && (j == 0 || j == n - 1)) || (i == n - 1 && j == 0);
+                if !corner {
+                    self.draw_alignment(x, y);
+                }
+            }
+        }
+
+        // Reserve the format areas; real bits are drawn once the mask is known.
+        self.draw_format_bits(ecc, 0);
+        self.draw_version(ver);
+    }
+
+    fn draw_codewords(&mut self, data: &[u8]) {
+        let size = self.size;
+        let mut i = 0;
+        let mut right = size as isize - 1;
+        while right >= 1 {
+            if right == 6 {
+                right = 5;
+            }
+            let upward = ((right + 1) & 2) == 0;
+            for vert in 0..size {
+                for j in 0..2 {
+                    let x = right as usize - j;
+                    let y = if upward { size - 1 - vert } else { vert };
+                    if !self.is_function[y * size + x] && i < data.len() * 8 {
+                        self.modules[y * size + x] = (data[i >> 3] >> (7 - (i & 7))) & 1 != 0;
+                        i += 1;
+                    }
+                }
+            }
+            right -= 2;
+        }
+    }
+
+    fn apply_mask(&mut self, mask: u32) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let invert = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                let idx = y * self.size + x;
+                self.modules[idx] ^= invert && !self.is_function[idx];
+            }
+        }
+    }
+
+    /// Standard mask penalty (runs, 2x2 blocks, finder-like patterns, balance).
+    fn penalty(&self) -> usize {
+        const FINDER_LIKE: [bool; 11] = [
+            true, false, true, true, true, false, true, false, false, false, false,
+        ];
+        let size = self.size;
+        let mut score = 0;
+
+        for horizontal in [true, false] {
+            for a in 0..size {
+                let line: Vec<bool> = (0..size)
+                    .map(|b| {
+                        if horizontal {
+                            self.get(b, a)
+                        } else {
+                            self.get(a, b)
+                        }
+                    })
+                    .collect();
+                let mut run = 1;
+                for b in 1..=size {
+                    if b < size && line[b] == line[b - 1] {
+                        run += 1;
+                    } else {
+                        if run >= 5 {
+                            score += 3 + (run - 5);
+                        }
+                        run = 1;
+                    }
+                }
+                for window in line.windows(11) {
+                    if window == FINDER_LIKE || window.iter().rev().eq(FINDER_LIKE.iter()) {
+                        score += 40;
+                    }
+                }
+            }
+        }
+
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let c = self.get(x, y);
+                if c == self.get(x + 1, y) && c == self.get(x, y + 1) && c == self.get(x + 1, y + 1)
+                {
+                    score += 3;
+                }
+            }
+        }
+
+        let dark = self.modules.iter().filter(|&&m| m).count();
+        let percent = dark * 100 / self.modules.len();
+        score + 10 * (percent.abs_diff(50) / 5)
+    }
+}
+
+fn encode(data: &[u8], ecc: Ecc) -> Result<Symbol, String> {
+    let ver = (1..=40)
+        .find(|&v| {
+            let count_bits = if v <= 9 { 8 } else { 16 };
+            data.len() < (1 << count_bits)
+                && 4 + count_bits + data.len() * 8 <= data_codewords(v, ecc) * 8
+        })
+        .ok_or("data is too long for a QR code")?;
+
+    let codewords = add_ecc_and_interleave(&encode_data(data, ver, ecc), ver, ecc);
+    let size = ver * 4 + 17;
+    let mut symbol = Symbol {
+        size,
+        modules: vec![false; size * size],
+        is_function: vec![false; size * size],
+    };
+    symbol.draw_function_patterns(ver, ecc);
+    symbol.draw_codewords(&codewords);
+
+    let mut best = (usize::MAX, 0);
+    for mask in 0..8 {
+        symbol.apply_mask(mask);
+        symbol.draw_format_bits(ecc, mask);
+        best = best.min((symbol.penalty(), mask));
+        symbol.apply_mask(mask);
+    }
+    symbol.apply_mask(best.1);
+    symbol.draw_format_bits(ecc, best.1);
+    Ok(symbol)
+}
+
+// ---------------------------------------------------------------------------
+// PNG output
+// ---------------------------------------------------------------------------
+
+fn crc32(chunks: &[&[u8]]) -> u32 {
+    let mut crc = !0u32;
+    for &b in chunks.iter().flat_map(|c| c.iter()) {
+        crc ^= u32::from(b);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + u32::from(byte)) % 65_521;
+        b = (b + a) % 65_521;
+    }
+    (b << 16) | a
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    out.extend_from_slice(&crc32(&[kind, data]).to_be_bytes());
+}
+
+/// Render with a quiet-zone border as a 1-bit grayscale PNG.
+fn to_png(symbol: &Symbol, scale: usize) -> Vec<u8> {
+    let dim = (symbol.size + 2 * BORDER) * scale;
+    let row_bytes = dim.div_ceil(8);
+
+    let mut raw = Vec::with_capacity((row_bytes + 1) * dim);
+    for py in 0..dim {
+        raw.push(0); // filter: none
+        let mut row = vec![0u8; row_bytes];
+        for px in 0..dim {
+            let (mx, my) = (px / scale, py / scale);
+            let dark = mx >= BORDER
+                && my >= BORDER
+                && mx - BORDER < symbol.size
+                && my - BORDER < symbol.size
+                && symbol.get(mx - BORDER, my - BORDER);
+            if !dark {
+                row[px / 8] |= 0x80 >> (px % 8);
+            }
+        }
+        raw.extend(row);
+    }
+
+    // zlib stream of stored deflate blocks.
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = raw.chunks(65_535).peekable();
+    while let Some(block) = blocks.next() {
+        zlib.push(u8::from(blocks.peek().is_none()));
+        let len = block.len() as u16;
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&(dim as u32).to_be_bytes());
+    ihdr.extend_from_slice(&(dim as u32).to_be_bytes());
+    ihdr.extend_from_slice(&[1, 0, 0, 0, 0]); // 1-bit grayscale, no interlace
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    png_chunk(&mut png, b"IHDR", &ihdr);
+    png_chunk(&mut png, b"IDAT", &zlib);
+    png_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Encode `data` as a QR code PNG. `ecc` is one of L/M/Q/H (default M);
+/// `scale` is pixels per module (default 8).
+#[tauri::command]
+pub(crate) fn generate_qr(
+    data: String,
+    ecc: Option<String>,
+    scale: Option<u32>,
+) -> Result<Response, String> {
+    let ecc = match ecc {
+        Some(level) => Ecc::parse(&level)
+            .ok_or_else(|| format!("unknown error-correction level \"{level}\""))?,
+        None => Ecc::Medium,
+    };
+    let scale = scale.unwrap_or(DEFAULT_SCALE).clamp(1, MAX_SCALE) as usize;
+    let symbol = encode(data.as_bytes(), ecc)?;
+    Ok(Response::new(to_png(&symbol, scale)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The 15 format bits as drawn next to the bottom-left and top-right
+    /// finders, most significant first.
+    fn read_format(symbol: &Symbol) -> u32 {
+        let size = symbol.size;
+        let mut bits = 0;
+        for i in 0..8 {
+            bits |= u32::from(symbol.get(size - 1 - i, 8)) << i;
+        }
+        for i in 8..15 {
+            bits |= u32::from(symbol.get(8, size - 15 + i)) << i;
+        }
+        bits
+    }
+
+    /// Read the codewords back in placement order, undoing the mask.
+    fn read_codewords(symbol: &Symbol, mask: u32) -> Vec<u8> {
+        let size = symbol.size;
+        let mut unmasked = Symbol {
+            size,
+            modules: symbol.modules.clone(),
+            is_function: symbol.is_function.clone(),
+        };
+        unmasked.apply_mask(mask);
+
+        let mut bits = Vec::new();
+        let mut right = size - 1;
+        loop {
+            let upward = (right + 1) & 2 == 0;
+            for vert in 0..size {
+                let y = if upward { size - 1 - vert } else { vert };
+                for x in [right, right - 1] {
+                    if !unmasked.is_function[y * size + x] {
+                        bits.push(unmasked.get(x, y));
+                    }
+                }
+            }
+            if right < 3 {
+                break;
+            }
+            right -= if right == 8 { 3 } else { 2 };
+        }
+        bits.chunks_exact(8)
+            .map(|byte| byte.iter().fold(0, |acc, &bit| (acc << 1) | u8::from(bit)))
+            .collect()
+    }
+
+    #[test]
+    fn reed_solomon_known_answers() {
+        // ISO/IEC 18004 Annex I: "01234567" at 1-M.
+        let data = [
+            0x10, 0x20, 0x0C, 0x56, 0x61, 0x80, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11,
+            0xEC, 0x11,
+        ];
+        assert_eq!(
+            rs_remainder(&data, &rs_divisor(10)),
+            [0xA5, 0x24, 0xD4, 0xC1, 0xED, 0x36, 0xC7, 0x87, 0x2C, 0x55]
+        );
+        // "HELLO WORLD" at 1-Q.
+        let data = [32, 91, 11, 120, 209, 114, 220, 77, 67, 64, 236, 17, 236];
+        assert_eq!(
+            rs_remainder(&data, &rs_divisor(13)),
+            [168, 72, 22, 82, 217, 54, 156, 0, 46, 15, 180, 122, 16]
+        );
+    }
+
+    #[test]
+    fn capacities_match_the_standard() {
+        for (ver, ecc, codewords) in [
+            (1, Ecc::Low, 19),
+            (1, Ecc::Medium, 16),
+            (1, Ecc::Quartile, 13),
+            (1, Ecc::High, 9),
+            (5, Ecc::Quartile, 62),
+            (10, Ecc::High, 122),
+            (40, Ecc::Low, 2956),
+            (40, Ecc::High, 1276),
+        ] {
+            assert_eq!(data_codewords(ver, ecc), codewords, "{ver}");
+        }
+        assert_eq!(encode(&[0; 17], Ecc::Low).unwrap().size, 21);
+        assert_eq!(encode(&[0; 18], Ecc::Low).unwrap().size, 25);
+        assert_eq!(encode(&[0; 2953], Ecc::Low).unwrap().size, 177);
+        assert!(encode(&[0; 2954], Ecc::Low).is_err());
+    }
+
+    #[test]
+    fn byte_mode_data_codewords() {
+        assert_eq!(
+            encode_data(b"hello", 1, Ecc::Medium),
+            [
+                0x40, 0x56, 0x86, 0x56, 0xC6, 0xC6, 0xF0, 0xEC, 0x11, 0xEC, 0x11, 0xEC, 0x11, 0xEC,
+                0x11, 0xEC,
+            ]
+        );
+    }
+
+    #[test]
+    fn format_and_version_bits_match_the_standard() {
+        for (ecc, bits) in [
+            (Ecc::Low, 0b111011111000100),
+            (Ecc::Medium, 0b101010000010010),
+            (Ecc::Quartile, 0b011010101011111),
+            (Ecc::High, 0b001011010001001),
+        ] {
+            let mut symbol = Symbol {
+                size: 21,
+                modules: vec![false; 21 * 21],
+                is_function: vec![false; 21 * 21],
+            };
+            symbol.draw_format_bits(ecc, 0);
+            assert_eq!(read_format(&symbol), bits);
+        }
+
+        for (ver, bits) in [(7, 0x07C94), (40, 0x28C69)] {
+            let size = ver * 4 + 17;
+            let mut symbol = Symbol {
+                size,
+                modules: vec![false; size * size],
+                is_function: vec![false; size * size],
+            };
+            symbol.draw_version(ver);
+            let drawn = (0..18).fold(0u32, |acc, i| {
+                acc | u32::from(symbol.get(size - 11 + i % 3, i / 3)) << i
+            });
+            assert_eq!(drawn, bits, "version {ver}");
+        }
+    }
+
+    #[test]
+    fn symbols_decode_to_their_codewords() {
+        for (len, ecc, ver) in [
+            (5, Ecc::Medium, 1),
+            (60, Ecc::Quartile, 5),
+            (100, Ecc::High, 10),
+            (500, Ecc::Low, 15),
+        ] {
+            let data: Vec<u8> = (0..len).map(|i| (i * 7) as u8).collect();
+            let symbol = encode(&data, ecc).unwrap();
+            assert_eq!(symbol.size, ver * 4 + 17);
+
+            let format = read_format(&symbol) ^ 0x5412;
+            assert_eq!(format >> 13, ecc.format_bits());
+            let mask = (format >> 10) & 7;
+
+            // Undo the interleaving and check each block's ECC.
+            let codewords = read_codewords(&symbol, mask);
+            let num_blocks = usize::from(NUM_ECC_BLOCKS[ecc.index()][ver]);
+            let block_ecc_len = usize::from(ECC_CODEWORDS_PER_BLOCK[ecc.index()][ver]);
+            let raw_codewords = raw_data_modules(ver) / 8;
+            let num_short_blocks = num_blocks - raw_codewords % num_blocks;
+            let short_data_len = raw_codewords / num_blocks - block_ecc_len;
+            let data_len = |block: usize| short_data_len + usize::from(block >= num_short_blocks);
+
+            let mut blocks = vec![Vec::new(); num_blocks];
+            let mut next = codewords.iter();
+            for i in 0..=short_data_len {
+                for (b, block) in blocks.iter_mut().enumerate() {
+                    if i < data_len(b) {
+                        block.push(*next.next().unwrap());
+                    }
+                }
+            }
+            for _ in 0..block_ecc_len {
+                for block in &mut blocks {
+                    block.push(*next.next().unwrap());
+                }
+            }
+
+            let divisor = rs_divisor(block_ecc_len);
+            let mut decoded = Vec::new();
+            for (b, block) in blocks.iter().enumerate() {
+                let (block_data, block_ecc) = block.split_at(data_len(b));
+                assert_eq!(rs_remainder(block_data, &divisor), block_ecc);
+                decoded.extend_from_slice(block_data);
+            }
+            assert_eq!(decoded, encode_data(&data, ver, ecc));
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+use tauri_plugin_notification::NotificationExt;
+
+use crate::{scheduler, store, APP_HANDLE};
+
+// ===========================================================================
+// Message Reminders
+// ===========================================================================
+//
+// "Remind me about this": `set_message_reminder(messageRef, at)` stores a
+// reminder in the native store and arms a scheduler timer. When it fires a
+// toast is shown and `reminder-fired` is emitted with the message reference
+// so the frontend can jump to the message. Desktop toasts carry no click
+// payload, so the reminder is also kept as `fired` until the user dismisses
+// it from the reminders panel — focusing the app after the toast can still
+// deep-link from `list_reminders()`.
+// ===========================================================================
+
+const STORE_DOC: &str = "reminders";
+const SCHEDULER_PREFIX: &str = "reminder:";
+
+/// Enough to navigate back to a message and label the reminder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MessageRef {
+    pub message_id: String,
+    pub channel_id: String,
+    pub guild_id: Option<String>,
+    pub author: String,
+    /// Short excerpt shown in the toast.
+    pub preview: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Reminder {
+    pub id: String,
+    pub message: MessageRef,
+    /// Milliseconds since the Unix epoch.
+    pub at: u64,
+    pub fired: bool,
+}
+
+static REMINDERS: Mutex<Option<Vec<Reminder>>> = Mutex::new(None);
+
+fn with_reminders<R>(f: impl FnOnce(&mut Vec<Reminder>) -> R) -> R {
+    let mut guard = REMINDERS.lock().unwrap();
+    f(guard.get_or_insert_with(|| store::load(STORE_DOC)))
+}
+
+/// Scheduler task — toast and notify the frontend.
+fn fire(key: &str) {
+    let id = key.trim_start_matches(SCHEDULER_PREFIX);
+    let reminder = with_reminders(|reminders| {
+        let reminder = reminders.iter_mut().find(|r| r.id == id)?;
+        reminder.fired = true;
+        let reminder = reminder.clone();
+        let _ = store::save(STORE_DOC, reminders);
+        Some(reminder)
+    });
+
+    if let (Some(reminder), Some(handle)) = (reminder, APP_HANDLE.get()) {
+        let _ = handle
+            .notification()
+            .builder()
+            .title(format!("Reminder: message from {}", reminder.message.author))
+            .body(&reminder.message.preview)
+            .show();
+        let _ = handle.emit("reminder-fired", reminder);
+    }
+}
+
+/// Re-arm timers for unfired reminders at startup.
+pub(crate) fn init() {
+    with_reminders(|reminders| {
+        for r in reminders.iter().filter(|r| !r.fired) {
+            scheduler::schedule(format!("{SCHEDULER_PREFIX}{}", r.id), r.at, fire);
+        }
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Remind the user about `message` at `at` (ms since the Unix epoch).
+#[tauri::command]
+pub(crate) fn set_message_reminder(message: MessageRef, at: u64) -> Result<Reminder, String> {
+    let reminder = Reminder {
+        id: store::new_id(),
+        message,
+        at,
+        fired: false,
+    };
+    with_reminders(|reminders| {
+        reminders.push(reminder.clone());
+        store::save(STORE_DOC, reminders)
+    })?;
+    scheduler::schedule(format!("{SCHEDULER_PREFIX}{}", reminder.id), at, fire);
+    Ok(reminder)
+}
+
+/// All reminders, soonest first (fired ones included until dismissed).
+#[tauri::command]
+pub(crate) fn list_reminders() -> Vec<Reminder> {
+    let mut reminders = with_reminders(|reminders| reminders.clone());
+    reminders.sort_by_key(|r| r.at);
+    reminders
+}
+
+/// Cancel a pending reminder or dismiss a fired one.
+#[tauri::command]
+pub(crate) fn cancel_reminder(id: String) -> Result<(), String> {
+    scheduler::cancel(&format!("{SCHEDULER_PREFIX}{id}"));
+    with_reminders(|reminders| {
+        reminders.retain(|r| r.id != id);
+        store::save(STORE_DOC, reminders)
+    })
+}
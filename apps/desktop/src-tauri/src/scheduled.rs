@@ -0,0 +1,158 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+use tauri_plugin_notification::NotificationExt;
+
+use crate::{scheduler, store, APP_HANDLE};
+
+// ===========================================================================
+// Scheduled Messages
+// ===========================================================================
+//
+// `schedule_message(channelId, content, sendAt)` persists the message in the
+// native store and arms a scheduler timer. Sending itself stays in the
+// webview's API client — it owns the (memory-only) access token — so when
+// the timer fires the message is marked `due` and `scheduled-message-due`
+// is emitted. The frontend sends it and reports back with
+// `complete_scheduled_message`, which removes it (or records the error) and
+// shows a success/failure toast.
+//
+// Because due messages stay in the store until completed, nothing is lost
+// if the app was closed at send time or the webview was not listening: on
+// the next launch the timer fires immediately, and the frontend also picks
+// up `due` entries from `list_scheduled_messages` after it loads.
+// ===========================================================================
+
+const STORE_DOC: &str = "scheduled-messages";
+const SCHEDULER_PREFIX: &str = "scheduled-message:";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ScheduledStatus {
+    Pending,
+    Due,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ScheduledMessage {
+    pub id: String,
+    pub channel_id: String,
+    pub content: String,
+    /// Milliseconds since the Unix epoch.
+    pub send_at: u64,
+    pub status: ScheduledStatus,
+    pub error: Option<String>,
+}
+
+static MESSAGES: Mutex<Option<Vec<ScheduledMessage>>> = Mutex::new(None);
+
+fn with_messages<R>(f: impl FnOnce(&mut Vec<ScheduledMessage>) -> R) -> R {
+    let mut guard = MESSAGES.lock().unwrap();
+    f(guard.get_or_insert_with(|| store::load(STORE_DOC)))
+}
+
+/// Scheduler task — mark the message due and ask the webview to send it.
+fn fire(key: &str) {
+    let id = key.trim_start_matches(SCHEDULER_PREFIX);
+    let message = with_messages(|messages| {
+        let message = messages.iter_mut().find(|m| m.id == id)?;
+        message.status = ScheduledStatus::Due;
+        let message = message.clone();
+        let _ = store::save(STORE_DOC, messages);
+        Some(message)
+    });
+    if let (Some(message), Some(handle)) = (message, APP_HANDLE.get()) {
+        let _ = handle.emit("scheduled-message-due", message);
+    }
+}
+
+/// Re-arm timers for pending messages at startup.
+pub(crate) fn init() {
+    with_messages(|messages| {
+        for m in messages.iter().filter(|m| m.status == ScheduledStatus::Pending) {
+            scheduler::schedule(format!("{SCHEDULER_PREFIX}{}", m.id), m.send_at, fire);
+        }
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Schedule `content` to be sent to `channel_id` at `send_at` (ms since the
+/// Unix epoch). Returns the new entry.
+#[tauri::command]
+pub(crate) fn schedule_message(
+    channel_id: String,
+    content: String,
+    send_at: u64,
+) -> Result<ScheduledMessage, String> {
+    if content.trim().is_empty() {
+        return Err("cannot schedule an empty message".into());
+    }
+    let message = ScheduledMessage {
+        id: store::new_id(),
+        channel_id,
+        content,
+        send_at,
+        status: ScheduledStatus::Pending,
+        error: None,
+    };
+
+    with_messages(|messages| {
+        messages.push(message.clone());
+        store::save(STORE_DOC, messages)
+    })?;
+    scheduler::schedule(format!("{SCHEDULER_PREFIX}{}", message.id), send_at, fire);
+    Ok(message)
+}
+
+/// All scheduled messages (pending, due and failed), soonest first.
+#[tauri::command]
+pub(crate) fn list_scheduled_messages() -> Vec<ScheduledMessage> {
+    let mut messages = with_messages(|messages| messages.clone());
+    messages.sort_by_key(|m| m.send_at);
+    messages
+}
+
+/// Cancel a scheduled message (or discard a failed one).
+#[tauri::command]
+pub(crate) fn cancel_scheduled_message(id: String) -> Result<(), String> {
+    scheduler::cancel(&format!("{SCHEDULER_PREFIX}{id}"));
+    with_messages(|messages| {
+        messages.retain(|m| m.id != id);
+        store::save(STORE_DOC, messages)
+    })
+}
+
+/// Report the outcome of sending a due message. On success it is removed;
+/// on failure it is kept as `failed` with the error for the UI to retry.
+#[tauri::command]
+pub(crate) fn complete_scheduled_message(id: String, error: Option<String>) -> Result<(), String> {
+    let failed = error.is_some();
+    with_messages(|messages| {
+        match &error {
+            None => messages.retain(|m| m.id != id),
+            Some(err) => {
+                if let Some(m) = messages.iter_mut().find(|m| m.id == id) {
+                    m.status = ScheduledStatus::Failed;
+                    m.error = Some(err.clone());
+                }
+            }
+        }
+        store::save(STORE_DOC, messages)
+    })?;
+
+    if let Some(handle) = APP_HANDLE.get() {
+        let (title, body) = if failed {
+            ("Scheduled message failed", error.unwrap_or_default())
+        } else {
+            ("Scheduled message sent", String::new())
+        };
+        let _ = handle.notification().builder().title(title).body(body).show();
+    }
+    Ok(())
+}
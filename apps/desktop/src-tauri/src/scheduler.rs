@@ -0,0 +1,109 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Condvar, Mutex, Once};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// ===========================================================================
+// Scheduler
+// ===========================================================================
+//
+// One timer thread shared by every native feature that needs to do
+// something at a wall-clock time (digests, snoozes, reminders, scheduled
+// messages). Tasks are plain `fn(&str)` handlers keyed by a string id; the
+// key is passed back to the handler so it can look up its own state.
+//
+// A handler that panics is caught and reported on stderr; the thread keeps
+// serving the other tasks.
+//
+// Timers are in-memory only. Features persist their own schedule in the
+// native store and re-arm it from `init` at startup — a task whose time
+// passed while the app was closed fires immediately.
+//
+// Due times are Unix-epoch milliseconds, and the thread never sleeps longer
+// than `MAX_SLEEP` so that system suspend or a wall-clock change is noticed
+// promptly instead of after the original monotonic sleep expires.
+// ===========================================================================
+
+/// Task handler — receives the key it was scheduled under.
+pub(crate) type Task = fn(&str);
+
+const MAX_SLEEP: Duration = Duration::from_secs(30);
+
+#[derive(Default)]
+struct Queue {
+    /// Pending tasks ordered by due time.
+    by_time: BTreeMap<(u64, String), Task>,
+    /// Key → due time, for cancel/replace.
+    by_key: HashMap<String, u64>,
+}
+
+static QUEUE: Mutex<Option<Queue>> = Mutex::new(None);
+static WAKE: Condvar = Condvar::new();
+static THREAD: Once = Once::new();
+
+/// Current wall-clock time in ms since the Unix epoch.
+pub(crate) fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Run `task(key)` at `at_ms`. Replaces any pending task with the same key.
+pub(crate) fn schedule(key: impl Into<String>, at_ms: u64, task: Task) {
+    THREAD.call_once(|| {
+        std::thread::spawn(run);
+    });
+
+    let key = key.into();
+    let mut guard = QUEUE.lock().unwrap();
+    let queue = guard.get_or_insert_with(Queue::default);
+    if let Some(old) = queue.by_key.insert(key.clone(), at_ms) {
+        queue.by_time.remove(&(old, key.clone()));
+    }
+    queue.by_time.insert((at_ms, key), task);
+    WAKE.notify_one();
+}
+
+/// Cancel a pending task. No-op if it already ran or never existed.
+pub(crate) fn cancel(key: &str) {
+    let mut guard = QUEUE.lock().unwrap();
+    if let Some(queue) = guard.as_mut() {
+        if let Some(at) = queue.by_key.remove(key) {
+            queue.by_time.remove(&(at, key.to_string()));
+        }
+    }
+}
+
+/// Timer thread body.
+fn run() {
+    let mut guard = QUEUE.lock().unwrap();
+    loop {
+        let queue = guard.get_or_insert_with(Queue::default);
+        let now = now_ms();
+
+        let due = match queue.by_time.first_key_value() {
+            Some((&(at, _), _)) if at <= now => queue.by_time.pop_first(),
+            _ => None,
+        };
+
+        if let Some(((_, key), task)) = due {
+            queue.by_key.remove(&key);
+            // Run without the lock — handlers commonly reschedule.
+            drop(guard);
+            // A panicking handler must not take the shared thread (and with
+            // it every other feature's timers) down with it.
+            if std::panic::catch_unwind(|| task(&key)).is_err() {
+                eprintln!("scheduler: task \"{key}\" panicked");
+            }
+            guard = QUEUE.lock().unwrap();
+            continue;
+        }
+
+        let sleep = queue
+            .by_time
+            .first_key_value()
+            .map(|(&(at, _), _)| Duration::from_millis(at - now).min(MAX_SLEEP))
+            .unwrap_or(MAX_SLEEP);
+        guard = WAKE.wait_timeout(guard, sleep).unwrap().0;
+    }
+}
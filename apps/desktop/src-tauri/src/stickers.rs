@@ -0,0 +1,406 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::audit;
+
+// ===========================================================================
+// Sticker Upload Preparation
+// ===========================================================================
+//
+// `prepare_sticker(path)` inspects a file before it is uploaded as a sticker
+// and reports every constraint it breaks, so users can fix the file instead
+// of getting a generic rejection from the server:
+//
+//   PNG / APNG — parsed chunk by chunk: dimensions from IHDR, frame count and
+//                loop count from acTL, total duration from each fcTL delay.
+//   GIF        — dimensions from the logical screen descriptor; frame count,
+//                total duration (Graphic Control Extension delays) and loop
+//                count (NETSCAPE2.0 extension) by walking the blocks.
+//   Lottie     — JSON structure, canvas size, frame rate / duration, and no
+//                embedded raster images (which the renderer won't load).
+//
+// This is validation only. Resizing and recompression need an image codec,
+// which is not in the dependency tree; oversize files are reported with the
+// target dimensions so the user (or the webview's canvas) can scale them.
+// ===========================================================================
+
+const MAX_FILE_BYTES: u64 = 512 * 1024;
+/// Files larger than this aren't read at all; anything over
+/// `MAX_FILE_BYTES` is rejected anyway, this just bounds the report's cost.
+const MAX_INSPECT_BYTES: u64 = 16 * 1024 * 1024;
+const MAX_DIMENSION: u32 = 320;
+const MAX_DURATION_MS: u64 = 5_000;
+const MAX_LOTTIE_FPS: f64 = 60.0;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum StickerFormat {
+    Png,
+    Apng,
+    Gif,
+    Lottie,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct StickerReport {
+    pub format: StickerFormat,
+    pub size_bytes: u64,
+    pub width: u32,
+    pub height: u32,
+    pub frame_count: u32,
+    pub duration_ms: u64,
+    /// Problems the server will reject. Empty means the file is uploadable.
+    pub errors: Vec<String>,
+    /// Non-fatal issues worth surfacing.
+    pub warnings: Vec<String>,
+}
+
+impl StickerReport {
+    fn new(format: StickerFormat, size_bytes: u64) -> Self {
+        Self {
+            format,
+            size_bytes,
+            width: 0,
+            height: 0,
+            frame_count: 1,
+            duration_ms: 0,
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Shared limits that apply regardless of format.
+    fn check_limits(&mut self) {
+        if self.size_bytes > MAX_FILE_BYTES {
+            self.errors.push(format!(
+                "file is {} KB; stickers must be at most {} KB",
+                self.size_bytes.div_ceil(1024),
+                MAX_FILE_BYTES / 1024
+            ));
+        }
+        if self.width > MAX_DIMENSION || self.height > MAX_DIMENSION {
+            let scale = f64::from(MAX_DIMENSION) / f64::from(self.width.max(self.height));
+            self.errors.push(format!(
+                "image is {}x{}; resize to at most {}x{} (e.g. {}x{})",
+                self.width,
+                self.height,
+                MAX_DIMENSION,
+                MAX_DIMENSION,
+                (f64::from(self.width) * scale).round() as u32,
+                (f64::from(self.height) * scale).round() as u32
+            ));
+        } else if self.width != self.height {
+            self.warnings.push(format!(
+                "image is {}x{}; square stickers display best",
+                self.width, self.height
+            ));
+        }
+        if self.duration_ms > MAX_DURATION_MS {
+            self.errors.push(format!(
+                "animation lasts {:.1}s; stickers may loop for at most {}s",
+                self.duration_ms as f64 / 1000.0,
+                MAX_DURATION_MS / 1000
+            ));
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Format inspection
+// ---------------------------------------------------------------------------
+
+fn be_u32(b: &[u8]) -> u32 {
+    u32::from_be_bytes([b[0], b[1], b[2], b[3]])
+}
+
+fn be_u16(b: &[u8]) -> u16 {
+    u16::from_be_bytes([b[0], b[1]])
+}
+
+fn inspect_png(data: &[u8], report: &mut StickerReport) {
+    let mut pos = PNG_SIGNATURE.len();
+    let mut declared_frames = None;
+    let mut frame_controls = 0u32;
+    let mut duration_ms = 0u64;
+
+    while pos + 8 <= data.len() {
+        let len = be_u32(&data[pos..]) as usize;
+        let kind = &data[pos + 4..pos + 8];
+        let body_start = pos + 8;
+        let Some(body) = data.get(body_start..body_start + len) else {
+            report.errors.push(format!(
+                "{} chunk at byte {pos} is truncated",
+                String::from_utf8_lossy(kind)
+            ));
+            return;
+        };
+
+        match kind {
+            b"IHDR" if len >= 8 => {
+                report.width = be_u32(body);
+                report.height = be_u32(&body[4..]);
+            }
+            b"acTL" if len >= 8 => {
+                declared_frames = Some(be_u32(body));
+                if be_u32(&body[4..]) != 0 {
+                    report.warnings.push(
+                        "APNG plays a fixed number of times; stickers normally loop forever".into(),
+                    );
+                }
+            }
+            b"fcTL" if len >= 26 => {
+                frame_controls += 1;
+                let num = u64::from(be_u16(&body[20..]));
+                let den = match be_u16(&body[22..]) {
+                    0 => 100,
+                    d => u64::from(d),
+                };
+                duration_ms += num * 1000 / den;
+            }
+            b"IEND" => break,
+            _ => {}
+        }
+        pos = body_start + len + 4; // skip CRC
+    }
+
+    if report.width == 0 || report.height == 0 {
+        report.errors.push("PNG has no valid IHDR header".into());
+    }
+    if let Some(frames) = declared_frames {
+        report.format = StickerFormat::Apng;
+        report.frame_count = frames;
+        report.duration_ms = duration_ms;
+        if frames != frame_controls {
+            report.errors.push(format!(
+                "APNG declares {frames} frames but contains {frame_controls} frame headers"
+            ));
+        }
+    }
+}
+
+fn le_u16(b: &[u8]) -> u16 {
+    u16::from_le_bytes([b[0], b[1]])
+}
+
+/// Skip a run of GIF data sub-blocks; returns the position after the
+/// terminator, or `None` if the data ends first.
+fn skip_sub_blocks(data: &[u8], mut pos: usize) -> Option<usize> {
+    loop {
+        let len = usize::from(*data.get(pos)?);
+        pos += 1 + len;
+        if len == 0 {
+            return Some(pos);
+        }
+    }
+}
+
+/// Size of the color table a GIF packed-fields byte announces, if any.
+fn gif_color_table_len(packed: u8) -> usize {
+    if packed & 0x80 != 0 {
+        3 << ((packed & 0x07) + 1)
+    } else {
+        0
+    }
+}
+
+fn inspect_gif(data: &[u8], report: &mut StickerReport) {
+    if data.len() < 13 {
+        report.errors.push("GIF header is truncated".into());
+        return;
+    }
+    report.width = u32::from(le_u16(&data[6..]));
+    report.height = u32::from(le_u16(&data[8..]));
+
+    let mut pos = 13 + gif_color_table_len(data[10]);
+    let mut frames = 0u32;
+    let mut duration_ms = 0u64;
+    // Delay from the Graphic Control Extension preceding the next image.
+    let mut delay_ms = 0u64;
+    let mut loop_count = None;
+
+    loop {
+        let next = match data.get(pos) {
+            Some(0x3B) => break,
+            Some(0x21) => {
+                let body = data.get(pos + 2..).unwrap_or_default();
+                match data.get(pos + 1) {
+                    Some(0xF9) if body.len() >= 5 && body[0] == 4 => {
+                        delay_ms = u64::from(le_u16(&body[2..])) * 10;
+                    }
+                    Some(0xFF) if body.len() >= 17 && &body[..12] == b"\x0BNETSCAPE2.0" => {
+                        loop_count = Some(le_u16(&body[14..]));
+                    }
+                    _ => {}
+                }
+                skip_sub_blocks(data, pos + 2)
+            }
+            Some(0x2C) => data.get(pos + 9).and_then(|&packed| {
+                frames += 1;
+                duration_ms += std::mem::take(&mut delay_ms);
+                // Descriptor, local color table, LZW code size, image data.
+                skip_sub_blocks(data, pos + 10 + gif_color_table_len(packed) + 1)
+            }),
+            _ => None,
+        };
+        match next {
+            Some(next) => pos = next,
+            None => {
+                report
+                    .errors
+                    .push(format!("GIF is truncated or corrupt at byte {pos}"));
+                break;
+            }
+        }
+    }
+
+    if frames == 0 {
+        report.errors.push("GIF contains no images".into());
+        return;
+    }
+    report.frame_count = frames;
+    report.duration_ms = duration_ms;
+    if frames > 1 && loop_count != Some(0) {
+        report
+            .warnings
+            .push("GIF plays a fixed number of times; stickers normally loop forever".into());
+    }
+}
+
+fn inspect_lottie(data: &[u8], report: &mut StickerReport) {
+    let json: Value = match serde_json::from_slice(data) {
+        Ok(v) => v,
+        Err(e) => {
+            report.errors.push(format!("invalid Lottie JSON: {e}"));
+            return;
+        }
+    };
+    let num = |key: &str| json.get(key).and_then(Value::as_f64);
+
+    for key in ["v", "fr", "ip", "op", "w", "h", "layers"] {
+        if json.get(key).is_none() {
+            report
+                .errors
+                .push(format!("Lottie file is missing the \"{key}\" field"));
+        }
+    }
+    report.width = num("w").unwrap_or(0.0) as u32;
+    report.height = num("h").unwrap_or(0.0) as u32;
+
+    if let (Some(fr), Some(ip), Some(op)) = (num("fr"), num("ip"), num("op")) {
+        if fr <= 0.0 || fr > MAX_LOTTIE_FPS {
+            report
+                .errors
+                .push(format!("frame rate {fr} is outside 1–{MAX_LOTTIE_FPS} fps"));
+        } else {
+            report.frame_count = (op - ip).max(0.0) as u32;
+            report.duration_ms = ((op - ip).max(0.0) / fr * 1000.0) as u64;
+        }
+    }
+
+    // Image assets carry a path (`p`); the sticker renderer only draws
+    // vectors, so any raster layer would show up blank.
+    let rasters = json
+        .get("assets")
+        .and_then(Value::as_array)
+        .map_or(0, |assets| {
+            assets.iter().filter(|a| a.get("p").is_some()).count()
+        });
+    if rasters > 0 {
+        report.errors.push(format!(
+            "Lottie file embeds {rasters} raster image(s); only vector shapes are supported"
+        ));
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Validate a sticker file against upload limits.
+#[tauri::command]
+pub(crate) async fn prepare_sticker(path: String) -> Result<StickerReport, String> {
+    audit::record_file("fileRead", "prepare_sticker", &path);
+    let data = tauri::async_runtime::spawn_blocking(move || -> Result<Vec<u8>, String> {
+        let size = std::fs::metadata(&path).map_err(|e| e.to_string())?.len();
+        if size > MAX_INSPECT_BYTES {
+            return Err(format!(
+                "file is too large ({} MB); stickers must be at most {} KB",
+                size / (1024 * 1024),
+                MAX_FILE_BYTES / 1024
+            ));
+        }
+        std::fs::read(&path).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    let size = data.len() as u64;
+
+    let mut report = if data.starts_with(PNG_SIGNATURE) {
+        let mut report = StickerReport::new(StickerFormat::Png, size);
+        inspect_png(&data, &mut report);
+        report
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        let mut report = StickerReport::new(StickerFormat::Gif, size);
+        inspect_gif(&data, &mut report);
+        report
+    } else if data.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'{') {
+        let mut report = StickerReport::new(StickerFormat::Lottie, size);
+        inspect_lottie(&data, &mut report);
+        report
+    } else {
+        return Err("unsupported sticker format; use PNG, APNG, GIF or Lottie JSON".into());
+    };
+
+    report.check_limits();
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 1x1 GIF of `frames` frames, each shown for `delay_cs` hundredths of
+    /// a second, looping forever.
+    fn gif(frames: usize, delay_cs: u16) -> Vec<u8> {
+        let mut data = b"GIF89a\x01\x00\x01\x00\x80\x00\x00".to_vec();
+        data.extend_from_slice(&[0, 0, 0, 255, 255, 255]); // global colors
+        data.extend_from_slice(b"\x21\xFF\x0BNETSCAPE2.0\x03\x01\x00\x00\x00");
+        for _ in 0..frames {
+            data.extend_from_slice(&[0x21, 0xF9, 4, 0]);
+            data.extend_from_slice(&delay_cs.to_le_bytes());
+            data.extend_from_slice(&[0, 0]);
+            data.extend_from_slice(&[0x2C, 0, 0, 0, 0, 1, 0, 1, 0, 0]);
+            data.extend_from_slice(&[2, 2, 0x44, 0x01, 0]);
+        }
+        data.push(0x3B);
+        data
+    }
+
+    fn inspect(data: &[u8]) -> StickerReport {
+        let mut report = StickerReport::new(StickerFormat::Gif, data.len() as u64);
+        inspect_gif(data, &mut report);
+        report.check_limits();
+        report
+    }
+
+    #[test]
+    fn sums_gif_frame_delays() {
+        let report = inspect(&gif(3, 50));
+        assert_eq!((report.width, report.height), (1, 1));
+        assert_eq!(report.frame_count, 3);
+        assert_eq!(report.duration_ms, 1500);
+        assert!(report.errors.is_empty() && report.warnings.is_empty());
+
+        // 60 frames at 0.1 s is over the duration limit.
+        let report = inspect(&gif(60, 10));
+        assert_eq!(report.duration_ms, 6000);
+        assert_eq!(report.errors.len(), 1);
+
+        let data = gif(2, 10);
+        let report = inspect(&data[..data.len() - 4]);
+        assert!(report.errors[0].starts_with("GIF is truncated"));
+    }
+}
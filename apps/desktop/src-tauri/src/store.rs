@@ -0,0 +1,234 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{de::DeserializeOwned, Serialize};
+use tauri::{Emitter, Manager};
+
+use crate::{audit, guest, APP_HANDLE};
+
+pub(crate) mod maintenance;
+
+// ===========================================================================
+// Native Store
+// ===========================================================================
+//
+// Small JSON documents owned by native subsystems (schedules, digests,
+// reminders, ...), one file per document under `<app data>/native/`. The
+// frontend's own persisted state stays in the webview; this is only for
+// state that native code must read before — or without — the UI.
+//
+// In guest mode (`--guest`) the directory lives under the per-session temp
+// directory instead and is deleted on exit.
+//
+// Documents are read whole and written whole. A save is journaled so that
+// neither a crash nor a power cut can lose a document:
+//
+//   1. the new version is written to `<name>.json.tmp` and flushed to disk
+//   2. the current version becomes the snapshot `<name>.json.bak`
+//   3. the temp file is renamed over `<name>.json` (and, on Unix, the
+//      directory is flushed so the rename itself is durable)
+//
+// A document that is missing or no longer parses when loaded is restored
+// from the temp file — a finished write whose rename didn't happen — or
+// else from the snapshot. The damaged file is kept as `<name>.corrupt` and
+// `store-recovered` is emitted with the document name. Maintenance and
+// `verify_store_integrity` run the same check over every document.
+//
+// The snapshot and `.corrupt` copy hold whatever the document held before.
+// When the user deletes something (dismissing a mention, turning edit
+// history off), save with `save_cleared`, which removes both once the new
+// version is in place, so the deleted data doesn't live on in them. It also
+// clears the audit log, which may name what was deleted.
+// ===========================================================================
+
+/// Directory holding all native store documents.
+pub(crate) fn dir() -> Option<PathBuf> {
+    let dir = match guest::data_dir() {
+        Some(guest_dir) => guest_dir.join("native"),
+        None => APP_HANDLE.get()?.path().app_data_dir().ok()?.join("native"),
+    };
+    std::fs::create_dir_all(&dir).ok()?;
+    Some(dir)
+}
+
+fn path(name: &str) -> Option<PathBuf> {
+    Some(dir()?.join(format!("{name}.json")))
+}
+
+/// A save in progress (or interrupted after its data was flushed).
+fn temp_path(path: &Path) -> PathBuf {
+    path.with_extension("json.tmp")
+}
+
+/// The version before the last save.
+fn snapshot_path(path: &Path) -> PathBuf {
+    path.with_extension("json.bak")
+}
+
+/// Whether a file exists and holds well-formed JSON.
+fn is_valid(path: &Path) -> bool {
+    std::fs::read(path)
+        .ok()
+        .is_some_and(|bytes| serde_json::from_slice::<serde_json::Value>(&bytes).is_ok())
+}
+
+fn write_synced(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()
+}
+
+/// Make renames in `dir` durable. Windows journals renames itself.
+#[cfg(unix)]
+fn sync_dir(dir: &Path) {
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) {}
+
+/// Result of checking one document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Repair {
+    Healthy,
+    /// Neither the document nor anything to restore it from exists.
+    Missing,
+    /// Restored from the temp file or the snapshot.
+    Recovered,
+    /// Damaged with nothing valid to restore; moved aside as `.corrupt`.
+    Unrecoverable,
+    /// Couldn't be read (locked, permissions) — not known to be damaged, so
+    /// left alone.
+    Unreadable,
+}
+
+/// Check the document at `path` and restore it if it is missing or damaged.
+fn repair(path: &Path) -> Repair {
+    let exists = match std::fs::read(path) {
+        Ok(bytes) if serde_json::from_slice::<serde_json::Value>(&bytes).is_ok() => {
+            return Repair::Healthy;
+        }
+        Ok(_) => true,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => false,
+        Err(_) => return Repair::Unreadable,
+    };
+    let tmp = temp_path(path);
+    let source = [tmp.clone(), snapshot_path(path)]
+        .into_iter()
+        .find(|candidate| is_valid(candidate));
+    if exists {
+        let _ = std::fs::rename(path, path.with_extension("corrupt"));
+    }
+    let Some(source) = source else {
+        return if exists {
+            Repair::Unrecoverable
+        } else {
+            Repair::Missing
+        };
+    };
+
+    // Stage a copy of the snapshot as the temp file, so the restore is a
+    // rename like any other save and the snapshot survives it.
+    if source != tmp {
+        let staged = std::fs::read(&source).and_then(|bytes| write_synced(&tmp, &bytes));
+        if staged.is_err() {
+            return Repair::Unrecoverable;
+        }
+    }
+    if std::fs::rename(&tmp, path).is_err() {
+        return Repair::Unrecoverable;
+    }
+    if let Some(dir) = path.parent() {
+        sync_dir(dir);
+    }
+
+    if let (Some(handle), Some(name)) = (APP_HANDLE.get(), path.file_stem()) {
+        let _ = handle.emit("store-recovered", name.to_string_lossy());
+    }
+    Repair::Recovered
+}
+
+/// Load a document, falling back to `T::default()` if it is missing or
+/// unreadable. A damaged document is first restored if possible (see the
+/// module notes); one that parses but doesn't fit `T` is left alone.
+pub(crate) fn load<T: DeserializeOwned + Default>(name: &str) -> T {
+    let Some(path) = path(name) else {
+        return T::default();
+    };
+    let parse = |path: &Path| {
+        std::fs::read(path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+    };
+    if let Some(value) = parse(&path) {
+        return value;
+    }
+    if is_valid(&path) || repair(&path) != Repair::Recovered {
+        return T::default();
+    }
+    parse(&path).unwrap_or_default()
+}
+
+/// Persist a document durably (flushed temp file, snapshot of the previous
+/// version, then rename).
+pub(crate) fn save<T: Serialize>(name: &str, value: &T) -> Result<(), String> {
+    let path = path(name).ok_or("native store directory unavailable")?;
+    let tmp = temp_path(&path);
+    let bytes = serde_json::to_vec_pretty(value).map_err(|e| e.to_string())?;
+    write_synced(&tmp, &bytes).map_err(|e| e.to_string())?;
+
+    // Hard-linking keeps the document in place until the rename replaces
+    // it; copy where the filesystem has no links. A damaged current version
+    // must not replace a good snapshot.
+    if is_valid(&path) {
+        let snapshot = snapshot_path(&path);
+        let _ = std::fs::remove_file(&snapshot);
+        if std::fs::hard_link(&path, &snapshot).is_err() {
+            let _ = std::fs::copy(&path, &snapshot);
+        }
+    }
+
+    std::fs::rename(&tmp, &path).map_err(|e| e.to_string())?;
+    if let Some(dir) = path.parent() {
+        sync_dir(dir);
+    }
+    Ok(())
+}
+
+/// `save` a version the user removed data from, then delete the snapshot and
+/// any `.corrupt` copy that still hold it. The document goes without a
+/// snapshot until its next save.
+pub(crate) fn save_cleared<T: Serialize>(name: &str, value: &T) -> Result<(), String> {
+    save(name, value)?;
+    let path = path(name).ok_or("native store directory unavailable")?;
+    for stale in [snapshot_path(&path), path.with_extension("corrupt")] {
+        if let Err(e) = std::fs::remove_file(&stale) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                return Err(e.to_string());
+            }
+        }
+    }
+    if let Some(dir) = path.parent() {
+        sync_dir(dir);
+    }
+    audit::clear();
+    Ok(())
+}
+
+/// Per-process counter making `new_id` unique within the same millisecond.
+static ID_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+/// Unique id for a stored record: creation time (ms) plus a counter, hex.
+pub(crate) fn new_id() -> String {
+    let ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let n = ID_COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{ms:x}-{:x}-{n:x}", std::process::id())
+}
@@ -0,0 +1,253 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+use super::Repair;
+use crate::{scheduler, APP_HANDLE};
+
+// ===========================================================================
+// Store Maintenance
+// ===========================================================================
+//
+// Periodic housekeeping for the native store directory, run weekly from the
+// scheduler (and on demand via `run_store_maintenance(now)`):
+//
+//   1. remove temp files left behind by interrupted writes, where the
+//      document itself is intact
+//   2. restore documents that are missing or no longer parse from their
+//      journal or snapshot (see `store`); ones with nothing to restore from
+//      are moved aside as `<name>.corrupt`, so the owning feature starts
+//      from defaults instead of failing every load
+//   3. delete quarantined files older than `QUARANTINE_DAYS`
+//
+// Each step emits `store-maintenance-progress`; the final report is also
+// emitted as `store-maintenance-finished` and persisted so the next run can
+// be scheduled relative to it. There is no database to vacuum — documents
+// are rewritten whole on every save, so they never fragment.
+//
+// `verify_store_integrity` runs step 2 alone, on demand.
+// ===========================================================================
+
+const STATE_DOC: &str = "maintenance";
+const SCHEDULER_KEY: &str = "store:maintenance";
+const MS_PER_DAY: u64 = 24 * 60 * 60 * 1000;
+const INTERVAL_MS: u64 = 7 * MS_PER_DAY;
+/// Delay after launch before an overdue run, to stay off the startup path.
+const STARTUP_DELAY_MS: u64 = 10 * 60 * 1000;
+const QUARANTINE_DAYS: u64 = 30;
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct MaintenanceReport {
+    /// Milliseconds since the Unix epoch.
+    pub ran_at: u64,
+    pub temp_files_removed: u32,
+    pub documents_checked: u32,
+    /// Names of documents restored from their journal or snapshot.
+    pub recovered: Vec<String>,
+    /// Names of documents moved aside because they failed to parse and
+    /// could not be restored.
+    pub quarantined: Vec<String>,
+    pub quarantine_files_removed: u32,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+/// Result of `verify_store_integrity`.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct IntegrityReport {
+    pub documents_checked: u32,
+    /// Documents restored from their journal or snapshot.
+    pub recovered: Vec<String>,
+    /// Damaged documents with nothing to restore from; their features now
+    /// start from defaults (the damaged file is kept as `.corrupt`).
+    pub unrecoverable: Vec<String>,
+    /// Documents that couldn't be read, e.g. locked by another process.
+    pub unreadable: Vec<String>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Progress {
+    step: &'static str,
+    done: u32,
+    total: u32,
+}
+
+fn emit_progress(step: &'static str, done: u32, total: u32) {
+    if let Some(handle) = APP_HANDLE.get() {
+        let _ = handle.emit("store-maintenance-progress", Progress { step, done, total });
+    }
+}
+
+fn dir_size(dir: &std::path::Path) -> u64 {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|e| e.metadata().ok())
+                .filter(|m| m.is_file())
+                .map(|m| m.len())
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// Names of every document with a file in `dir` — the document itself, its
+/// temp file or its snapshot.
+fn document_names(dir: &Path) -> Result<BTreeSet<String>, String> {
+    Ok(std::fs::read_dir(dir)
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            [".json", ".json.tmp", ".json.bak"]
+                .iter()
+                .find_map(|suffix| file_name.strip_suffix(suffix))
+                .map(str::to_string)
+        })
+        .collect())
+}
+
+/// Check every document, restoring or quarantining damaged ones.
+fn check_documents(dir: &Path) -> Result<IntegrityReport, String> {
+    let mut report = IntegrityReport::default();
+    for name in document_names(dir)? {
+        report.documents_checked += 1;
+        match super::repair(&dir.join(format!("{name}.json"))) {
+            Repair::Recovered => report.recovered.push(name),
+            Repair::Unrecoverable => report.unrecoverable.push(name),
+            Repair::Unreadable => report.unreadable.push(name),
+            Repair::Healthy | Repair::Missing => {}
+        }
+    }
+    Ok(report)
+}
+
+fn run() -> Result<MaintenanceReport, String> {
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Err("store maintenance is already running".into());
+    }
+    let result = run_inner();
+    RUNNING.store(false, Ordering::SeqCst);
+
+    let report = result?;
+    let _ = super::save(STATE_DOC, &report);
+    if let Some(handle) = APP_HANDLE.get() {
+        let _ = handle.emit("store-maintenance-finished", report.clone());
+    }
+    arm(report.ran_at + INTERVAL_MS);
+    Ok(report)
+}
+
+fn run_inner() -> Result<MaintenanceReport, String> {
+    let dir = super::dir().ok_or("native store directory unavailable")?;
+    let now = scheduler::now_ms();
+    let mut report = MaintenanceReport {
+        ran_at: now,
+        bytes_before: dir_size(&dir),
+        ..MaintenanceReport::default()
+    };
+    let files: Vec<_> = std::fs::read_dir(&dir)
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .collect();
+
+    // Leftover temp files. One next to a damaged document may be what
+    // restores it in the next step.
+    for path in files
+        .iter()
+        .filter(|p| p.to_string_lossy().ends_with(".json.tmp"))
+        .filter(|p| super::is_valid(&p.with_extension("")))
+    {
+        if std::fs::remove_file(path).is_ok() {
+            report.temp_files_removed += 1;
+        }
+    }
+    emit_progress("tempFiles", 1, 3);
+
+    // Missing or unparseable documents.
+    let documents = check_documents(&dir)?;
+    report.documents_checked = documents.documents_checked;
+    report.recovered = documents.recovered;
+    report.quarantined = documents.unrecoverable;
+    emit_progress("documents", 2, 3);
+
+    // Old quarantine files.
+    let cutoff = std::time::Duration::from_millis(QUARANTINE_DAYS * MS_PER_DAY);
+    for path in files
+        .iter()
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("corrupt"))
+    {
+        let expired = std::fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.elapsed().ok())
+            .is_some_and(|age| age > cutoff);
+        if expired && std::fs::remove_file(path).is_ok() {
+            report.quarantine_files_removed += 1;
+        }
+    }
+    emit_progress("quarantine", 3, 3);
+
+    report.bytes_after = dir_size(&dir);
+    Ok(report)
+}
+
+/// Scheduler task.
+fn scheduled_run(_key: &str) {
+    if run().is_err() {
+        // Already running or the directory is gone; try again next interval.
+        arm(scheduler::now_ms() + INTERVAL_MS);
+    }
+}
+
+fn arm(at: u64) {
+    scheduler::schedule(SCHEDULER_KEY, at, scheduled_run);
+}
+
+/// Schedule the next run relative to the last one.
+pub(crate) fn init() {
+    let last: MaintenanceReport = super::load(STATE_DOC);
+    let earliest = scheduler::now_ms() + STARTUP_DELAY_MS;
+    arm((last.ran_at + INTERVAL_MS).max(earliest));
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Run maintenance now (`now = true`, returns the report) or just make sure
+/// a background run is scheduled soon (`now = false`, returns the last one).
+#[tauri::command]
+pub(crate) async fn run_store_maintenance(now: bool) -> Result<MaintenanceReport, String> {
+    if now {
+        tauri::async_runtime::spawn_blocking(run)
+            .await
+            .map_err(|e| e.to_string())?
+    } else {
+        arm(scheduler::now_ms() + STARTUP_DELAY_MS);
+        Ok(super::load(STATE_DOC))
+    }
+}
+
+/// Check every native store document now, restoring damaged or missing
+/// ones from their journal or snapshot. Features that already loaded a
+/// document keep their in-memory copy and write it back on their next save.
+#[tauri::command]
+pub(crate) async fn verify_store_integrity() -> Result<IntegrityReport, String> {
+    tauri::async_runtime::spawn_blocking(|| {
+        let dir = super::dir().ok_or("native store directory unavailable")?;
+        check_documents(&dir)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,1133 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+use crate::{scheduler, APP_HANDLE};
+
+// ===========================================================================
+// Local Time Resolution
+// ===========================================================================
+//
+// Resolves `<t:unix:style>` message timestamps (see `markdown.rs`) to local
+// wall-clock time using the OS time zone database rather than the
+// webview's, whose notion of the zone is fixed when the page loads:
+//
+//   Windows:     `SystemTimeToTzSpecificLocalTimeEx` with the dynamic zone
+//                (historical and future DST rules from the registry)
+//   macOS/Linux: the TZif file behind `$TZ` or `/etc/localtime`, including
+//                its POSIX footer rule for instants past the last listed
+//                transition
+//
+// The zone is re-read once a minute on the shared scheduler thread; when it
+// differs from the cached one, `timezone-changed` is emitted with the new
+// `TimeZoneInfo` so rendered timestamps can be resolved again.
+//
+// Text is formatted in English, like Discord's defaults: `t` "4:20 PM",
+// `T` "4:20:30 PM", `d` "10/16/2026", `D` "October 16, 2026", `f` (default)
+// "October 16, 2026 4:20 PM", `F` "Friday, October 16, 2026 4:20 PM", `R`
+// "in 2 hours". The broken-down local fields are returned too, for UIs that
+// format with their own locale.
+//
+// The same zone backs everything native that works in local wall-clock
+// time (digest slots, quiet hours, macro dates) through `local_time_at` and
+// `local_to_utc`, so none of it depends on an offset sent by the webview.
+// ===========================================================================
+
+/// How often the OS zone is re-read, in ms.
+const CHECK_INTERVAL_MS: u64 = 60_000;
+
+/// Scheduler key for the zone check.
+const CHECK_KEY: &str = "timezone-check";
+
+/// Largest timestamp accepted, in seconds either side of the epoch (the
+/// range of a JS `Date`).
+const MAX_TIMESTAMP: i64 = 8_640_000_000_000;
+
+const SECS_PER_DAY: i64 = 86_400;
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+/// UTC offset in effect at some instant.
+#[derive(Debug, Clone, PartialEq)]
+struct Offset {
+    /// Seconds east of UTC.
+    secs: i32,
+    is_dst: bool,
+    /// Abbreviation ("CEST"); Windows only has full names ("Pacific
+    /// Daylight Time").
+    abbreviation: String,
+}
+
+impl Offset {
+    fn utc() -> Self {
+        Offset {
+            secs: 0,
+            is_dst: false,
+            abbreviation: "UTC".into(),
+        }
+    }
+}
+
+#[cfg(unix)]
+type Rules = tzif::Rules;
+#[cfg(target_os = "windows")]
+type Rules = win32::DYNAMIC_TIME_ZONE_INFORMATION;
+
+/// The OS time zone as last read.
+#[derive(Debug, Clone, PartialEq)]
+struct Zone {
+    /// IANA name ("Europe/Berlin") or Windows key name ("W. Europe Standard
+    /// Time").
+    name: String,
+    /// `None` if the zone could not be read; everything resolves as UTC.
+    rules: Option<Rules>,
+}
+
+static ZONE: Mutex<Option<Zone>> = Mutex::new(None);
+
+/// The current zone, as reported by `get_time_zone` and `timezone-changed`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TimeZoneInfo {
+    pub name: String,
+    /// Current offset east of UTC.
+    pub offset_minutes: i32,
+    pub abbreviation: String,
+    pub is_dst: bool,
+}
+
+/// One timestamp to resolve.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TimestampRequest {
+    /// Seconds since the Unix epoch, as written in `<t:...>`.
+    pub ts: i64,
+    /// One of `tTdDfFR`; defaults to `f`.
+    #[serde(default)]
+    pub style: Option<String>,
+}
+
+/// Broken-down local time.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LocalTime {
+    pub year: i64,
+    /// 1–12.
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    /// 0 = Sunday.
+    pub weekday: u32,
+}
+
+/// A resolved timestamp.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ResolvedTimestamp {
+    pub ts: i64,
+    pub style: String,
+    /// Formatted for `style`.
+    pub text: String,
+    pub local: LocalTime,
+    /// Offset east of UTC in effect at `ts`.
+    pub offset_minutes: i32,
+    pub abbreviation: String,
+    pub is_dst: bool,
+}
+
+// ---------------------------------------------------------------------------
+// Calendar math
+// ---------------------------------------------------------------------------
+
+/// Days since 1970-01-01 for a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = i64::from((month + 9) % 12);
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// (year, month, day) for a day count since 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn is_leap(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn local_time(local_secs: i64) -> LocalTime {
+    let days = local_secs.div_euclid(SECS_PER_DAY);
+    let secs = local_secs.rem_euclid(SECS_PER_DAY) as u32;
+    let (year, month, day) = civil_from_days(days);
+    LocalTime {
+        year,
+        month,
+        day,
+        hour: secs / 3600,
+        minute: secs / 60 % 60,
+        second: secs % 60,
+        // 1970-01-01 was a Thursday.
+        weekday: (days + 4).rem_euclid(7) as u32,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// TZif (macOS/Linux)
+// ---------------------------------------------------------------------------
+
+#[cfg(unix)]
+mod tzif {
+    use super::{civil_from_days, days_from_civil, is_leap, Offset, SECS_PER_DAY};
+
+    /// A parsed TZif file (RFC 8536).
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Rules {
+        /// Transition instants, ascending.
+        transitions: Vec<i64>,
+        /// Index into `types` taking effect at each transition.
+        indices: Vec<u8>,
+        types: Vec<Offset>,
+        /// Rule for instants after the last transition.
+        footer: Option<Posix>,
+    }
+
+    /// A POSIX `TZ` rule such as `CET-1CEST,M3.5.0,M10.5.0/3`.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Posix {
+        std: Offset,
+        dst: Option<Dst>,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Dst {
+        offset: Offset,
+        /// Start and end dates, with the local time of day (seconds) at
+        /// which each transition happens.
+        start: (Date, i32),
+        end: (Date, i32),
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Date {
+        /// `Jn`: day 1–365, February 29 never counted.
+        Julian(u16),
+        /// `n`: day 0–365, February 29 counted.
+        Ordinal(u16),
+        /// `Mm.w.d`: weekday `d` of week `w` (5 = last) of month `m`.
+        Weekday { month: u8, week: u8, weekday: u8 },
+    }
+
+    impl Rules {
+        pub fn offset_at(&self, t: i64) -> Offset {
+            let n = self.transitions.partition_point(|&at| at <= t);
+            if n == 0 {
+                if self.transitions.is_empty() {
+                    if let Some(footer) = &self.footer {
+                        return footer.offset_at(t);
+                    }
+                }
+                // Before the first transition: the first standard-time type.
+                return self
+                    .types
+                    .iter()
+                    .find(|ty| !ty.is_dst)
+                    .unwrap_or(&self.types[0])
+                    .clone();
+            }
+            if n == self.transitions.len() {
+                if let Some(footer) = &self.footer {
+                    return footer.offset_at(t);
+                }
+            }
+            self.types[usize::from(self.indices[n - 1])].clone()
+        }
+
+        /// Rules from a bare POSIX `TZ` value with no file behind it.
+        pub fn from_posix(tz: &str) -> Option<Rules> {
+            let footer = Posix::parse(tz)?;
+            Some(Rules {
+                transitions: Vec::new(),
+                indices: Vec::new(),
+                types: vec![footer.std.clone()],
+                footer: Some(footer),
+            })
+        }
+    }
+
+    /// Parse a TZif file, preferring the 64-bit data block of v2+ files.
+    pub fn parse(data: &[u8]) -> Option<Rules> {
+        // isutcnt, isstdcnt, leapcnt, timecnt, typecnt, charcnt
+        let header = |at: usize| -> Option<[usize; 6]> {
+            if data.get(at..at + 4)? != b"TZif" {
+                return None;
+            }
+            let mut counts = [0usize; 6];
+            for (i, count) in counts.iter_mut().enumerate() {
+                let at = at + 20 + i * 4;
+                *count = u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?) as usize;
+            }
+            Some(counts)
+        };
+
+        let counts = header(0)?;
+        let (mut pos, time_size, counts) = if *data.get(4)? >= b'2' {
+            let [isut, isstd, leap, time, types, chars] = counts;
+            let v1_len = time * 5 + types * 6 + chars + leap * 8 + isstd + isut;
+            (44 + v1_len + 44, 8, header(44 + v1_len)?)
+        } else {
+            (44, 4, counts)
+        };
+        let [isut, isstd, leap, time, types, chars] = counts;
+
+        let mut take = |len: usize| -> Option<&[u8]> {
+            let bytes = data.get(pos..pos + len)?;
+            pos += len;
+            Some(bytes)
+        };
+        let transitions = take(time * time_size)?
+            .chunks_exact(time_size)
+            .map(|c| match time_size {
+                8 => i64::from_be_bytes(c.try_into().unwrap()),
+                _ => i64::from(i32::from_be_bytes(c.try_into().unwrap())),
+            })
+            .collect();
+        let indices = take(time)?.to_vec();
+        let raw_types = take(types * 6)?;
+        let abbreviations = take(chars)?;
+        take(leap * (time_size + 4) + isstd + isut)?;
+
+        let types: Vec<Offset> = raw_types
+            .chunks_exact(6)
+            .map(|c| {
+                let name = abbreviations.get(usize::from(c[5])..).unwrap_or_default();
+                let end = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+                Offset {
+                    secs: i32::from_be_bytes(c[..4].try_into().unwrap()),
+                    is_dst: c[4] != 0,
+                    abbreviation: String::from_utf8_lossy(&name[..end]).into_owned(),
+                }
+            })
+            .collect();
+        if types.is_empty() || indices.iter().any(|&i| usize::from(i) >= types.len()) {
+            return None;
+        }
+
+        // The footer follows the v2+ data block: `\n<TZ string>\n`.
+        let footer = match time_size {
+            8 => data
+                .get(pos..)
+                .and_then(|rest| rest.strip_prefix(b"\n"))
+                .and_then(|rest| {
+                    let end = rest.iter().position(|&b| b == b'\n')?;
+                    Posix::parse(std::str::from_utf8(&rest[..end]).ok()?)
+                }),
+            _ => None,
+        };
+
+        Some(Rules {
+            transitions,
+            indices,
+            types,
+            footer,
+        })
+    }
+
+    impl Posix {
+        fn parse(tz: &str) -> Option<Posix> {
+            let mut cursor = Cursor {
+                s: tz.as_bytes(),
+                pos: 0,
+            };
+            let std_name = cursor.name()?;
+            let std = Offset {
+                secs: -cursor.time()?,
+                is_dst: false,
+                abbreviation: std_name,
+            };
+            if cursor.done() {
+                return Some(Posix { std, dst: None });
+            }
+
+            let dst_name = cursor.name()?;
+            let dst_secs = match cursor.peek() {
+                Some(b',') | None => std.secs + 3600,
+                _ => -cursor.time()?,
+            };
+            // No rule: US rules, as POSIX leaves it implementation-defined
+            // and glibc does the same.
+            let (start, end) = if cursor.done() {
+                (
+                    (
+                        Date::Weekday {
+                            month: 3,
+                            week: 2,
+                            weekday: 0,
+                        },
+                        7200,
+                    ),
+                    (
+                        Date::Weekday {
+                            month: 11,
+                            week: 1,
+                            weekday: 0,
+                        },
+                        7200,
+                    ),
+                )
+            } else {
+                (cursor.rule()?, cursor.rule()?)
+            };
+            if !cursor.done() {
+                return None;
+            }
+
+            Some(Posix {
+                std,
+                dst: Some(Dst {
+                    offset: Offset {
+                        secs: dst_secs,
+                        is_dst: true,
+                        abbreviation: dst_name,
+                    },
+                    start,
+                    end,
+                }),
+            })
+        }
+
+        fn offset_at(&self, t: i64) -> Offset {
+            let Some(dst) = &self.dst else {
+                return self.std.clone();
+            };
+            let std_secs = i64::from(self.std.secs);
+            let year = civil_from_days((t + std_secs).div_euclid(SECS_PER_DAY)).0;
+            // The start is given in standard time, the end in daylight time.
+            let start = dst.start.0.day(year) * SECS_PER_DAY + i64::from(dst.start.1) - std_secs;
+            let end = dst.end.0.day(year) * SECS_PER_DAY + i64::from(dst.end.1)
+                - i64::from(dst.offset.secs);
+            // Southern-hemisphere zones start DST late in the year.
+            let in_dst = if start < end {
+                (start..end).contains(&t)
+            } else {
+                !(end..start).contains(&t)
+            };
+            if in_dst {
+                dst.offset.clone()
+            } else {
+                self.std.clone()
+            }
+        }
+    }
+
+    impl Date {
+        /// Days since 1970-01-01 on which this rule falls in `year`.
+        fn day(self, year: i64) -> i64 {
+            let jan1 = days_from_civil(year, 1, 1);
+            match self {
+                Date::Julian(n) => {
+                    let n = i64::from(n);
+                    jan1 + n - 1 + i64::from(is_leap(year) && n >= 60)
+                }
+                Date::Ordinal(n) => jan1 + i64::from(n),
+                Date::Weekday {
+                    month,
+                    week,
+                    weekday,
+                } => {
+                    let month = u32::from(month);
+                    let first = days_from_civil(year, month, 1);
+                    let next = match month {
+                        12 => days_from_civil(year + 1, 1, 1),
+                        _ => days_from_civil(year, month + 1, 1),
+                    };
+                    let first_weekday = (first + 4).rem_euclid(7);
+                    let mut day = first
+                        + (i64::from(weekday) - first_weekday).rem_euclid(7)
+                        + (i64::from(week) - 1) * 7;
+                    while day >= next {
+                        day -= 7;
+                    }
+                    day
+                }
+            }
+        }
+    }
+
+    struct Cursor<'a> {
+        s: &'a [u8],
+        pos: usize,
+    }
+
+    impl Cursor<'_> {
+        fn peek(&self) -> Option<u8> {
+            self.s.get(self.pos).copied()
+        }
+
+        fn done(&self) -> bool {
+            self.pos == self.s.len()
+        }
+
+        fn eat(&mut self, byte: u8) -> bool {
+            let matched = self.peek() == Some(byte);
+            self.pos += usize::from(matched);
+            matched
+        }
+
+        /// `EST` or `<+0330>`.
+        fn name(&mut self) -> Option<String> {
+            let start = self.pos;
+            let name = if self.eat(b'<') {
+                while !self.eat(b'>') {
+                    self.peek()?;
+                    self.pos += 1;
+                }
+                &self.s[start + 1..self.pos - 1]
+            } else {
+                while self.peek().is_some_and(|b| b.is_ascii_alphabetic()) {
+                    self.pos += 1;
+                }
+                &self.s[start..self.pos]
+            };
+            (name.len() >= 3).then(|| String::from_utf8_lossy(name).into_owned())
+        }
+
+        fn number(&mut self) -> Option<i32> {
+            let start = self.pos;
+            while self.peek().is_some_and(|b| b.is_ascii_digit()) {
+                self.pos += 1;
+            }
+            std::str::from_utf8(&self.s[start..self.pos])
+                .ok()?
+                .parse()
+                .ok()
+        }
+
+        /// `[+-]hh[:mm[:ss]]` in seconds.
+        fn time(&mut self) -> Option<i32> {
+            let sign = if self.eat(b'-') {
+                -1
+            } else {
+                self.eat(b'+');
+                1
+            };
+            let mut secs = self.number()? * 3600;
+            for unit in [60, 1] {
+                if !self.eat(b':') {
+                    break;
+                }
+                secs += self.number()? * unit;
+            }
+            Some(sign * secs)
+        }
+
+        /// `,date[/time]`
+        fn rule(&mut self) -> Option<(Date, i32)> {
+            if !self.eat(b',') {
+                return None;
+            }
+            let date = if self.eat(b'J') {
+                Date::Julian(self.number()?.clamp(1, 365) as u16)
+            } else if self.eat(b'M') {
+                let month = self.number()?;
+                if !self.eat(b'.') {
+                    return None;
+                }
+                let week = self.number()?;
+                if !self.eat(b'.') {
+                    return None;
+                }
+                let weekday = self.number()?;
+                if !(1..=12).contains(&month) || !(1..=5).contains(&week) || weekday > 6 {
+                    return None;
+                }
+                Date::Weekday {
+                    month: month as u8,
+                    week: week as u8,
+                    weekday: weekday as u8,
+                }
+            } else {
+                Date::Ordinal(self.number()?.min(365) as u16)
+            };
+            let time = if self.eat(b'/') { self.time()? } else { 7200 };
+            Some((date, time))
+        }
+    }
+}
+
+#[cfg(unix)]
+fn load_zone() -> Zone {
+    const ZONEINFO: &str = "/usr/share/zoneinfo";
+    const LOCALTIME: &str = "/etc/localtime";
+
+    let read = |path: &std::path::Path| std::fs::read(path).ok().and_then(|d| tzif::parse(&d));
+
+    if let Some(tz) = std::env::var("TZ").ok().filter(|tz| !tz.is_empty()) {
+        let name = tz.strip_prefix(':').unwrap_or(&tz).to_string();
+        let path = if name.starts_with('/') {
+            std::path::PathBuf::from(&name)
+        } else {
+            std::path::Path::new(ZONEINFO).join(&name)
+        };
+        // Not a zone file: a bare rule such as `EST5EDT,M3.2.0,M11.1.0`.
+        let rules = read(&path).or_else(|| tzif::Rules::from_posix(&name));
+        return Zone { name, rules };
+    }
+
+    let rules = read(std::path::Path::new(LOCALTIME));
+    let name = std::fs::read_link(LOCALTIME)
+        .ok()
+        .and_then(|target| {
+            let target = target.to_string_lossy().into_owned();
+            target
+                .split_once("zoneinfo/")
+                .map(|(_, name)| name.to_string())
+        })
+        .or_else(|| {
+            std::fs::read_to_string("/etc/timezone")
+                .ok()
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+        })
+        .unwrap_or_else(|| if rules.is_some() { "localtime" } else { "UTC" }.into());
+    Zone { name, rules }
+}
+
+#[cfg(unix)]
+fn rules_offset(rules: &Rules, t: i64) -> Offset {
+    rules.offset_at(t)
+}
+
+// ---------------------------------------------------------------------------
+// Win32 FFI
+// ---------------------------------------------------------------------------
+
+#[cfg(target_os = "windows")]
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+mod win32 {
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct SYSTEMTIME {
+        pub w_year: u16,
+        pub w_month: u16,
+        pub w_day_of_week: u16,
+        pub w_day: u16,
+        pub w_hour: u16,
+        pub w_minute: u16,
+        pub w_second: u16,
+        pub w_milliseconds: u16,
+    }
+
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct TIME_ZONE_INFORMATION {
+        pub bias: i32,
+        pub standard_name: [u16; 32],
+        pub standard_date: SYSTEMTIME,
+        pub standard_bias: i32,
+        pub daylight_name: [u16; 32],
+        pub daylight_date: SYSTEMTIME,
+        pub daylight_bias: i32,
+    }
+
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct DYNAMIC_TIME_ZONE_INFORMATION {
+        pub bias: i32,
+        pub standard_name: [u16; 32],
+        pub standard_date: SYSTEMTIME,
+        pub standard_bias: i32,
+        pub daylight_name: [u16; 32],
+        pub daylight_date: SYSTEMTIME,
+        pub daylight_bias: i32,
+        pub time_zone_key_name: [u16; 128],
+        pub dynamic_daylight_time_disabled: u8,
+    }
+
+    /// `TIME_ZONE_ID_INVALID`
+    pub const TIME_ZONE_ID_INVALID: u32 = 0xFFFF_FFFF;
+
+    extern "system" {
+        pub fn GetDynamicTimeZoneInformation(info: *mut DYNAMIC_TIME_ZONE_INFORMATION) -> u32;
+        pub fn GetTimeZoneInformationForYear(
+            year: u16,
+            dynamic: *const DYNAMIC_TIME_ZONE_INFORMATION,
+            info: *mut TIME_ZONE_INFORMATION,
+        ) -> i32;
+        pub fn SystemTimeToTzSpecificLocalTimeEx(
+            info: *const DYNAMIC_TIME_ZONE_INFORMATION,
+            universal: *const SYSTEMTIME,
+            local: *mut SYSTEMTIME,
+        ) -> i32;
+    }
+
+    /// NUL-terminated UTF-16 buffer -> String.
+    pub fn wide(buf: &[u16]) -> String {
+        let end = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+        String::from_utf16_lossy(&buf[..end])
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn load_zone() -> Zone {
+    let mut info: win32::DYNAMIC_TIME_ZONE_INFORMATION = unsafe { std::mem::zeroed() };
+    if unsafe { win32::GetDynamicTimeZoneInformation(&mut info) } == win32::TIME_ZONE_ID_INVALID {
+        return Zone {
+            name: "UTC".into(),
+            rules: None,
+        };
+    }
+    Zone {
+        name: win32::wide(&info.time_zone_key_name),
+        rules: Some(info),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn rules_offset(info: &Rules, t: i64) -> Offset {
+    let (year, month, day) = civil_from_days(t.div_euclid(SECS_PER_DAY));
+    let secs = t.rem_euclid(SECS_PER_DAY);
+    let standard = Offset {
+        secs: -(info.bias + info.standard_bias) * 60,
+        is_dst: false,
+        abbreviation: win32::wide(&info.standard_name),
+    };
+    // SYSTEMTIME's range.
+    if !(1601..=30827).contains(&year) {
+        return standard;
+    }
+
+    let universal = win32::SYSTEMTIME {
+        w_year: year as u16,
+        w_month: month as u16,
+        w_day_of_week: 0,
+        w_day: day as u16,
+        w_hour: (secs / 3600) as u16,
+        w_minute: (secs / 60 % 60) as u16,
+        w_second: (secs % 60) as u16,
+        w_milliseconds: 0,
+    };
+    let mut local: win32::SYSTEMTIME = unsafe { std::mem::zeroed() };
+    if unsafe { win32::SystemTimeToTzSpecificLocalTimeEx(info, &universal, &mut local) } == 0 {
+        return standard;
+    }
+    let local_secs = days_from_civil(
+        i64::from(local.w_year),
+        u32::from(local.w_month),
+        u32::from(local.w_day),
+    ) * SECS_PER_DAY
+        + i64::from(local.w_hour) * 3600
+        + i64::from(local.w_minute) * 60
+        + i64::from(local.w_second);
+    let offset = (local_secs - t) as i32;
+
+    // The year's own rules say which of its two offsets this is.
+    let mut year_info: win32::TIME_ZONE_INFORMATION = unsafe { std::mem::zeroed() };
+    if unsafe { win32::GetTimeZoneInformationForYear(year as u16, info, &mut year_info) } == 0 {
+        return Offset {
+            secs: offset,
+            ..standard
+        };
+    }
+    let is_dst = offset != -(year_info.bias + year_info.standard_bias) * 60;
+    Offset {
+        secs: offset,
+        is_dst,
+        abbreviation: win32::wide(if is_dst {
+            &year_info.daylight_name
+        } else {
+            &year_info.standard_name
+        }),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Resolution and formatting
+// ---------------------------------------------------------------------------
+
+fn with_zone<R>(f: impl FnOnce(&Zone) -> R) -> R {
+    let mut zone = ZONE.lock().unwrap();
+    f(zone.get_or_insert_with(load_zone))
+}
+
+fn offset_at(zone: &Zone, t: i64) -> Offset {
+    match &zone.rules {
+        Some(rules) => rules_offset(rules, t),
+        None => Offset::utc(),
+    }
+}
+
+fn zone_info(zone: &Zone) -> TimeZoneInfo {
+    let now = (scheduler::now_ms() / 1000) as i64;
+    let offset = offset_at(zone, now);
+    TimeZoneInfo {
+        name: zone.name.clone(),
+        offset_minutes: offset.secs / 60,
+        abbreviation: offset.abbreviation,
+        is_dst: offset.is_dst,
+    }
+}
+
+/// "4:20 PM" / "4:20:30 PM".
+fn clock_text(local: &LocalTime, seconds: bool) -> String {
+    let hour = match local.hour % 12 {
+        0 => 12,
+        hour => hour,
+    };
+    let meridiem = if local.hour < 12 { "AM" } else { "PM" };
+    if seconds {
+        format!("{hour}:{:02}:{:02} {meridiem}", local.minute, local.second)
+    } else {
+        format!("{hour}:{:02} {meridiem}", local.minute)
+    }
+}
+
+/// "October 16, 2026".
+fn date_text(local: &LocalTime) -> String {
+    let month = MONTHS[local.month as usize - 1];
+    format!("{month} {}, {}", local.day, local.year)
+}
+
+/// "in 2 hours" / "3 days ago", relative to `now`.
+fn relative_text(t: i64, now: i64) -> String {
+    const MINUTE: u64 = 60;
+    const HOUR: u64 = 60 * MINUTE;
+    const DAY: u64 = 24 * HOUR;
+
+    let delta = t - now;
+    let secs = delta.unsigned_abs();
+    let (unit, name) = match secs {
+        0..=44 => {
+            return if delta > 0 {
+                "in a few seconds".into()
+            } else {
+                "a few seconds ago".into()
+            };
+        }
+        _ if secs < 45 * MINUTE => (MINUTE, "minute"),
+        _ if secs < 22 * HOUR => (HOUR, "hour"),
+        _ if secs < 26 * DAY => (DAY, "day"),
+        _ if secs < 320 * DAY => (30 * DAY, "month"),
+        _ => (365 * DAY, "year"),
+    };
+    let count = ((secs + unit / 2) / unit).max(1);
+    let plural = if count == 1 { "" } else { "s" };
+    if delta > 0 {
+        format!("in {count} {name}{plural}")
+    } else {
+        format!("{count} {name}{plural} ago")
+    }
+}
+
+fn resolve(
+    zone: &Zone,
+    ts: i64,
+    style: Option<String>,
+    now: i64,
+) -> Result<ResolvedTimestamp, String> {
+    if !(-MAX_TIMESTAMP..=MAX_TIMESTAMP).contains(&ts) {
+        return Err(format!("timestamp out of range: {ts}"));
+    }
+    let style = style.unwrap_or_else(|| "f".into());
+
+    let offset = offset_at(zone, ts);
+    let local = local_time(ts + i64::from(offset.secs));
+    let text = match style.as_str() {
+        "t" => clock_text(&local, false),
+        "T" => clock_text(&local, true),
+        "d" => format!("{}/{}/{}", local.month, local.day, local.year),
+        "D" => date_text(&local),
+        "f" => format!("{} {}", date_text(&local), clock_text(&local, false)),
+        "F" => format!(
+            "{}, {} {}",
+            WEEKDAYS[local.weekday as usize],
+            date_text(&local),
+            clock_text(&local, false)
+        ),
+        "R" => relative_text(ts, now),
+        other => return Err(format!("unknown timestamp style: {other}")),
+    };
+
+    Ok(ResolvedTimestamp {
+        ts,
+        style,
+        text,
+        local,
+        offset_minutes: offset.secs / 60,
+        abbreviation: offset.abbreviation,
+        is_dst: offset.is_dst,
+    })
+}
+
+/// Unix time of a local wall-clock time in `zone`. Times skipped by a DST
+/// jump land just after it; repeated times take the first occurrence.
+fn utc_from_local(zone: &Zone, local: i64) -> i64 {
+    let offset = |t| i64::from(offset_at(zone, t).secs);
+    let guess = local - offset(local);
+    local - offset(guess).max(offset(guess - 3600))
+}
+
+// ---------------------------------------------------------------------------
+// Wall-clock time for other modules
+// ---------------------------------------------------------------------------
+
+/// Seconds east of UTC in the OS zone at Unix time `t`.
+pub(crate) fn utc_offset_at(t: i64) -> i32 {
+    with_zone(|zone| offset_at(zone, t).secs)
+}
+
+/// Local time in the OS zone at Unix time `t`.
+pub(crate) fn local_time_at(t: i64) -> LocalTime {
+    with_zone(|zone| local_time(t + i64::from(offset_at(zone, t).secs)))
+}
+
+/// Unix time of `local` (seconds since 1970-01-01 on the local calendar)
+/// in the OS zone. See `utc_from_local`.
+pub(crate) fn local_to_utc(local: i64) -> i64 {
+    with_zone(|zone| utc_from_local(zone, local))
+}
+
+/// Re-read the OS zone, emit `timezone-changed` if it moved, and re-arm.
+fn check_zone(_key: &str) {
+    let zone = load_zone();
+    let changed = {
+        let mut current = ZONE.lock().unwrap();
+        let changed = current.as_ref().is_some_and(|current| *current != zone);
+        *current = Some(zone.clone());
+        changed
+    };
+    if changed {
+        if let Some(handle) = APP_HANDLE.get() {
+            let _ = handle.emit("timezone-changed", zone_info(&zone));
+        }
+    }
+    scheduler::schedule(
+        CHECK_KEY,
+        scheduler::now_ms() + CHECK_INTERVAL_MS,
+        check_zone,
+    );
+}
+
+/// Load the zone and start watching it for changes.
+pub(crate) fn init() {
+    check_zone(CHECK_KEY);
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// The OS time zone and its current offset.
+#[tauri::command]
+pub(crate) fn get_time_zone() -> TimeZoneInfo {
+    with_zone(zone_info)
+}
+
+/// Resolve a `<t:ts:style>` timestamp to local time. `style` is one of
+/// `tTdDfFR` (default `f`).
+#[tauri::command]
+pub(crate) fn resolve_timestamp(
+    ts: i64,
+    style: Option<String>,
+) -> Result<ResolvedTimestamp, String> {
+    let now = (scheduler::now_ms() / 1000) as i64;
+    with_zone(|zone| resolve(zone, ts, style, now))
+}
+
+/// Resolve many timestamps at once (a page of messages). Fails if any
+/// request is invalid.
+#[tauri::command]
+pub(crate) fn resolve_timestamps(
+    requests: Vec<TimestampRequest>,
+) -> Result<Vec<ResolvedTimestamp>, String> {
+    let now = (scheduler::now_ms() / 1000) as i64;
+    with_zone(|zone| {
+        requests
+            .into_iter()
+            .map(|request| resolve(zone, request.ts, request.style, now))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Offset seconds and abbreviation at `t`.
+    #[cfg(unix)]
+    fn at(rules: &Rules, t: i64) -> (i32, String) {
+        let offset = rules.offset_at(t);
+        (offset.secs, offset.abbreviation)
+    }
+
+    #[cfg(unix)]
+    fn zone(tz: &str) -> Zone {
+        Zone {
+            name: tz.into(),
+            rules: tzif::Rules::from_posix(tz),
+        }
+    }
+
+    /// A v2 TZif file with an empty v1 block: `transitions` as
+    /// (instant, type index), then `types` as (offset, is_dst, name).
+    #[cfg(unix)]
+    fn tzif_file(transitions: &[(i64, u8)], types: &[(i32, bool, &str)], footer: &str) -> Vec<u8> {
+        let header = |counts: [usize; 6]| {
+            let mut header = b"TZif2".to_vec();
+            header.resize(20, 0);
+            for count in counts {
+                header.extend_from_slice(&(count as u32).to_be_bytes());
+            }
+            header
+        };
+        let mut chars = Vec::new();
+        let mut raw_types = Vec::new();
+        for &(secs, is_dst, name) in types {
+            raw_types.extend_from_slice(&secs.to_be_bytes());
+            raw_types.push(u8::from(is_dst));
+            raw_types.push(chars.len() as u8);
+            chars.extend_from_slice(name.as_bytes());
+            chars.push(0);
+        }
+
+        let mut data = header([0; 6]);
+        data.extend(header([
+            0,
+            0,
+            0,
+            transitions.len(),
+            types.len(),
+            chars.len(),
+        ]));
+        for &(at, _) in transitions {
+            data.extend_from_slice(&at.to_be_bytes());
+        }
+        data.extend(transitions.iter().map(|&(_, index)| index));
+        data.extend(raw_types);
+        data.extend(chars);
+        data.extend_from_slice(format!("\n{footer}\n").as_bytes());
+        data
+    }
+
+    #[test]
+    fn calendar_round_trips() {
+        for (days, date) in [
+            (0, (1970, 1, 1)),
+            (-1, (1969, 12, 31)),
+            (11_016, (2000, 2, 29)),
+            (20_742, (2026, 10, 16)),
+            (47_482, (2100, 1, 1)),
+        ] {
+            assert_eq!(civil_from_days(days), date);
+            assert_eq!(days_from_civil(date.0, date.1, date.2), days);
+        }
+        // 2026-10-16 12:00 UTC, a Friday.
+        let local = local_time(1_792_152_000);
+        assert_eq!((local.hour, local.minute, local.weekday), (12, 0, 5));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn posix_rule_switches_on_the_transition_second() {
+        let rules = tzif::Rules::from_posix("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+        // 2026-03-29 01:00 UTC and 2026-10-25 01:00 UTC.
+        assert_eq!(at(&rules, 1_774_746_000 - 1), (3600, "CET".into()));
+        assert_eq!(at(&rules, 1_774_746_000), (7200, "CEST".into()));
+        assert_eq!(at(&rules, 1_792_890_000 - 1), (7200, "CEST".into()));
+        assert_eq!(at(&rules, 1_792_890_000), (3600, "CET".into()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn posix_rule_handles_southern_and_default_dst() {
+        // DST ends 2026-04-04 16:00 UTC and starts 2026-10-03 16:00 UTC.
+        let sydney = tzif::Rules::from_posix("AEST-10AEDT,M10.1.0,M4.1.0/3").unwrap();
+        assert_eq!(at(&sydney, 1_775_318_400 - 1).0, 39_600);
+        assert_eq!(at(&sydney, 1_775_318_400).0, 36_000);
+        assert_eq!(at(&sydney, 1_791_043_200 - 1).0, 36_000);
+        assert_eq!(at(&sydney, 1_791_043_200).0, 39_600);
+        assert_eq!(at(&sydney, 1_785_585_600).1, "AEST");
+
+        // No rule: the US one, 2026-03-08 07:00 UTC to 2026-11-01 06:00 UTC.
+        let us = tzif::Rules::from_posix("EST5EDT").unwrap();
+        assert_eq!(at(&us, 1_772_953_200 - 1), (-18_000, "EST".into()));
+        assert_eq!(at(&us, 1_772_953_200), (-14_400, "EDT".into()));
+        assert_eq!(at(&us, 1_793_512_800), (-18_000, "EST".into()));
+
+        assert_eq!(
+            at(&tzif::Rules::from_posix("<+0530>-5:30").unwrap(), 0).0,
+            19_800
+        );
+        assert!(tzif::Rules::from_posix("CET-1CEST,M13.5.0,M10.5.0").is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn tzif_transitions_then_footer() {
+        let data = tzif_file(
+            &[(1_000, 1), (2_000, 0)],
+            &[(3600, false, "AAA"), (7200, true, "BBB")],
+            "CET-1CEST,M3.5.0,M10.5.0/3",
+        );
+        let rules = tzif::parse(&data).unwrap();
+        assert_eq!(at(&rules, 999), (3600, "AAA".into()));
+        assert_eq!(at(&rules, 1_000), (7200, "BBB".into()));
+        assert_eq!(at(&rules, 1_999), (7200, "BBB".into()));
+        // Past the last transition the footer decides.
+        assert_eq!(at(&rules, 2_000), (3600, "CET".into()));
+        assert_eq!(at(&rules, 1_774_746_000), (7200, "CEST".into()));
+
+        let bad = tzif_file(&[(1_000, 2)], &[(3600, false, "AAA")], "");
+        assert!(tzif::parse(&bad).is_none());
+        assert!(tzif::parse(&data[..60]).is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn local_times_map_back_across_dst() {
+        let zone = zone("CET-1CEST,M3.5.0,M10.5.0/3");
+        // 2026-10-16 14:00 CEST.
+        assert_eq!(utc_from_local(&zone, 1_792_152_000 + 7200), 1_792_152_000);
+        // 02:30 on 2026-03-29 doesn't exist; it lands at 03:30 CEST.
+        assert_eq!(utc_from_local(&zone, 1_774_751_400), 1_774_747_800);
+        // 02:30 on 2026-10-25 happens twice; the CEST one comes first.
+        assert_eq!(utc_from_local(&zone, 1_792_895_400), 1_792_888_200);
+    }
+}
@@ -0,0 +1,352 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri_plugin_http::reqwest;
+
+use crate::{audit, store};
+
+// ===========================================================================
+// Message Translation
+// ===========================================================================
+//
+// `translate_text(text, targetLang)` behind a small provider abstraction:
+//
+//   remote — any LibreTranslate-compatible endpoint (self-hosted or public),
+//            optional API key. The key stays in the native store and never
+//            reaches the frontend bundle.
+//   local  — an offline command-line translator such as Argos Translate
+//            (`argos-translate --from en --to de`, message on stdin). Works
+//            with no network at all once the language packs are installed.
+//            The message never goes on the command line, where text like
+//            `--model-dir …` in someone else's message would be taken as
+//            options. The program must exist when it is configured; it is
+//            stored as an absolute path and the choice is audited.
+//
+// Results are cached per (text, target) so re-opening a translated message
+// is free. The source language is auto-detected: remote endpoints are asked
+// with `source: "auto"`; for local providers a lightweight script/stop-word
+// detector picks the source, since CLI translators need an explicit one.
+// ===========================================================================
+
+const STORE_DOC: &str = "translation";
+const CACHE_CAPACITY: usize = 1024;
+
+/// Which backend to translate with.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub(crate) enum TranslationProvider {
+    #[serde(rename_all = "camelCase")]
+    Remote {
+        endpoint: String,
+        api_key: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Local {
+        /// Executable to run; receives `--from <src> --to <dst>` and the
+        /// text on stdin.
+        command: String,
+    },
+}
+
+/// A translated message.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Translation {
+    pub text: String,
+    /// Detected source language (ISO 639-1), if known.
+    pub detected_source: Option<String>,
+    pub provider: &'static str,
+}
+
+#[derive(Default)]
+struct Cache {
+    entries: HashMap<u64, Translation>,
+    order: VecDeque<u64>,
+}
+
+static CACHE: Mutex<Option<Cache>> = Mutex::new(None);
+
+fn cache_key(text: &str, target: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    target.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn cache_get(key: u64) -> Option<Translation> {
+    CACHE.lock().unwrap().as_ref()?.entries.get(&key).cloned()
+}
+
+fn cache_put(key: u64, translation: Translation) {
+    let mut guard = CACHE.lock().unwrap();
+    let cache = guard.get_or_insert_with(Cache::default);
+    if cache.entries.insert(key, translation).is_none() {
+        cache.order.push_back(key);
+    }
+    while cache.order.len() > CACHE_CAPACITY {
+        if let Some(oldest) = cache.order.pop_front() {
+            cache.entries.remove(&oldest);
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Language detection
+// ---------------------------------------------------------------------------
+
+/// Common short words per Latin-script language.
+const STOP_WORDS: &[(&str, &[&str])] = &[
+    ("en", &["the", "and", "is", "you", "that", "it", "to", "of", "what", "this"]),
+    ("de", &["der", "die", "und", "ist", "nicht", "das", "ich", "du", "ein", "mit"]),
+    ("fr", &["le", "la", "et", "est", "les", "des", "je", "pas", "une", "que"]),
+    ("es", &["el", "la", "y", "es", "los", "que", "no", "una", "por", "con"]),
+    ("it", &["il", "di", "che", "è", "non", "una", "per", "sono", "gli", "della"]),
+    ("pt", &["o", "que", "não", "uma", "os", "com", "para", "é", "do", "você"]),
+    ("nl", &["de", "het", "een", "en", "is", "niet", "dat", "ik", "je", "van"]),
+];
+
+/// Best-effort source language guess (ISO 639-1).
+pub(crate) fn detect_language(text: &str) -> Option<&'static str> {
+    // Non-Latin scripts are unambiguous enough to decide by character range.
+    let mut kana = false;
+    for c in text.chars() {
+        let lang = match c as u32 {
+            0x3040..=0x30FF => {
+                kana = true;
+                continue;
+            }
+            0x0400..=0x04FF => "ru",
+            0x0370..=0x03FF => "el",
+            0x0590..=0x05FF => "he",
+            0x0600..=0x06FF => "ar",
+            0x0900..=0x097F => "hi",
+            0x0E00..=0x0E7F => "th",
+            0xAC00..=0xD7AF => "ko",
+            0x4E00..=0x9FFF => {
+                if kana {
+                    "ja"
+                } else {
+                    "zh"
+                }
+            }
+            _ => continue,
+        };
+        return Some(lang);
+    }
+    if kana {
+        return Some("ja");
+    }
+
+    let lower = text.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+        .collect();
+    STOP_WORDS
+        .iter()
+        .map(|(lang, stops)| (*lang, words.iter().filter(|w| stops.contains(w)).count()))
+        .filter(|&(_, hits)| hits > 0)
+        .max_by_key(|&(_, hits)| hits)
+        .map(|(lang, _)| lang)
+}
+
+// ---------------------------------------------------------------------------
+// Providers
+// ---------------------------------------------------------------------------
+
+#[derive(Serialize)]
+struct RemoteRequest<'a> {
+    q: &'a str,
+    source: &'a str,
+    target: &'a str,
+    format: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api_key: Option<&'a str>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteResponse {
+    translated_text: String,
+    detected_language: Option<RemoteDetected>,
+}
+
+#[derive(Deserialize)]
+struct RemoteDetected {
+    language: String,
+}
+
+async fn translate_remote(
+    endpoint: &str,
+    api_key: Option<&str>,
+    text: &str,
+    target: &str,
+) -> Result<Translation, String> {
+    let url = format!("{}/translate", endpoint.trim_end_matches('/'));
+    let body = serde_json::to_vec(&RemoteRequest {
+        q: text,
+        source: "auto",
+        target,
+        format: "text",
+        api_key,
+    })
+    .map_err(|e| e.to_string())?;
+
+    let response = reqwest::Client::new()
+        .post(url)
+        .header("Content-Type", "application/json")
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| format!("translation request failed: {e}"))?;
+    let status = response.status();
+    let bytes = response.bytes().await.map_err(|e| e.to_string())?;
+    if !status.is_success() {
+        return Err(format!(
+            "translation endpoint returned {status}: {}",
+            String::from_utf8_lossy(&bytes)
+        ));
+    }
+
+    let parsed: RemoteResponse = serde_json::from_slice(&bytes).map_err(|e| e.to_string())?;
+    Ok(Translation {
+        text: parsed.translated_text,
+        detected_source: parsed.detected_language.map(|d| d.language),
+        provider: "remote",
+    })
+}
+
+/// Resolve a local translator to the absolute path of an existing file,
+/// searching `PATH` for a bare program name.
+fn resolve_command(command: &str) -> Result<PathBuf, String> {
+    let path = Path::new(command);
+    let candidates: Vec<PathBuf> = if path.components().count() > 1 {
+        vec![path.to_path_buf()]
+    } else {
+        // Windows finds `argos-translate` as `argos-translate.exe` etc.
+        let mut extensions = vec![String::new()];
+        if cfg!(target_os = "windows") {
+            let pathext = std::env::var("PATHEXT").unwrap_or_else(|_| ".EXE;.CMD;.BAT".into());
+            extensions.extend(pathext.split(';').map(str::to_string));
+        }
+        let dirs = std::env::var_os("PATH").unwrap_or_default();
+        std::env::split_paths(&dirs)
+            .flat_map(|dir| {
+                extensions
+                    .iter()
+                    .map(move |ext| dir.join(format!("{command}{ext}")))
+            })
+            .collect()
+    };
+    candidates
+        .into_iter()
+        .find(|candidate| candidate.is_file())
+        .and_then(|found| std::path::absolute(found).ok())
+        .ok_or_else(|| format!("{command} was not found"))
+}
+
+/// Whether `lang` looks like a language code (`de`, `pt-BR`), so it can be
+/// passed as an argument.
+fn is_language_code(lang: &str) -> bool {
+    !lang.is_empty()
+        && lang.len() <= 8
+        && !lang.starts_with('-')
+        && lang.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+}
+
+fn translate_local(command: &str, text: &str, target: &str) -> Result<Translation, String> {
+    if !is_language_code(target) {
+        return Err(format!("invalid target language {target:?}"));
+    }
+    let source = detect_language(text).ok_or("could not detect the source language")?;
+    if source == target {
+        return Ok(Translation {
+            text: text.to_string(),
+            detected_source: Some(source.to_string()),
+            provider: "local",
+        });
+    }
+
+    let mut child = Command::new(command)
+        .args(["--from", source, "--to", target])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to run {command}: {e}"))?;
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    // Write from another thread: a long message can fill the pipe while
+    // the translator is already blocked writing its output.
+    let output = std::thread::scope(|scope| {
+        scope.spawn(move || {
+            let _ = stdin.write_all(text.as_bytes());
+        });
+        child.wait_with_output()
+    })
+    .map_err(|e| format!("failed to run {command}: {e}"))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(Translation {
+        text: String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        detected_source: Some(source.to_string()),
+        provider: "local",
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Select and persist the translation provider (`None` disables translation).
+#[tauri::command]
+pub(crate) fn set_translation_provider(
+    mut provider: Option<TranslationProvider>,
+) -> Result<(), String> {
+    if let Some(TranslationProvider::Local { command }) = &mut provider {
+        let resolved = resolve_command(command)?;
+        *command = resolved.to_string_lossy().into_owned();
+        audit::record("programConfigured", "set_translation_provider", command.as_str());
+    }
+    store::save(STORE_DOC, &provider)
+}
+
+/// Translate `text` into `target_lang` (ISO 639-1) with the configured
+/// provider, auto-detecting the source language.
+#[tauri::command]
+pub(crate) async fn translate_text(text: String, target_lang: String) -> Result<Translation, String> {
+    let key = cache_key(&text, &target_lang);
+    if let Some(hit) = cache_get(key) {
+        return Ok(hit);
+    }
+
+    let provider: Option<TranslationProvider> = store::load(STORE_DOC);
+    let translation = match provider.ok_or("no translation provider configured")? {
+        TranslationProvider::Remote { endpoint, api_key } => {
+            audit::record("networkRequest", "translate_text", &endpoint);
+            translate_remote(&endpoint, api_key.as_deref(), &text, &target_lang).await?
+        }
+        TranslationProvider::Local { command } => {
+            audit::record("processLaunched", "translate_text", &command);
+            let (text, target) = (text.clone(), target_lang.clone());
+            tauri::async_runtime::spawn_blocking(move || translate_local(&command, &text, &target))
+                .await
+                .map_err(|e| e.to_string())??
+        }
+    };
+
+    cache_put(key, translation.clone());
+    Ok(translation)
+}
+
+/// Guess the language of `text` (ISO 639-1), if possible.
+#[tauri::command]
+pub(crate) fn detect_text_language(text: String) -> Option<&'static str> {
+    detect_language(&text)
+}
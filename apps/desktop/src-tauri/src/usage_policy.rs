@@ -0,0 +1,297 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+use crate::notifications::parse_time;
+use crate::sha256::{self, Sha256};
+use crate::{scheduler, store, timezone, APP_HANDLE};
+
+// ===========================================================================
+// Usage Policy (Quiet Hours)
+// ===========================================================================
+//
+// Optional local time limits for shared or family computers. A policy is a
+// set of weekly quiet-hour windows during which the app suppresses
+// notifications (`queue_digest_notification` drops them) and, optionally,
+// refuses voice: the frontend checks `is_voice_allowed()` before joining.
+//
+// Changing or disabling a policy requires its PIN once one is set. There is
+// no OS keychain integration yet (see tasks/todo.md), so the PIN is kept in
+// the native store as a salted, iterated SHA-256 hash — enough to keep it
+// out of plain sight, not a defence against someone with access to the
+// user's files. Hashing runs outside the state lock and the digests are
+// compared in constant time. Repeated wrong PINs lock changes out for a few
+// minutes; the count and the lockout are kept in the same document, so
+// relaunching doesn't reset them.
+//
+// Window times are local wall-clock times in the OS zone (see `timezone`),
+// so they follow DST and zone changes.
+//
+// The scheduler fires at every window boundary and emits
+// `usage-policy-quiet-changed` (bool) so the UI can show/hide its banner.
+// ===========================================================================
+
+const STORE_DOC: &str = "usage-policy";
+const SCHEDULER_KEY: &str = "usage-policy:boundary";
+const MS_PER_MINUTE: i64 = 60_000;
+const MS_PER_DAY: i64 = 24 * 60 * MS_PER_MINUTE;
+const PIN_ITERATIONS: u32 = 10_000;
+const MAX_PIN_FAILURES: u32 = 5;
+const LOCKOUT_MS: u64 = 5 * 60 * 1000;
+
+/// A recurring quiet period. Windows whose end is before their start run
+/// past midnight into the next day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct QuietWindow {
+    /// Days the window starts on, 0 = Sunday … 6 = Saturday.
+    pub days: Vec<u8>,
+    /// Local "HH:MM".
+    pub start: String,
+    pub end: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct UsagePolicy {
+    pub enabled: bool,
+    pub quiet_hours: Vec<QuietWindow>,
+    pub suppress_notifications: bool,
+    pub block_voice: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct PolicyState {
+    policy: UsagePolicy,
+    pin_salt: Option<String>,
+    pin_hash: Option<String>,
+    /// Consecutive wrong PINs.
+    pin_failures: u32,
+    /// When the wrong-PIN lockout ends, in ms since the epoch (0 if none).
+    locked_until_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UsagePolicyStatus {
+    pub policy: UsagePolicy,
+    pub pin_set: bool,
+    pub quiet_now: bool,
+}
+
+static STATE: Mutex<Option<PolicyState>> = Mutex::new(None);
+
+fn with_state<R>(f: impl FnOnce(&mut PolicyState) -> R) -> R {
+    let mut guard = STATE.lock().unwrap();
+    f(guard.get_or_insert_with(|| store::load(STORE_DOC)))
+}
+
+fn hash_pin(salt: &str, pin: &str) -> String {
+    let mut digest = {
+        let mut hasher = Sha256::new();
+        hasher.update(salt.as_bytes());
+        hasher.update(pin.as_bytes());
+        hasher.finalize()
+    };
+    for _ in 1..PIN_ITERATIONS {
+        let mut hasher = Sha256::new();
+        hasher.update(&digest);
+        hasher.update(salt.as_bytes());
+        digest = hasher.finalize();
+    }
+    sha256::hex(&digest)
+}
+
+/// The stored (salt, hash), or `None` if no PIN is set. Fails during the
+/// wrong-PIN lockout.
+fn stored_pin(state: &PolicyState) -> Result<Option<(String, String)>, String> {
+    let (Some(salt), Some(hash)) = (&state.pin_salt, &state.pin_hash) else {
+        return Ok(None);
+    };
+    if state.locked_until_ms > scheduler::now_ms() {
+        return Err("too many wrong PINs; try again in a few minutes".into());
+    }
+    Ok(Some((salt.clone(), hash.clone())))
+}
+
+/// Record a PIN check, applying the failure lockout. A wrong PIN is saved
+/// before returning its error.
+fn record_pin_attempt(state: &mut PolicyState, correct: bool) -> Result<(), String> {
+    if correct {
+        state.pin_failures = 0;
+        state.locked_until_ms = 0;
+        return Ok(());
+    }
+    state.pin_failures += 1;
+    if state.pin_failures >= MAX_PIN_FAILURES {
+        state.pin_failures = 0;
+        state.locked_until_ms = scheduler::now_ms() + LOCKOUT_MS;
+    }
+    store::save(STORE_DOC, state)?;
+    Err("incorrect PIN".into())
+}
+
+/// Local (day of week, minute of day) for a UTC instant.
+fn local_time(now_ms: u64) -> (i64, i64) {
+    let local = timezone::local_time_at((now_ms / 1000) as i64);
+    (
+        i64::from(local.weekday),
+        i64::from(local.hour * 60 + local.minute),
+    )
+}
+
+fn is_quiet(policy: &UsagePolicy, now_ms: u64) -> bool {
+    if !policy.enabled {
+        return false;
+    }
+    let (day, minute) = local_time(now_ms);
+    let yesterday = (day + 6) % 7;
+    policy.quiet_hours.iter().any(|w| {
+        let (Some(start), Some(end)) = (parse_time(&w.start), parse_time(&w.end)) else {
+            return false;
+        };
+        let on = |d: i64| w.days.iter().any(|&x| i64::from(x) == d);
+        if start <= end {
+            on(day) && (start..end).contains(&minute)
+        } else {
+            (on(day) && minute >= start) || (on(yesterday) && minute < end)
+        }
+    })
+}
+
+/// Next window start or end after `now_ms`, within the coming week.
+fn next_boundary(policy: &UsagePolicy, now_ms: u64) -> Option<u64> {
+    if !policy.enabled {
+        return None;
+    }
+    let now = now_ms as i64;
+    let offset = i64::from(timezone::utc_offset_at(now / 1000)) * 1000;
+    let local_midnight = (now + offset).div_euclid(MS_PER_DAY) * MS_PER_DAY;
+
+    (0..=8)
+        .flat_map(|day| {
+            policy.quiet_hours.iter().flat_map(move |w| {
+                [parse_time(&w.start), parse_time(&w.end)]
+                    .into_iter()
+                    .flatten()
+                    .map(move |m| {
+                        let local = local_midnight + day * MS_PER_DAY + m * MS_PER_MINUTE;
+                        timezone::local_to_utc(local / 1000) * 1000
+                    })
+            })
+        })
+        .filter(|&t| t > now)
+        .min()
+        .map(|t| t as u64)
+}
+
+fn arm(policy: &UsagePolicy) {
+    match next_boundary(policy, scheduler::now_ms()) {
+        Some(at) => scheduler::schedule(SCHEDULER_KEY, at, on_boundary),
+        None => scheduler::cancel(SCHEDULER_KEY),
+    }
+}
+
+/// Scheduler task — announce the new state and arm the next boundary.
+fn on_boundary(_key: &str) {
+    let (quiet, policy) = with_state(|state| {
+        (
+            is_quiet(&state.policy, scheduler::now_ms()),
+            state.policy.clone(),
+        )
+    });
+    if let Some(handle) = APP_HANDLE.get() {
+        let _ = handle.emit("usage-policy-quiet-changed", quiet);
+    }
+    arm(&policy);
+}
+
+/// Whether notifications should be dropped right now.
+pub(crate) fn notifications_suppressed() -> bool {
+    with_state(|state| {
+        state.policy.suppress_notifications && is_quiet(&state.policy, scheduler::now_ms())
+    })
+}
+
+/// Arm the boundary timer at startup.
+pub(crate) fn init() {
+    let policy = with_state(|state| state.policy.clone());
+    arm(&policy);
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Replace the usage policy. `pin` is required once a PIN has been set;
+/// `new_pin` sets or changes it (an empty string removes it).
+#[tauri::command]
+pub(crate) fn set_usage_policy(
+    policy: UsagePolicy,
+    pin: Option<String>,
+    new_pin: Option<String>,
+) -> Result<(), String> {
+    for w in &policy.quiet_hours {
+        if parse_time(&w.start).is_none() || parse_time(&w.end).is_none() {
+            return Err(format!("invalid quiet hours: {}–{}", w.start, w.end));
+        }
+        if w.days.iter().any(|&d| d > 6) {
+            return Err("quiet hour days must be 0 (Sunday) to 6 (Saturday)".into());
+        }
+    }
+
+    // Hash outside the lock: the iterations take a noticeable while.
+    let stored = with_state(|state| stored_pin(state))?;
+    let correct = stored.as_ref().map(|(salt, hash)| {
+        pin.as_deref()
+            .is_some_and(|pin| sha256::ct_eq(hash_pin(salt, pin).as_bytes(), hash.as_bytes()))
+    });
+    let new_pin = new_pin.map(|new_pin| {
+        (!new_pin.is_empty()).then(|| {
+            let salt = store::new_id();
+            (hash_pin(&salt, &new_pin), salt)
+        })
+    });
+
+    let policy = with_state(|state| {
+        // Checked against a PIN that has since been changed.
+        if state.pin_salt != stored.as_ref().map(|(salt, _)| salt.clone()) {
+            return Err("the PIN was changed; try again".to_string());
+        }
+        if let Some(correct) = correct {
+            record_pin_attempt(state, correct)?;
+        }
+        if let Some(new_pin) = new_pin {
+            (state.pin_hash, state.pin_salt) = new_pin.unzip();
+        }
+        state.policy = policy;
+        store::save(STORE_DOC, state).map(|_| state.policy.clone())
+    })?;
+
+    arm(&policy);
+    if let Some(handle) = APP_HANDLE.get() {
+        let _ = handle.emit(
+            "usage-policy-quiet-changed",
+            is_quiet(&policy, scheduler::now_ms()),
+        );
+    }
+    Ok(())
+}
+
+/// The current policy and whether quiet hours are in effect.
+#[tauri::command]
+pub(crate) fn get_usage_policy() -> UsagePolicyStatus {
+    with_state(|state| UsagePolicyStatus {
+        policy: state.policy.clone(),
+        pin_set: state.pin_hash.is_some(),
+        quiet_now: is_quiet(&state.policy, scheduler::now_ms()),
+    })
+}
+
+/// Whether joining voice is allowed right now.
+#[tauri::command]
+pub(crate) fn is_voice_allowed() -> bool {
+    with_state(|state| !(state.policy.block_voice && is_quiet(&state.policy, scheduler::now_ms())))
+}
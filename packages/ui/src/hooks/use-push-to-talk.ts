@@ -295,9 +295,10 @@ export function usePushToTalk({
       // --- Try WH_KEYBOARD_LL hook first (Windows) ---
       if (invoke && listen && vkCode !== null) {
         try {
-          const success = (await invoke('start_ptt_hook', { keyCode: vkCode })) as boolean;
+          // Rejects with a PttError ({ code, message }) if the hook can't start.
+          await invoke('start_ptt_hook', { keyCode: vkCode });
 
-          if (success && !cancelled) {
+          if (!cancelled) {
             hookActive = true;
 
             // Listen for hook-emitted press/release events
@@ -339,7 +340,7 @@ export function usePushToTalk({
             return;
           }
         } catch {
-          // start_ptt_hook command not available — fall through
+          // start_ptt_hook unavailable or the hook failed — fall through
         }
       }
 